@@ -1,18 +1,62 @@
+use audio_widgets::compressor::*;
+use audio_widgets::deesser::*;
+use audio_widgets::dynamics::*;
+use audio_widgets::limiter::*;
+use audio_widgets::multiband::*;
 use yew::*;
 
-pub struct CompressorView {}
+pub struct CompressorView {
+    link: ComponentLink<Self>,
+    compressor: CompressorModel,
+    multiband: MultibandModel,
+    selected_band: usize,
+    limiter: LimiterModel,
+    limiter_history: LimiterHistory,
+    deesser: DeEsserModel,
+}
+
+pub enum Msg {
+    Compressor(CompressorParameter),
+    Multiband(MultibandParameter),
+    SelectBand(usize),
+    Band(CompressorParameter),
+    Limiter(LimiterParameter),
+    DeEsser(DeEsserParameter),
+}
 
 impl Component for CompressorView {
-    type Message = ();
+    type Message = Msg;
 
     type Properties = ();
 
-    fn create(_props: Self::Properties, _link: ComponentLink<Self>) -> Self {
-        CompressorView {}
+    fn create(_props: Self::Properties, link: ComponentLink<Self>) -> Self {
+        CompressorView {
+            link,
+            compressor: CompressorModel::default(),
+            multiband: MultibandModel::default(),
+            selected_band: 0,
+            limiter: LimiterModel::default(),
+            limiter_history: limiter_history(&LimiterModel::default()),
+            deesser: DeEsserModel::default(),
+        }
     }
 
-    fn update(&mut self, _msg: Self::Message) -> ShouldRender {
-        false
+    fn update(&mut self, msg: Self::Message) -> ShouldRender {
+        match msg {
+            Msg::Compressor(change) => self.compressor.update(change),
+            Msg::Multiband(change) => self.multiband.update(change),
+            Msg::SelectBand(band) => self.selected_band = band,
+            Msg::Band(change) => self
+                .multiband
+                .update(MultibandParameter::Band(self.selected_band, change)),
+            Msg::Limiter(change) => {
+                self.limiter.update(change);
+                self.limiter_history = limiter_history(&self.limiter);
+            }
+            Msg::DeEsser(change) => self.deesser.update(change),
+        }
+        self.selected_band = self.selected_band.min(self.multiband.bands.len() - 1);
+        true
     }
 
     fn change(&mut self, _props: Self::Properties) -> ShouldRender {
@@ -20,10 +64,108 @@ impl Component for CompressorView {
     }
 
     fn view(&self) -> Html {
+        // a fake reading, so the bars have something to show
+        let gain_reduction: Vec<f64> = self
+            .multiband
+            .bands
+            .iter()
+            .map(|band| band.compressor.gain_computer(-6.0))
+            .collect();
+        let band = &self.multiband.bands[self.selected_band];
+        let sibilance_level = -20.0;
+
         html! {
             <div class="view compressor-view">
-                <h1>{"Coming soon..."}</h1>
+                <Compressor
+                    id="compressor"
+                    compressor={self.compressor.clone()}
+                    input_level={None}
+                    width={400.0}
+                    height={400.0}
+                    show_sidechain={true}
+                    on_input={Some(self.link.callback(Msg::Compressor))}
+                />
+                <div class="caption">
+                {"A compressor transfer curve. Drag the threshold point along the curve, drag the ratio handle up or down to change the ratio and scroll to adjust the knee. The side chain filter below is switched on and off with the SC button and shapes the envelope simulation too."}
+                </div>
+                <CompressorEnvelope
+                    id="compressor-envelope"
+                    compressor={self.compressor.clone()}
+                    signal={TestSignal::default()}
+                    duration={0.6}
+                    gain_range={24.0}
+                    width={400.0}
+                    height={150.0}
+                />
+                <div class="caption">
+                {"The compressor's response to a tone burst: the signal level in gray, the output level and the gain change below it."}
+                </div>
+                <Multiband
+                    id="multiband"
+                    multiband={self.multiband.clone()}
+                    gain_reduction={gain_reduction}
+                    selected={Some(self.selected_band)}
+                    width={600.0}
+                    height={200.0}
+                    on_input={Some(self.link.callback(Msg::Multiband))}
+                    on_select={Some(self.link.callback(Msg::SelectBand))}
+                />
+                <Compressor
+                    id="multiband-compressor"
+                    compressor={band.compressor.clone()}
+                    input_level={Some(-6.0)}
+                    width={300.0}
+                    height={300.0}
+                    show_sidechain={false}
+                    on_input={Some(self.link.callback(Msg::Band))}
+                />
+                <div class="caption">
+                {"A multiband compressor. Drag the crossovers to move them, double click to add or remove a crossover, right click to bypass a band and scroll over a band to adjust its threshold. Click a band to edit its compressor below."}
+                </div>
+                <Limiter
+                    id="limiter"
+                    limiter={self.limiter.clone()}
+                    history={self.limiter_history.clone()}
+                    width={400.0}
+                    height={200.0}
+                    on_input={Some(self.link.callback(Msg::Limiter))}
+                />
+                <div class="caption">
+                {"A limiter processing a series of decaying tone bursts, with input, gain reduction and output meters. Drag the threshold or the ceiling line, scroll to adjust the release and shift-scroll to adjust the input gain."}
+                </div>
+                <DeEsser
+                    id="deesser"
+                    deesser={self.deesser.clone()}
+                    gain_reduction={self.deesser.gain(sibilance_level)}
+                    detector_level={Some(sibilance_level)}
+                    width={500.0}
+                    height={150.0}
+                    on_input={Some(self.link.callback(Msg::DeEsser))}
+                />
+                <div class="caption">
+                {"A de-esser fed a constant level in its detection band. Drag the center line to move the band, drag in the level strip on the right to set the threshold, scroll to adjust the bandwidth and shift-scroll to adjust the range. The button switches between split band and wideband reduction."}
+                </div>
             </div>
         }
     }
 }
+
+/// Runs two seconds of decaying tone bursts through a limiter.
+fn limiter_history(limiter: &LimiterModel) -> LimiterHistory {
+    let sample_rate = 48_000.0;
+    let block_size = 240;
+    let mut processor = LimiterProcessor::new(limiter, 1, sample_rate);
+    let mut history = LimiterHistory::new(400);
+    let signal: Vec<f32> = (0..96_000)
+        .map(|i| {
+            let t = i as f64 / sample_rate;
+            let decay = (-(t % 0.25) * 20.0).exp();
+            let level = if (t % 1.0) < 0.5 { 1.0 } else { 0.5 };
+            (level * decay * (2.0 * std::f64::consts::PI * 220.0 * t).sin()) as f32
+        })
+        .collect();
+    for block in signal.chunks(block_size) {
+        history.push(processor.process_block(&[block]));
+    }
+    history
+}
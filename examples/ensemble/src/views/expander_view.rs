@@ -1,18 +1,39 @@
+use audio_widgets::expander::*;
 use yew::*;
 
-pub struct ExpanderView {}
+/// A fixed input level, so the gate indicator reacts to threshold changes.
+const INPUT_LEVEL: f64 = -30.0;
+
+pub struct ExpanderView {
+    link: ComponentLink<Self>,
+    expander: ExpanderModel,
+    gate: ExpanderModel,
+}
+
+pub enum Msg {
+    Expander(ExpanderParameter),
+    Gate(ExpanderParameter),
+}
 
 impl Component for ExpanderView {
-    type Message = ();
+    type Message = Msg;
 
     type Properties = ();
 
-    fn create(_props: Self::Properties, _link: ComponentLink<Self>) -> Self {
-        ExpanderView {}
+    fn create(_props: Self::Properties, link: ComponentLink<Self>) -> Self {
+        ExpanderView {
+            link,
+            expander: ExpanderModel::default(),
+            gate: ExpanderModel::gate(-40.0, 60.0, 6.0),
+        }
     }
 
-    fn update(&mut self, _msg: Self::Message) -> ShouldRender {
-        false
+    fn update(&mut self, msg: Self::Message) -> ShouldRender {
+        match msg {
+            Msg::Expander(change) => self.expander.update(change),
+            Msg::Gate(change) => self.gate.update(change),
+        }
+        true
     }
 
     fn change(&mut self, _props: Self::Properties) -> ShouldRender {
@@ -22,7 +43,32 @@ impl Component for ExpanderView {
     fn view(&self) -> Html {
         html! {
             <div class="view expander-view">
-                <h1>{"Coming soon..."}</h1>
+                <Expander
+                    id="expander"
+                    expander={self.expander.clone()}
+                    input_level={None}
+                    gate_open={None}
+                    width={400.0}
+                    height={400.0}
+                    show_sidechain={true}
+                    on_input={Some(self.link.callback(Msg::Expander))}
+                />
+                <div class="caption">
+                {"A downward expander. Drag the threshold point along the curve, drag the ratio handle up or down to change the ratio and scroll to adjust the range. Shift-scroll adjusts the hysteresis, alt-scroll the knee. The side chain filter below is switched on and off with the SC button."}
+                </div>
+                <Expander
+                    id="gate"
+                    expander={self.gate.clone()}
+                    input_level={Some(INPUT_LEVEL)}
+                    gate_open={Some(self.gate.is_open(INPUT_LEVEL, false))}
+                    width={400.0}
+                    height={400.0}
+                    show_sidechain={false}
+                    on_input={Some(self.link.callback(Msg::Gate))}
+                />
+                <div class="caption">
+                {"A gate with hysteresis, fed with a constant level of -30 dB. The indicator shows whether the gate is open."}
+                </div>
             </div>
         }
     }
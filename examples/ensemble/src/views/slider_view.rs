@@ -1,18 +1,79 @@
+use audio_widgets::eq::*;
+use audio_widgets::knob::{DragMode, Knob, KnobModel};
+use audio_widgets::scale::{HorizontalPosition, Layout, ScaleModel};
+use audio_widgets::slider::*;
+use audio_widgets::*;
 use yew::*;
 
-pub struct SliderView {}
+pub struct SliderView {
+    link: ComponentLink<Self>,
+    frequency: SliderModel<FreqScale>,
+    gain: SliderModel<GainScale>,
+    knob_gain: KnobModel<GainScale>,
+    knob_frequency: KnobModel<FreqScale>,
+    knob_q: KnobModel<QScale>,
+}
+
+pub enum Msg {
+    Frequency(SliderValue),
+    Gain(SliderValue),
+    KnobGain(KnobValue),
+    KnobFrequency(KnobValue),
+    KnobQ(KnobValue),
+}
 
 impl Component for SliderView {
-    type Message = ();
+    type Message = Msg;
 
     type Properties = ();
 
-    fn create(_props: Self::Properties, _link: ComponentLink<Self>) -> Self {
-        SliderView {}
+    fn create(_props: Self::Properties, link: ComponentLink<Self>) -> Self {
+        let frequency_scale = ScaleModel::new(
+            FreqScale::new(20.0, 20_000.0),
+            Layout::Horizontal(HorizontalPosition::Bottom),
+            Some(1_000.0),
+            MAJOR_FREQUENCY_MARKERS.to_vec(),
+            MINOR_FREQUENCY_MARKERS.to_vec(),
+        );
+        let gain_scale = ScaleModel::new(
+            GainScale::new(-18.0, 18.0),
+            Layout::Horizontal(HorizontalPosition::Bottom),
+            Some(0.0),
+            MAJOR_GAIN_MARKERS.to_vec(),
+            MINOR_GAIN_MARKERS.to_vec(),
+        );
+        let q_scale = ScaleModel::new(
+            QScale::new(0.1, 10.0),
+            Layout::Horizontal(HorizontalPosition::Bottom),
+            Some(0.71),
+            vec![],
+            vec![],
+        );
+
+        let mut knob_frequency = KnobModel::new(frequency_scale.clone());
+        knob_frequency.drag_mode = DragMode::Circular;
+        let mut knob_q = KnobModel::new(q_scale);
+        knob_q.set_sweep(300.0);
+
+        SliderView {
+            link,
+            frequency: SliderModel::new(frequency_scale),
+            knob_gain: KnobModel::bipolar(gain_scale.clone()),
+            gain: SliderModel::bipolar(gain_scale),
+            knob_frequency,
+            knob_q,
+        }
     }
 
-    fn update(&mut self, _msg: Self::Message) -> ShouldRender {
-        false
+    fn update(&mut self, msg: Self::Message) -> ShouldRender {
+        match msg {
+            Msg::Frequency(value) => self.frequency.update(value),
+            Msg::Gain(value) => self.gain.update(value),
+            Msg::KnobGain(value) => self.knob_gain.update(value),
+            Msg::KnobFrequency(value) => self.knob_frequency.update(value),
+            Msg::KnobQ(value) => self.knob_q.update(value),
+        }
+        true
     }
 
     fn change(&mut self, _props: Self::Properties) -> ShouldRender {
@@ -20,9 +81,57 @@ impl Component for SliderView {
     }
 
     fn view(&self) -> Html {
+        let frequency = Props::regular(
+            self.frequency.clone(),
+            self.link.callback(Msg::Frequency),
+            "Frequency".to_owned(),
+            LabelFormat::FrequencyShort(true),
+        );
+        let gain = Props::regular(
+            self.gain.clone(),
+            self.link.callback(Msg::Gain),
+            "Gain".to_owned(),
+            LabelFormat::GainShort(true),
+        );
         html! {
             <div class="view slider-view">
-                <h1>{"Coming soon..."}</h1>
+                <Slider<FreqScale> with frequency />
+                <div class="caption">
+                {"A frequency slider. Drag the knob or scroll to change the value and double click to reset it."}
+                </div>
+                <Slider<GainScale> with gain />
+                <div class="caption">
+                {"A bipolar gain slider. The track fills from the centre and the knob sticks to the centre detent when moved across it."}
+                </div>
+                <div class="knobs">
+                    <Knob<GainScale>
+                        id="knob-gain"
+                        knob={self.knob_gain.clone()}
+                        label={"Gain"}
+                        value_format={LabelFormat::Gain(true)}
+                        size={64.0}
+                        on_input={Some(self.link.callback(Msg::KnobGain))}
+                    />
+                    <Knob<FreqScale>
+                        id="knob-frequency"
+                        knob={self.knob_frequency.clone()}
+                        label={"Frequency"}
+                        value_format={LabelFormat::Frequency(true)}
+                        size={64.0}
+                        on_input={Some(self.link.callback(Msg::KnobFrequency))}
+                    />
+                    <Knob<QScale>
+                        id="knob-q"
+                        knob={self.knob_q.clone()}
+                        label={"Q"}
+                        value_format={LabelFormat::Q}
+                        size={64.0}
+                        on_input={Some(self.link.callback(Msg::KnobQ))}
+                    />
+                </div>
+                <div class="caption">
+                {"Rotary knobs: a bipolar gain knob, a frequency knob that follows the pointer around its centre and a Q knob with a wider sweep. Drag up and down (hold shift for fine adjustment) or scroll to turn them and double click to reset them."}
+                </div>
             </div>
         }
     }
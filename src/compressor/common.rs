@@ -0,0 +1,382 @@
+use crate::dynamics::*;
+use crate::eq::{EqModel, Parameter};
+use crate::*;
+
+pub const MAX_RATIO: f64 = 40.0;
+pub const MAX_KNEE: Gain = 24.0;
+pub const MAX_MAKEUP: Gain = 24.0;
+pub const MAX_ATTACK: Seconds = 0.5;
+pub const MAX_HOLD: Seconds = 0.5;
+pub const MAX_RELEASE: Seconds = 5.0;
+
+#[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CompressorModel {
+    pub threshold: Gain,
+    pub ratio: f64,
+    /// Width of the soft knee in dB, centered on the threshold. 0 means hard knee.
+    pub knee: Gain,
+    pub makeup: Gain,
+    /// Replaces the makeup gain by one compensating half the gain reduction at 0 dBFS.
+    pub auto_makeup: bool,
+    pub detector: Detector,
+    pub time_constants: TimeConstants,
+    /// Filters the signal feeding the detector, e.g. to keep low frequencies from triggering
+    /// gain changes.
+    pub sidechain: EqModel,
+    /// The lowest level shown in the transfer curve plot.
+    pub min_level: Gain,
+    /// The highest level shown in the transfer curve plot.
+    pub max_level: Gain,
+}
+
+impl CompressorModel {
+    pub fn new(threshold: Gain, ratio: f64, knee: Gain, makeup: Gain) -> CompressorModel {
+        CompressorModel {
+            threshold,
+            ratio,
+            knee,
+            makeup,
+            auto_makeup: false,
+            detector: Detector::Peak,
+            time_constants: TimeConstants::default(),
+            sidechain: EqModel::sidechain(),
+            min_level: -60.0,
+            max_level: 0.0,
+        }
+    }
+
+    /// The static gain change in dB at the given input level, excluding makeup gain.
+    pub fn gain_computer(&self, input: Gain) -> Gain {
+        let ratio = self.ratio.max(1.0);
+        knee(input, self.threshold, self.knee, 1.0 / ratio - 1.0)
+    }
+
+    pub fn update(&mut self, change: CompressorParameter) {
+        match change {
+            CompressorParameter::Threshold(threshold) => {
+                self.threshold = threshold.max(self.min_level).min(self.max_level)
+            }
+            CompressorParameter::Ratio(ratio) => self.ratio = ratio.clamp(1.0, MAX_RATIO),
+            CompressorParameter::Knee(knee) => self.knee = knee.clamp(0.0, MAX_KNEE),
+            CompressorParameter::Makeup(makeup) => {
+                self.makeup = makeup.clamp(-MAX_MAKEUP, MAX_MAKEUP)
+            }
+            CompressorParameter::AutoMakeup(auto_makeup) => self.auto_makeup = auto_makeup,
+            CompressorParameter::Detector(detector) => self.detector = detector,
+            CompressorParameter::Attack(attack) => {
+                self.time_constants.attack = attack.clamp(0.0, MAX_ATTACK)
+            }
+            CompressorParameter::Hold(hold) => self.time_constants.hold = hold.clamp(0.0, MAX_HOLD),
+            CompressorParameter::Release(release) => {
+                self.time_constants.release = release.clamp(0.0, MAX_RELEASE)
+            }
+            CompressorParameter::ProgramDependentRelease(program_dependent) => {
+                self.time_constants.program_dependent_release = program_dependent
+            }
+            CompressorParameter::Sidechain(index, change) => self.sidechain.update(index, change),
+            CompressorParameter::SidechainActive(active) => self.sidechain.active = active,
+        }
+    }
+
+    /// The input level of the handle used to adjust the ratio, halfway between the threshold and
+    /// the top of the plot. With auto makeup the makeup gain compensates ratio changes there, so
+    /// the handle moves to the top of the plot.
+    pub fn ratio_handle_level(&self) -> Gain {
+        if self.auto_makeup {
+            self.max_level
+        } else {
+            self.threshold + (self.max_level - self.threshold) / 2.0
+        }
+    }
+
+    /// The ratio that moves the curve through `output` at the ratio handle's input level,
+    /// including the knee and the makeup gain. Keeps the current ratio if the handle's output
+    /// does not depend on the ratio.
+    pub fn ratio_for_handle(&self, output: Gain) -> f64 {
+        let input = self.ratio_handle_level();
+        // the gain change is the slope 1 / ratio - 1 times a shape that only depends on the knee
+        let shape = |level: Gain| knee(level, self.threshold, self.knee, 1.0);
+        let (sensitivity, makeup) = if self.auto_makeup {
+            (shape(input) - shape(0.0) / 2.0, 0.0)
+        } else {
+            (shape(input), self.makeup)
+        };
+        if sensitivity <= f64::EPSILON {
+            return self.ratio;
+        }
+        let slope = (output - input - makeup) / sensitivity;
+        if slope <= 1.0 / MAX_RATIO - 1.0 {
+            MAX_RATIO
+        } else {
+            (1.0 / (1.0 + slope)).clamp(1.0, MAX_RATIO)
+        }
+    }
+
+    pub fn envelope_follower(&self, sample_rate: f64) -> EnvelopeFollower {
+        EnvelopeFollower::new(
+            sample_rate,
+            self.detector.clone(),
+            self.time_constants.clone(),
+        )
+    }
+
+    /// Runs the compressor's side chain against `signal` for `duration` seconds.
+    pub fn simulate(&self, signal: &TestSignal, duration: Seconds, sample_rate: f64) -> Simulation {
+        let mut follower = self.envelope_follower(sample_rate);
+        simulate(self, &mut follower, Some(&self.sidechain), signal, duration)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum CompressorParameter {
+    Threshold(Gain),
+    Ratio(f64),
+    Knee(Gain),
+    Makeup(Gain),
+    AutoMakeup(bool),
+    Detector(Detector),
+    Attack(Seconds),
+    Hold(Seconds),
+    Release(Seconds),
+    ProgramDependentRelease(bool),
+    /// A change to a band of the side chain filter.
+    Sidechain(usize, Parameter),
+    SidechainActive(bool),
+}
+
+impl GainComputer for CompressorModel {
+    fn gain(&self, input: Gain) -> Gain {
+        self.gain_computer(input)
+    }
+
+    fn makeup(&self) -> Gain {
+        if self.auto_makeup {
+            -self.gain_computer(0.0) / 2.0
+        } else {
+            self.makeup
+        }
+    }
+
+    fn threshold(&self) -> Option<Gain> {
+        Some(self.threshold)
+    }
+}
+
+impl TransferCurveModel for CompressorModel {
+    type Parameter = CompressorParameter;
+
+    fn min_level(&self) -> Gain {
+        self.min_level
+    }
+
+    fn max_level(&self) -> Gain {
+        self.max_level
+    }
+
+    fn threshold_level(&self) -> Gain {
+        self.threshold
+    }
+
+    fn ratio(&self) -> f64 {
+        self.ratio
+    }
+
+    fn ratio_handle_level(&self) -> Gain {
+        self.ratio_handle_level()
+    }
+
+    fn ratio_for_handle(&self, output: Gain) -> f64 {
+        self.ratio_for_handle(output)
+    }
+
+    fn sidechain(&self) -> &EqModel {
+        &self.sidechain
+    }
+
+    fn update(&mut self, change: CompressorParameter) {
+        self.update(change)
+    }
+
+    fn threshold_change(threshold: Gain) -> CompressorParameter {
+        CompressorParameter::Threshold(threshold)
+    }
+
+    fn ratio_change(ratio: f64) -> CompressorParameter {
+        CompressorParameter::Ratio(ratio)
+    }
+
+    fn sidechain_change(index: usize, change: Parameter) -> CompressorParameter {
+        CompressorParameter::Sidechain(index, change)
+    }
+
+    fn sidechain_active_change(active: bool) -> CompressorParameter {
+        CompressorParameter::SidechainActive(active)
+    }
+
+    /// The mouse wheel adjusts the knee.
+    fn wheel_change(&self, step: f64, _shift: bool, _alt: bool) -> Option<CompressorParameter> {
+        let knee = (self.knee + step).clamp(0.0, MAX_KNEE);
+        Some(knee)
+            .filter(|k| (k - self.knee).abs() > f64::EPSILON)
+            .map(CompressorParameter::Knee)
+    }
+}
+
+impl Default for CompressorModel {
+    fn default() -> Self {
+        CompressorModel::new(-20.0, 4.0, 6.0, 0.0)
+    }
+}
+
+pub type CompressorGraph = TransferGraph;
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_hard_knee() {
+        let compressor = CompressorModel::new(-20.0, 4.0, 0.0, 2.0);
+
+        assert!(compressor.gain_computer(-30.0).abs() < f64::EPSILON);
+        assert!((compressor.output(-30.0) - -28.0).abs() < 1e-9);
+        assert!((compressor.gain_computer(0.0) - -15.0).abs() < 1e-9);
+        assert!((compressor.output(0.0) - -13.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_soft_knee_is_continuous() {
+        let compressor = CompressorModel::new(-20.0, 4.0, 10.0, 0.0);
+
+        assert!(compressor.gain_computer(-25.0).abs() < 1e-9);
+        assert!((compressor.gain_computer(-15.0) - -3.75).abs() < 1e-9);
+        // halfway into the knee the curve is below the hard knee's threshold point
+        assert!((compressor.gain_computer(-20.0) - -0.9375).abs() < 1e-9);
+        let curve: Vec<Gain> = (-300..=0)
+            .map(|i| compressor.output(i as f64 / 10.0))
+            .collect();
+        assert!(curve.windows(2).all(|w| w[1] > w[0]));
+    }
+
+    #[test]
+    fn test_ratio_handle() {
+        let mut compressor = CompressorModel::new(-30.0, 2.0, 0.0, 0.0);
+
+        assert!((compressor.ratio_handle_level() - -15.0).abs() < f64::EPSILON);
+        let output = compressor.output(compressor.ratio_handle_level());
+        assert!((compressor.ratio_for_handle(output) - 2.0).abs() < 1e-9);
+
+        compressor.update(CompressorParameter::Ratio(0.5));
+        assert!((compressor.ratio - 1.0).abs() < f64::EPSILON);
+        assert!((compressor.ratio_for_handle(-40.0) - MAX_RATIO).abs() < f64::EPSILON);
+
+        compressor.update(CompressorParameter::Threshold(12.0));
+        assert!(compressor.threshold.abs() < f64::EPSILON);
+    }
+
+    #[test]
+    fn test_ratio_handle_with_auto_makeup() {
+        let mut compressor = CompressorModel::new(-20.0, 4.0, 0.0, 0.0);
+        compressor.auto_makeup = true;
+
+        let handle = compressor.ratio_handle_level();
+        let output = compressor.output(handle);
+        assert!((compressor.ratio_for_handle(output) - 4.0).abs() < 1e-9);
+
+        // the handle still moves with the ratio, makeup included
+        compressor.update(CompressorParameter::Ratio(2.0));
+        let moved = compressor.output(handle);
+        assert!((moved - output).abs() > 1.0);
+        assert!((compressor.ratio_for_handle(moved) - 2.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_ratio_handle_within_wide_knee() {
+        let compressor = CompressorModel::new(-10.0, 3.0, 24.0, 2.0);
+
+        // the handle at -5 dB lies inside the knee
+        let handle = compressor.ratio_handle_level();
+        assert!(handle - compressor.threshold < compressor.knee / 2.0);
+        let output = compressor.output(handle);
+        assert!((compressor.ratio_for_handle(output) - 3.0).abs() < 1e-9);
+        assert!((compressor.ratio_for_handle(output + 0.5) - 3.0).abs() > 0.1);
+    }
+
+    #[test]
+    fn test_auto_makeup_and_plot() {
+        let mut compressor = CompressorModel::new(-24.0, 3.0, 0.0, 0.0);
+        compressor.auto_makeup = true;
+
+        assert!((compressor.makeup() - 8.0).abs() < 1e-9);
+
+        compressor.auto_makeup = false;
+        let graph = compressor.plot(60.0, 60.0);
+        assert_eq!(61, graph.curve.len());
+        // unity gain below the threshold
+        assert!((graph.curve[30].1 - 30.0).abs() < 1e-9);
+        // 0 dB in is -16 dB out
+        assert!((graph.curve[60].1 - 16.0).abs() < 1e-9);
+        let threshold = graph.threshold.expect("no threshold");
+        assert!((threshold.x_start - 36.0).abs() < f64::EPSILON);
+    }
+
+    #[test]
+    fn test_simulate_step() {
+        let mut compressor = CompressorModel::new(-20.0, 2.0, 0.0, 0.0);
+        compressor.update(CompressorParameter::Attack(0.001));
+        compressor.update(CompressorParameter::Release(10.0));
+        assert!((compressor.time_constants.release - MAX_RELEASE).abs() < f64::EPSILON);
+
+        let signal = TestSignal::Step {
+            from: -30.0,
+            to: 0.0,
+            at: 0.05,
+        };
+        let simulation = compressor.simulate(&signal, 0.1, 48_000.0);
+        assert!(simulation.gain[2_399].abs() < f64::EPSILON);
+        assert!((simulation.gain[4_799] - -10.0).abs() < 0.5);
+    }
+
+    #[test]
+    fn test_sidechain_filters_detection() {
+        let mut compressor = CompressorModel::new(-20.0, 2.0, 0.0, 0.0);
+        compressor.update(CompressorParameter::Attack(0.001));
+        compressor.update(CompressorParameter::Sidechain(
+            1,
+            Parameter::Frequency(1_000.0),
+        ));
+        compressor.update(CompressorParameter::Sidechain(1, Parameter::Gain(-12.0)));
+
+        let signal = TestSignal::Step {
+            from: 0.0,
+            to: 0.0,
+            at: 0.0,
+        };
+        let gain = |compressor: &CompressorModel| {
+            let simulation = compressor.simulate(&signal, 0.05, 48_000.0);
+            simulation.gain[simulation.gain.len() - 1]
+        };
+
+        // the side chain filter starts out inactive
+        assert!((gain(&compressor) - -10.0).abs() < 0.5);
+        compressor.update(CompressorParameter::SidechainActive(true));
+        assert!((gain(&compressor) - -4.0).abs() < 0.5);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serialize_with_sidechain() {
+        let mut compressor = CompressorModel::default();
+        compressor.update(CompressorParameter::Sidechain(
+            0,
+            Parameter::Frequency(150.0),
+        ));
+        compressor.update(CompressorParameter::SidechainActive(true));
+
+        let json = serde_json::to_string(&compressor).expect("serialization failed");
+        let deserialized: CompressorModel =
+            serde_json::from_str(&json).expect("deserialization failed");
+        assert_eq!(compressor, deserialized);
+    }
+}
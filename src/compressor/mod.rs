@@ -1 +1,13 @@
+mod common;
 
+pub use crate::compressor::common::*;
+
+#[cfg(feature = "yew-components")]
+mod yew_component;
+#[cfg(feature = "yew-components")]
+pub use crate::compressor::yew_component::*;
+
+#[cfg(feature = "yew-components")]
+mod yew_component_envelope;
+#[cfg(feature = "yew-components")]
+pub use crate::compressor::yew_component_envelope::*;
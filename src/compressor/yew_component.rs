@@ -0,0 +1,61 @@
+use crate::compressor::*;
+use crate::dynamics::*;
+use crate::*;
+use derivative::*;
+use yew::prelude::*;
+
+/// The transfer curve of a compressor.
+pub struct Compressor {
+    props: Props,
+}
+
+#[derive(Derivative, Properties)]
+#[derivative(Debug, Clone, PartialEq)]
+pub struct Props {
+    pub id: String,
+    pub compressor: CompressorModel,
+    /// The current input level, shown as a dot on the transfer curve.
+    pub input_level: Option<Gain>,
+    pub width: f64,
+    pub height: f64,
+    /// Shows an editable view of the side chain filter below the transfer curve.
+    #[prop_or_default]
+    pub show_sidechain: bool,
+    #[derivative(PartialEq = "ignore")]
+    pub on_input: Option<Callback<CompressorParameter>>,
+}
+
+impl Component for Compressor {
+    type Message = ();
+
+    type Properties = Props;
+
+    fn create(props: Self::Properties, _link: ComponentLink<Self>) -> Self {
+        Compressor { props }
+    }
+
+    fn update(&mut self, _msg: Self::Message) -> ShouldRender {
+        false
+    }
+
+    fn change(&mut self, props: Self::Properties) -> ShouldRender {
+        let changed = props != self.props;
+        self.props = props;
+        changed
+    }
+
+    fn view(&self) -> Html {
+        html! {
+            <TransferCurve<CompressorModel>
+                id={self.props.id.clone()}
+                model={self.props.compressor.clone()}
+                class="compressor"
+                input_level={self.props.input_level}
+                width={self.props.width}
+                height={self.props.height}
+                show_sidechain={self.props.show_sidechain}
+                on_input={self.props.on_input.clone()}
+            />
+        }
+    }
+}
@@ -0,0 +1,189 @@
+use crate::compressor::*;
+use crate::dynamics::*;
+use crate::js_utils::*;
+use crate::*;
+use wasm_bindgen::prelude::*;
+use web_sys::HtmlCanvasElement;
+use yew::prelude::*;
+
+/// The sample rate test signals are simulated at.
+const SIMULATION_SAMPLE_RATE: f64 = 48_000.0;
+
+/// Shows what a compressor's time constants do to a test signal: the signal level, the output
+/// level and the gain change over time.
+pub struct CompressorEnvelope {
+    props: EnvelopeProps,
+    canvas: NodeRef,
+    renderer: Option<CanvasEnvelopeRenderer>,
+    render_callback: Closure<dyn FnMut()>,
+    needs_repaint: bool,
+    /// The last simulation, rerun only when the props it depends on change.
+    simulation: Simulation,
+}
+
+#[derive(Debug, Clone, PartialEq, Properties)]
+pub struct EnvelopeProps {
+    pub id: String,
+    pub compressor: CompressorModel,
+    pub signal: TestSignal,
+    pub duration: Seconds,
+    /// The lowest gain change shown.
+    pub gain_range: Gain,
+    pub width: f64,
+    pub height: f64,
+}
+
+pub enum EnvelopeMsg {
+    Render,
+}
+
+impl Component for CompressorEnvelope {
+    type Message = EnvelopeMsg;
+
+    type Properties = EnvelopeProps;
+
+    fn create(props: Self::Properties, link: ComponentLink<Self>) -> Self {
+        let render_callback = Closure::wrap(
+            Box::new(move || link.send_message(EnvelopeMsg::Render)) as Box<dyn FnMut()>,
+        );
+
+        let simulation = simulate_props(&props);
+
+        CompressorEnvelope {
+            props,
+            canvas: NodeRef::default(),
+            renderer: None,
+            render_callback,
+            needs_repaint: false,
+            simulation,
+        }
+    }
+
+    fn update(&mut self, msg: Self::Message) -> ShouldRender {
+        match msg {
+            EnvelopeMsg::Render => self.render(),
+        }
+        false
+    }
+
+    fn change(&mut self, props: Self::Properties) -> ShouldRender {
+        let resized = props.width != self.props.width || props.height != self.props.height;
+        if props != self.props {
+            if simulation_changed(&props, &self.props) {
+                self.simulation = simulate_props(&props);
+            }
+            self.props = props;
+            self.schedule_repaint();
+        }
+        if resized {
+            self.renderer = None;
+        }
+        resized
+    }
+
+    fn view(&self) -> Html {
+        html! {
+            <canvas
+                class="compressor-envelope"
+                id={self.props.id.clone()}
+                ref=self.canvas.clone()
+                width={self.props.width}
+                height={self.props.height}
+            />
+        }
+    }
+
+    fn rendered(&mut self, _first_render: bool) {
+        if self.renderer.is_none() {
+            self.renderer = self
+                .canvas
+                .cast::<HtmlCanvasElement>()
+                .and_then(CanvasEnvelopeRenderer::new);
+        }
+
+        self.schedule_repaint();
+    }
+}
+
+impl CompressorEnvelope {
+    fn render(&mut self) {
+        self.needs_repaint = false;
+        if let Some(renderer) = self.renderer.as_ref() {
+            let props = &self.props;
+            let compressor = &props.compressor;
+            let graph = plot_envelope(
+                &self.simulation,
+                compressor.min_level,
+                compressor.max_level,
+                props.gain_range,
+                props.width,
+                props.height,
+            );
+            renderer.render_to_canvas(&graph);
+        }
+    }
+
+    fn schedule_repaint(&mut self) {
+        if !self.needs_repaint {
+            self.needs_repaint = true;
+            request_animation_frame(&self.render_callback);
+        }
+    }
+}
+
+fn simulate_props(props: &EnvelopeProps) -> Simulation {
+    props
+        .compressor
+        .simulate(&props.signal, props.duration, SIMULATION_SAMPLE_RATE)
+}
+
+/// Whether the simulation has to be rerun for `props`. The makeup gain and the plotted level
+/// range don't affect it.
+fn simulation_changed(props: &EnvelopeProps, previous: &EnvelopeProps) -> bool {
+    let (compressor, previous_compressor) = (&props.compressor, &previous.compressor);
+    props.signal != previous.signal
+        || props.duration != previous.duration
+        || compressor.threshold != previous_compressor.threshold
+        || compressor.ratio != previous_compressor.ratio
+        || compressor.knee != previous_compressor.knee
+        || compressor.detector != previous_compressor.detector
+        || compressor.time_constants != previous_compressor.time_constants
+        || compressor.sidechain != previous_compressor.sidechain
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_simulation_changed() {
+        let props = EnvelopeProps {
+            id: "envelope".to_owned(),
+            compressor: CompressorModel::default(),
+            signal: TestSignal::Step {
+                from: -40.0,
+                to: -10.0,
+                at: 0.1,
+            },
+            duration: 0.5,
+            gain_range: 24.0,
+            width: 300.0,
+            height: 100.0,
+        };
+
+        let mut next = props.clone();
+        next.width = 400.0;
+        next.gain_range = 12.0;
+        next.compressor.update(CompressorParameter::Makeup(6.0));
+        next.compressor
+            .update(CompressorParameter::AutoMakeup(true));
+        assert!(!simulation_changed(&next, &props));
+
+        next.compressor.update(CompressorParameter::Release(1.0));
+        assert!(simulation_changed(&next, &props));
+
+        let mut next = props.clone();
+        next.duration = 1.0;
+        assert!(simulation_changed(&next, &props));
+    }
+}
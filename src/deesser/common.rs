@@ -0,0 +1,257 @@
+use crate::deesser::plotter;
+use crate::dynamics::*;
+use crate::eq::{
+    EqBand, EqModel, MAJOR_FREQUENCY_MARKERS, MAJOR_GAIN_MARKERS, MINOR_FREQUENCY_MARKERS,
+    MINOR_GAIN_MARKERS,
+};
+use crate::utils::*;
+use crate::*;
+
+pub const MIN_FREQUENCY: Frequency = 1_000.0;
+pub const MAX_FREQUENCY: Frequency = 16_000.0;
+/// Bandwidths are in octaves.
+pub const MIN_BANDWIDTH: f64 = 0.25;
+pub const MAX_BANDWIDTH: f64 = 4.0;
+pub const MAX_RANGE: Gain = 24.0;
+/// The slope of the high and low pass filters forming the detection band.
+const DETECTION_SLOPE: Slope = 12;
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum DeEsserMode {
+    /// Only the detection band is attenuated.
+    SplitBand,
+    /// The whole signal is attenuated.
+    Wideband,
+}
+
+/// Attenuates sibilance: whenever the level within the detection band exceeds the threshold, the
+/// band or the whole signal is turned down by the excess, up to `range`.
+#[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DeEsserModel {
+    /// The center of the detection band.
+    pub frequency: Frequency,
+    /// The width of the detection band in octaves.
+    pub bandwidth: f64,
+    pub threshold: Gain,
+    /// The maximum attenuation in dB.
+    pub range: Gain,
+    pub mode: DeEsserMode,
+    pub min_frequency: Frequency,
+    pub max_frequency: Frequency,
+    /// The lowest gain shown in the plot.
+    pub min_gain: Gain,
+    /// The lowest level shown by the detector strip.
+    pub min_level: Gain,
+}
+
+impl DeEsserModel {
+    pub fn new(frequency: Frequency, bandwidth: f64, threshold: Gain) -> DeEsserModel {
+        DeEsserModel {
+            frequency,
+            bandwidth,
+            threshold,
+            range: 12.0,
+            mode: DeEsserMode::SplitBand,
+            min_frequency: 20.0,
+            max_frequency: 24_000.0,
+            min_gain: -24.0,
+            min_level: -60.0,
+        }
+    }
+
+    /// The lower and upper edge of the detection band.
+    pub fn band_edges(&self) -> (Frequency, Frequency) {
+        let factor = 2f64.powf(self.bandwidth / 2.0);
+        (self.frequency / factor, self.frequency * factor)
+    }
+
+    /// The detection band as a high pass and a low pass filter at the band edges.
+    pub fn detection_filter(&self) -> EqModel {
+        let (lower, upper) = self.band_edges();
+        let bands = vec![
+            (
+                EqBand::HighPass {
+                    frequency: lower,
+                    slope: DETECTION_SLOPE,
+                },
+                true,
+            ),
+            (
+                EqBand::LowPass {
+                    frequency: upper,
+                    slope: DETECTION_SLOPE,
+                },
+                true,
+            ),
+        ];
+        EqModel::new(
+            bands,
+            self.min_gain,
+            0.0,
+            self.min_frequency,
+            self.max_frequency,
+            0.1,
+            10.0,
+            true,
+        )
+    }
+
+    /// The gain in dB applied while the de-esser reduces by `gain_reduction`, at a frequency
+    /// where the detection filter's response relative to its center frequency is `response`.
+    /// In split band mode the reduction follows that response, so it is complete at the center
+    /// frequency and fades out outside of the band.
+    pub fn reduction_at(&self, response: Gain, gain_reduction: Gain) -> Gain {
+        match self.mode {
+            DeEsserMode::Wideband => gain_reduction,
+            DeEsserMode::SplitBand => {
+                let weight = to_amplitude(response).min(1.0);
+                to_db(1.0 - weight * (1.0 - to_amplitude(gain_reduction)))
+            }
+        }
+    }
+
+    pub fn update(&mut self, change: DeEsserParameter) {
+        match change {
+            DeEsserParameter::Frequency(frequency) => {
+                self.frequency = frequency.clamp(MIN_FREQUENCY, MAX_FREQUENCY)
+            }
+            DeEsserParameter::Bandwidth(bandwidth) => {
+                self.bandwidth = bandwidth.clamp(MIN_BANDWIDTH, MAX_BANDWIDTH)
+            }
+            DeEsserParameter::Threshold(threshold) => {
+                self.threshold = threshold.max(self.min_level).min(0.0)
+            }
+            DeEsserParameter::Range(range) => self.range = range.clamp(0.0, MAX_RANGE),
+            DeEsserParameter::Mode(mode) => self.mode = mode,
+        }
+    }
+
+    pub fn x_to_frequency_converter(&self, width: f64) -> (PixelScale, FreqScale) {
+        let x_scale = PixelScale::new(0.0, width);
+        let freq_scale = FreqScale::new(self.min_frequency, self.max_frequency);
+        (x_scale, freq_scale)
+    }
+
+    pub fn y_to_gain_converter(&self, height: f64) -> (PixelScale, GainScale) {
+        let y_scale = PixelScale::inverted(0.0, height);
+        let gain_scale = GainScale::new(self.min_gain, 0.0);
+        (y_scale, gain_scale)
+    }
+
+    pub fn y_to_level_converter(&self, height: f64) -> (PixelScale, GainScale) {
+        let y_scale = PixelScale::inverted(0.0, height);
+        let level_scale = GainScale::new(self.min_level, 0.0);
+        (y_scale, level_scale)
+    }
+
+    pub fn frequency_markers(&self) -> (Vec<Frequency>, Vec<Frequency>) {
+        let (min, max) = (self.min_frequency, self.max_frequency);
+        let major = filter_markers(&MAJOR_FREQUENCY_MARKERS, min, max, false);
+        let minor = filter_markers(&MINOR_FREQUENCY_MARKERS, min, max, false);
+        (major, minor)
+    }
+
+    pub fn gain_markers(&self) -> (Vec<Gain>, Vec<Gain>) {
+        let major = filter_markers(&MAJOR_GAIN_MARKERS, self.min_gain, 0.0, false);
+        let minor = filter_markers(&MINOR_GAIN_MARKERS, self.min_gain, 0.0, false);
+        (major, minor)
+    }
+
+    /// Plots the detection band and the current reduction over frequency, with a strip of
+    /// `strip_width` pixels at the right edge showing the detected level against the threshold.
+    pub fn plot(
+        &self,
+        gain_reduction: Gain,
+        detector_level: Option<Gain>,
+        width: f64,
+        height: f64,
+        strip_width: f64,
+    ) -> DeEsserGraph {
+        plotter::plot_deesser(
+            self,
+            gain_reduction,
+            detector_level,
+            width,
+            height,
+            strip_width,
+        )
+    }
+}
+
+impl GainComputer for DeEsserModel {
+    fn gain(&self, input: Gain) -> Gain {
+        (self.threshold - input).max(-self.range).min(0.0)
+    }
+
+    fn threshold(&self) -> Option<Gain> {
+        Some(self.threshold)
+    }
+}
+
+impl Default for DeEsserModel {
+    fn default() -> Self {
+        DeEsserModel::new(6_000.0, 1.0, -30.0)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum DeEsserParameter {
+    Frequency(Frequency),
+    Bandwidth(f64),
+    Threshold(Gain),
+    Range(Gain),
+    Mode(DeEsserMode),
+}
+
+pub struct DeEsserGraph {
+    /// The detection filter's response.
+    pub detection: Vec<(X, Y)>,
+    /// The gain applied at each frequency at the current gain reduction.
+    pub reduction: Vec<(X, Y)>,
+    /// The horizontal extent of the detection band between its edges.
+    pub band: (X, X),
+    pub center: Line,
+    /// The area showing the detected level, right of the frequency plot.
+    pub strip: Bounds,
+    pub detector_level: Option<Bounds>,
+    pub threshold: Line,
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_detection_band() {
+        let deesser = DeEsserModel::new(4_000.0, 2.0, -30.0);
+        let (lower, upper) = deesser.band_edges();
+        assert!((lower - 2_000.0).abs() < 1e-9);
+        assert!((upper - 8_000.0).abs() < 1e-9);
+
+        let filter = deesser.detection_filter();
+        assert!(filter.gain_at(4_000.0).abs() < 1.0);
+        assert!(filter.gain_at(500.0) < -20.0);
+        assert!((filter.gain_at(2_000.0) - -3.0).abs() < 0.5);
+    }
+
+    #[test]
+    fn test_reduction_modes() {
+        let mut deesser = DeEsserModel::new(4_000.0, 2.0, -30.0);
+
+        assert!((deesser.gain(-20.0) - -10.0).abs() < 1e-9);
+        assert!((deesser.gain(0.0) - -12.0).abs() < 1e-9);
+
+        let filter = deesser.detection_filter();
+        let response = |f: Frequency| filter.gain_at(f) - filter.gain_at(4_000.0);
+        assert!((deesser.reduction_at(response(4_000.0), -6.0) - -6.0).abs() < 1e-9);
+        assert!(deesser.reduction_at(response(200.0), -6.0).abs() < 0.1);
+
+        deesser.update(DeEsserParameter::Mode(DeEsserMode::Wideband));
+        assert!((deesser.reduction_at(response(200.0), -6.0) - -6.0).abs() < f64::EPSILON);
+
+        deesser.update(DeEsserParameter::Frequency(100.0));
+        assert!((deesser.frequency - MIN_FREQUENCY).abs() < f64::EPSILON);
+    }
+}
@@ -0,0 +1,150 @@
+use crate::deesser::common::*;
+use crate::js_utils::*;
+use crate::*;
+use web_sys::CanvasRenderingContext2d;
+use web_sys::HtmlCanvasElement;
+
+const FREQUENCY_HANDLE_WIDTH: f64 = 6.0;
+const FREQUENCY_HANDLE_HEIGHT: f64 = 16.0;
+
+pub struct CanvasDeEsserRenderer {
+    pub context: CanvasRenderingContext2d,
+    pub style: Style,
+    pub bounds: Bounds,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Style {
+    band_fill: Option<String>,
+    detection_stroke: Option<String>,
+    reduction_stroke: Option<String>,
+    reduction_fill: Option<String>,
+    center_stroke: Option<String>,
+    handle_fill: Option<String>,
+    strip_fill: Option<String>,
+    level_fill: Option<String>,
+    level_over_fill: Option<String>,
+    threshold_stroke: Option<String>,
+}
+
+impl CanvasDeEsserRenderer {
+    pub fn new(canvas: HtmlCanvasElement) -> Option<CanvasDeEsserRenderer> {
+        let context = get_context_2d(&canvas)?;
+        let style = get_styles(&canvas);
+
+        let bounds = canvas.get_bounding_client_rect().into();
+
+        let band_fill = get_style("--band-fill", &style, Some("#88f2"));
+        let detection_stroke = get_style("--detection-stroke", &style, Some("#88f"));
+        let reduction_stroke = get_style("--reduction-stroke", &style, Some("orange"));
+        let reduction_fill = get_style("--reduction-fill", &style, Some("#ffa50044"));
+        let center_stroke = get_style("--center-stroke", &style, Some("#777"));
+        let handle_fill = get_style("--handle-fill", &style, Some("#88f6"));
+        let strip_fill = get_style("--strip-fill", &style, Some("#0004"));
+        let level_fill = get_style("--level-fill", &style, Some("#8f8"));
+        let level_over_fill = get_style("--level-over-fill", &style, Some("orange"));
+        let threshold_stroke = get_style("--threshold-stroke", &style, Some("#fff"));
+
+        let style = Style {
+            band_fill,
+            detection_stroke,
+            reduction_stroke,
+            reduction_fill,
+            center_stroke,
+            handle_fill,
+            strip_fill,
+            level_fill,
+            level_over_fill,
+            threshold_stroke,
+        };
+
+        Some(CanvasDeEsserRenderer {
+            context,
+            style,
+            bounds,
+        })
+    }
+
+    pub fn render_to_canvas(&self, graph: &DeEsserGraph) {
+        let width = self.bounds.width;
+        let height = self.bounds.height;
+
+        let context = &self.context;
+
+        context.clear_rect(0.0, 0.0, width, height);
+
+        let (from, to) = graph.band;
+        set_fill(context, self.style.band_fill.as_ref());
+        context.fill_rect(from, 0.0, to - from, height);
+
+        // the area between the top and the reduction curve shows what is taken away
+        let mut points = graph.reduction.iter();
+        if let Some((x, y)) = points.next() {
+            context.begin_path();
+            context.move_to(*x, 0.0);
+            context.line_to(*x, *y);
+            for (x, y) in points {
+                context.line_to(*x, *y);
+            }
+            if let Some((x, _)) = graph.reduction.last() {
+                context.line_to(*x, 0.0);
+            }
+            context.close_path();
+            set_fill(context, self.style.reduction_fill.as_ref());
+            context.fill();
+        }
+
+        self.stroke_curve(&graph.detection, self.style.detection_stroke.as_ref());
+        self.stroke_curve(&graph.reduction, self.style.reduction_stroke.as_ref());
+
+        let center = &graph.center;
+        context.begin_path();
+        set_stroke(context, self.style.center_stroke.as_ref());
+        context.move_to(center.x_start + 0.5, center.y_start);
+        context.line_to(center.x_end + 0.5, center.y_end);
+        context.stroke();
+
+        set_fill(context, self.style.handle_fill.as_ref());
+        context.fill_rect(
+            center.x_start + 0.5 - FREQUENCY_HANDLE_WIDTH / 2.0,
+            (height - FREQUENCY_HANDLE_HEIGHT) / 2.0,
+            FREQUENCY_HANDLE_WIDTH,
+            FREQUENCY_HANDLE_HEIGHT,
+        );
+
+        let strip = &graph.strip;
+        set_fill(context, self.style.strip_fill.as_ref());
+        context.fill_rect(strip.x, strip.y, strip.width, strip.height);
+
+        let threshold = &graph.threshold;
+        if let Some(level) = &graph.detector_level {
+            let fill = if level.y < threshold.y_start {
+                self.style.level_over_fill.as_ref()
+            } else {
+                self.style.level_fill.as_ref()
+            };
+            set_fill(context, fill);
+            context.fill_rect(level.x + 2.0, level.y, level.width - 4.0, level.height);
+        }
+
+        context.begin_path();
+        set_stroke(context, self.style.threshold_stroke.as_ref());
+        context.move_to(threshold.x_start, threshold.y_start + 0.5);
+        context.line_to(threshold.x_end, threshold.y_end + 0.5);
+        context.stroke();
+    }
+
+    fn stroke_curve(&self, curve: &[(X, Y)], stroke: Option<&String>) {
+        let context = &self.context;
+        let mut points = curve.iter();
+        if let Some((x, y)) = points.next() {
+            context.begin_path();
+            set_stroke(context, stroke);
+            context.move_to(*x, *y);
+            for (x, y) in points {
+                context.line_to(*x, *y);
+            }
+            context.stroke();
+        }
+    }
+}
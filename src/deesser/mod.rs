@@ -0,0 +1,15 @@
+mod common;
+mod plotter;
+
+pub use crate::deesser::common::*;
+pub use crate::deesser::plotter::*;
+
+#[cfg(feature = "js")]
+mod js;
+#[cfg(feature = "js")]
+pub use crate::deesser::js::*;
+
+#[cfg(feature = "yew-components")]
+mod yew_component;
+#[cfg(feature = "yew-components")]
+pub use crate::deesser::yew_component::*;
@@ -0,0 +1,111 @@
+use crate::deesser::common::*;
+use crate::*;
+use scales::prelude::*;
+
+pub fn plot_deesser(
+    model: &DeEsserModel,
+    gain_reduction: Gain,
+    detector_level: Option<Gain>,
+    width: f64,
+    height: f64,
+    strip_width: f64,
+) -> DeEsserGraph {
+    let plot_width = (width - strip_width).max(0.0);
+    let x_conv = model.x_to_frequency_converter(plot_width);
+    let y_conv = model.y_to_gain_converter(height);
+    let level_conv = model.y_to_level_converter(height);
+
+    let filter = model.detection_filter();
+    let center = filter.gain_at(model.frequency);
+    let gain_reduction = gain_reduction.max(model.min_gain).min(0.0);
+
+    let (detection, reduction) = (0..=plot_width as usize)
+        .map(|x| {
+            let x = x as f64;
+            let f = x_conv.convert(x);
+            let response = filter.gain_at(f);
+            let detection = response.max(model.min_gain);
+            let reduction = model
+                .reduction_at(response - center, gain_reduction)
+                .max(model.min_gain);
+            (
+                (x, y_conv.convert_back(detection)),
+                (x, y_conv.convert_back(reduction)),
+            )
+        })
+        .unzip();
+
+    let (lower, upper) = model.band_edges();
+    let band = (x_conv.convert_back(lower), x_conv.convert_back(upper));
+
+    let center_x = x_conv.convert_back(model.frequency).floor();
+    let center = Line {
+        x_start: center_x,
+        y_start: 0.0,
+        x_end: center_x,
+        y_end: height,
+        value: model.frequency,
+    };
+
+    let strip = Bounds {
+        x: plot_width,
+        y: 0.0,
+        width: strip_width,
+        height,
+    };
+
+    let detector_level = detector_level.map(|level| {
+        let y = level_conv.convert_back(level.max(model.min_level).min(0.0));
+        Bounds {
+            x: plot_width,
+            y,
+            width: strip_width,
+            height: height - y,
+        }
+    });
+
+    let threshold_y = level_conv.convert_back(model.threshold).floor();
+    let threshold = Line {
+        x_start: plot_width,
+        y_start: threshold_y,
+        x_end: width,
+        y_end: threshold_y,
+        value: model.threshold,
+    };
+
+    DeEsserGraph {
+        detection,
+        reduction,
+        band,
+        center,
+        strip,
+        detector_level,
+        threshold,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_plot_deesser() {
+        let mut model = DeEsserModel::new(2_000.0, 2.0, -30.0);
+        model.min_frequency = 20.0;
+        model.max_frequency = 20_000.0;
+
+        let graph = model.plot(-12.0, Some(-15.0), 316.0, 48.0, 16.0);
+
+        assert_eq!(301, graph.detection.len());
+        assert_eq!(301, graph.reduction.len());
+        assert!((graph.center.x_start - 200.0).abs() < f64::EPSILON);
+        assert!((graph.band.0 - 100.0 * 50f64.log10()).abs() < 1e-9);
+        assert!((graph.band.1 - 100.0 * 200f64.log10()).abs() < 1e-9);
+        assert!((graph.reduction[200].1 - 24.0).abs() < 1.0);
+        assert!(graph.reduction[0].1.abs() < 0.1);
+        assert!((graph.strip.x - 300.0).abs() < f64::EPSILON);
+        let level = graph.detector_level.unwrap();
+        assert!((level.height - 36.0).abs() < 1e-9);
+        assert!((graph.threshold.y_start - 24.0).abs() < f64::EPSILON);
+    }
+}
@@ -0,0 +1,361 @@
+use crate::deesser::*;
+use crate::js_utils::*;
+use crate::scale;
+use crate::scale::ScaleModel;
+use crate::*;
+use derivative::*;
+use scales::prelude::*;
+use wasm_bindgen::prelude::*;
+use web_sys::HtmlCanvasElement;
+use yew::prelude::*;
+
+/// The width of the detector level strip right of the frequency plot.
+const STRIP_WIDTH: f64 = 16.0;
+/// The center frequency is not grabbed further away from the pointer than this.
+const GRAB_DISTANCE: f64 = 8.0;
+/// Wheel steps change the bandwidth by this many octaves.
+const BANDWIDTH_STEP: f64 = 0.25;
+
+pub struct DeEsser {
+    props: Props,
+    ext_props: Option<Props>,
+    link: ComponentLink<Self>,
+    canvas: NodeRef,
+    active_handle: Option<Handle>,
+    last_touch: Option<(X, Y)>,
+    renderer: Option<CanvasDeEsserRenderer>,
+    render_callback: Closure<dyn FnMut()>,
+    needs_repaint: bool,
+}
+
+#[derive(Derivative, Properties)]
+#[derivative(Debug, Clone, PartialEq)]
+pub struct Props {
+    pub id: String,
+    pub deesser: DeEsserModel,
+    /// The current gain reduction, shown as a curve over the frequency axis.
+    pub gain_reduction: Gain,
+    /// The current level within the detection band, shown against the threshold.
+    pub detector_level: Option<Gain>,
+    pub width: f64,
+    pub height: f64,
+    #[derivative(PartialEq = "ignore")]
+    pub on_input: Option<Callback<DeEsserParameter>>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Handle {
+    Frequency,
+    Threshold,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Msg {
+    MouseDown(MouseEvent),
+    MouseUp(MouseEvent),
+    MouseMove(MouseEvent),
+    TouchStart(TouchEvent),
+    TouchEnd(TouchEvent),
+    TouchMove(TouchEvent),
+    Wheel(WheelEvent),
+    ToggleMode,
+    Render,
+}
+
+impl Component for DeEsser {
+    type Message = Msg;
+
+    type Properties = Props;
+
+    fn create(props: Self::Properties, link: ComponentLink<Self>) -> Self {
+        let cb_link = link.clone();
+        let render_callback =
+            Closure::wrap(Box::new(move || cb_link.send_message(Msg::Render)) as Box<dyn FnMut()>);
+
+        let cb_link = link.clone();
+        let mouse_moved =
+            Closure::wrap(Box::new(move |e| cb_link.send_message(Msg::MouseMove(e)))
+                as Box<dyn Fn(MouseEvent)>);
+
+        let cb_link = link.clone();
+        let mouse_up = Closure::wrap(
+            Box::new(move |e| cb_link.send_message(Msg::MouseUp(e))) as Box<dyn Fn(MouseEvent)>
+        );
+
+        register_global_listener("mousemove", &mouse_moved);
+        register_global_listener("mouseup", &mouse_up);
+
+        mouse_moved.forget();
+        mouse_up.forget();
+
+        DeEsser {
+            props,
+            ext_props: None,
+            link,
+            canvas: NodeRef::default(),
+            active_handle: None,
+            last_touch: None,
+            renderer: None,
+            render_callback,
+            needs_repaint: false,
+        }
+    }
+
+    fn update(&mut self, msg: Self::Message) -> ShouldRender {
+        match msg {
+            Msg::MouseDown(e) => self.handle_mouse_down(e),
+            Msg::MouseUp(_) => self.handle_up(),
+            Msg::MouseMove(e) => self.handle_mouse_move(e),
+            Msg::TouchStart(e) => self.handle_touch_start(e),
+            Msg::TouchEnd(_) => self.handle_up(),
+            Msg::TouchMove(e) => self.handle_touch_move(e),
+            Msg::Wheel(e) => self.handle_wheel(e),
+            Msg::ToggleMode => {
+                let mode = match self.props.deesser.mode {
+                    DeEsserMode::SplitBand => DeEsserMode::Wideband,
+                    DeEsserMode::Wideband => DeEsserMode::SplitBand,
+                };
+                self.apply(DeEsserParameter::Mode(mode));
+                return true;
+            }
+            Msg::Render => self.render(),
+        }
+        false
+    }
+
+    fn change(&mut self, props: Self::Properties) -> ShouldRender {
+        let resized = props.width != self.props.width || props.height != self.props.height;
+        let reduction_changed = props.gain_reduction != self.props.gain_reduction;
+        // don't accept external changes while a handle is being dragged
+        if self.active_handle.is_some() {
+            self.props.gain_reduction = props.gain_reduction;
+            self.props.detector_level = props.detector_level;
+            self.ext_props = Some(props);
+            self.schedule_repaint();
+        } else if props != self.props {
+            self.props = props;
+            self.schedule_repaint();
+        }
+        if resized {
+            self.renderer = None;
+        }
+        resized || reduction_changed
+    }
+
+    fn view(&self) -> Html {
+        let mouse_down_callback = self.link.callback(Msg::MouseDown);
+        let touch_start_callback = self.link.callback(Msg::TouchStart);
+        let touch_end_callback = self.link.callback(Msg::TouchEnd);
+        let touch_move_callback = self.link.callback(Msg::TouchMove);
+        let wheel_callback = self.link.callback(Msg::Wheel);
+
+        let width = self.props.width;
+        let height = self.props.height;
+
+        let (freq_scale, gain_scale) = scales(&self.props.deesser);
+        let pixel_scale_x = PixelScale::new(0.0, width - STRIP_WIDTH);
+        let pixel_scale_y = PixelScale::inverted(0.0, height);
+
+        let mode = match self.props.deesser.mode {
+            DeEsserMode::SplitBand => "Split",
+            DeEsserMode::Wideband => "Wide",
+        };
+        let gain_reduction = LabelFormat::Gain(true).format(self.props.gain_reduction);
+
+        html! {
+            <div class="deesser">
+                <svg class="scale" width={width} height={height}>
+                    <scale::Scale<FreqScale> scale={freq_scale} pixel_scale={pixel_scale_x} label_format={Some(LabelFormat::FrequencyShort(true))} width={height} />
+                    <scale::Scale<GainScale> scale={gain_scale} pixel_scale={pixel_scale_y} label_format={Some(LabelFormat::GainShort(true))} width={width - STRIP_WIDTH} />
+                </svg>
+                <canvas
+                    id={self.props.id.clone()}
+                    ref=self.canvas.clone()
+                    width={width}
+                    height={height}
+                    onmousedown={mouse_down_callback}
+                    ontouchstart={touch_start_callback}
+                    ontouchend={touch_end_callback}
+                    ontouchmove={touch_move_callback}
+                    onwheel={wheel_callback}
+                />
+                <div class="deesser-controls">
+                    <button class="mode-toggle" onclick={self.link.callback(|_| Msg::ToggleMode)}>
+                        {mode}
+                    </button>
+                    <span class="gain-reduction">{gain_reduction}</span>
+                </div>
+            </div>
+        }
+    }
+
+    fn rendered(&mut self, _first_render: bool) {
+        if self.renderer.is_none() {
+            self.renderer = self
+                .canvas
+                .cast::<HtmlCanvasElement>()
+                .and_then(CanvasDeEsserRenderer::new);
+        }
+
+        self.schedule_repaint();
+    }
+}
+
+impl DeEsser {
+    fn render(&mut self) {
+        self.needs_repaint = false;
+        if let Some(renderer) = self.renderer.as_ref() {
+            let props = &self.props;
+            let graph = props.deesser.plot(
+                props.gain_reduction,
+                props.detector_level,
+                props.width,
+                props.height,
+                STRIP_WIDTH,
+            );
+            renderer.render_to_canvas(&graph);
+        }
+    }
+
+    fn schedule_repaint(&mut self) {
+        if !self.needs_repaint {
+            self.needs_repaint = true;
+            request_animation_frame(&self.render_callback);
+        }
+    }
+
+    fn handle_mouse_down(&mut self, e: MouseEvent) {
+        if e.button() != 0 {
+            return;
+        }
+        self.handle_down(e.offset_x() as f64);
+    }
+
+    fn handle_mouse_move(&mut self, e: MouseEvent) {
+        if let Some(handle) = self.active_handle {
+            self.handle_move(e.movement_x() as f64, e.movement_y() as f64, handle);
+        }
+    }
+
+    fn handle_touch_start(&mut self, e: TouchEvent) {
+        if e.target_touches().length() != 1 {
+            return;
+        }
+        if let (Some(touch), Some(canvas)) = (
+            e.changed_touches().get(0),
+            self.canvas.cast::<HtmlCanvasElement>(),
+        ) {
+            let rect = canvas.get_bounding_client_rect();
+            let x = touch.client_x() as f64;
+            let y = touch.client_y() as f64;
+            self.last_touch = Some((x, y));
+            self.handle_down(x - rect.x());
+        }
+    }
+
+    fn handle_touch_move(&mut self, e: TouchEvent) {
+        if e.target_touches().length() != 1 {
+            return;
+        }
+        if let (Some(handle), Some(touch)) = (self.active_handle, e.changed_touches().get(0)) {
+            let x = touch.client_x() as f64;
+            let y = touch.client_y() as f64;
+            if let Some((last_x, last_y)) = self.last_touch {
+                self.handle_move(x - last_x, y - last_y, handle);
+            }
+            self.last_touch = Some((x, y));
+        }
+        e.prevent_default();
+    }
+
+    /// Scrolling adjusts the bandwidth, with shift held the range.
+    fn handle_wheel(&mut self, e: WheelEvent) {
+        let deesser = &self.props.deesser;
+        let step = -e.delta_y().signum();
+        let change = if e.shift_key() {
+            DeEsserParameter::Range(deesser.range + step)
+        } else {
+            DeEsserParameter::Bandwidth(deesser.bandwidth + step * BANDWIDTH_STEP)
+        };
+        self.apply(change);
+        e.prevent_default();
+    }
+
+    /// The detector strip grabs the threshold, the center line the frequency.
+    fn handle_down(&mut self, x: X) {
+        let plot_width = self.props.width - STRIP_WIDTH;
+        self.active_handle = if x >= plot_width {
+            Some(Handle::Threshold)
+        } else {
+            let deesser = &self.props.deesser;
+            let x_conv = deesser.x_to_frequency_converter(plot_width);
+            let center = x_conv.convert_back(deesser.frequency);
+            if (x - center).abs() <= GRAB_DISTANCE {
+                Some(Handle::Frequency)
+            } else {
+                None
+            }
+        };
+    }
+
+    fn handle_up(&mut self) {
+        self.active_handle = None;
+        self.last_touch = None;
+        if let Some(props) = self.ext_props.take() {
+            self.props = props;
+            self.schedule_repaint();
+        }
+    }
+
+    fn handle_move(&mut self, d_x: X, d_y: Y, handle: Handle) {
+        let deesser = &self.props.deesser;
+        match handle {
+            Handle::Frequency => {
+                let x_conv = deesser.x_to_frequency_converter(self.props.width - STRIP_WIDTH);
+                let frequency = deesser.frequency;
+                let new_frequency = x_conv.add_external_clamped(d_x, frequency);
+                if (new_frequency - frequency).abs() > f64::EPSILON {
+                    self.apply(DeEsserParameter::Frequency(new_frequency));
+                }
+            }
+            Handle::Threshold => {
+                let y_conv = deesser.y_to_level_converter(self.props.height);
+                let threshold = deesser.threshold;
+                let new_threshold = y_conv.add_external_clamped(d_y, threshold);
+                if (new_threshold - threshold).abs() > f64::EPSILON {
+                    self.apply(DeEsserParameter::Threshold(new_threshold));
+                }
+            }
+        }
+    }
+
+    fn apply(&mut self, change: DeEsserParameter) {
+        self.props.deesser.update(change.clone());
+        self.schedule_repaint();
+        if let Some(Callback::Callback(fun)) = &self.props.on_input {
+            fun(change);
+        }
+    }
+}
+
+fn scales(deesser: &DeEsserModel) -> (ScaleModel<FreqScale>, ScaleModel<GainScale>) {
+    let (major_scale_markers, minor_scale_markers) = deesser.frequency_markers();
+    let freq_scale = ScaleModel::new(
+        FreqScale::new(deesser.min_frequency, deesser.max_frequency),
+        scale::Layout::Horizontal(scale::HorizontalPosition::Top),
+        None,
+        major_scale_markers,
+        minor_scale_markers,
+    );
+
+    let (major_scale_markers, minor_scale_markers) = deesser.gain_markers();
+    let gain_scale = ScaleModel::new(
+        GainScale::new(deesser.min_gain, 0.0),
+        scale::Layout::Vertical(scale::VerticalPosition::Left),
+        None,
+        major_scale_markers,
+        minor_scale_markers,
+    );
+
+    (freq_scale, gain_scale)
+}
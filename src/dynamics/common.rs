@@ -0,0 +1,137 @@
+use crate::dynamics::plotter::*;
+use crate::eq::{EqModel, Parameter};
+use crate::scale::{HorizontalPosition, Layout, ScaleModel, VerticalPosition};
+use crate::*;
+
+/// The static curve of a dynamics processor, mapping input levels to output levels in dB.
+pub trait GainComputer {
+    /// The gain change in dB applied to a signal at the given input level, excluding makeup gain.
+    /// Negative values mean gain reduction.
+    fn gain(&self, input: Gain) -> Gain;
+
+    fn makeup(&self) -> Gain {
+        0.0
+    }
+
+    /// The output level for the given input level, including makeup gain.
+    fn output(&self, input: Gain) -> Gain {
+        input + self.gain(input) + self.makeup()
+    }
+
+    /// The level the curve starts to deviate from unity gain at, drawn as a marker.
+    fn threshold(&self) -> Option<Gain> {
+        None
+    }
+
+    /// The level a gate with hysteresis closes at again, drawn as a second marker.
+    fn close_threshold(&self) -> Option<Gain> {
+        None
+    }
+}
+
+/// A transfer curve plot of a gain computer, input level on the x axis and output level on the
+/// y axis, both covering the same level range.
+pub struct TransferGraph {
+    pub curve: Vec<(X, Y)>,
+    /// The unity gain diagonal.
+    pub unity: Line,
+    pub threshold: Option<Line>,
+    pub close_threshold: Option<Line>,
+}
+
+/// Applies the gain change of a hard or soft knee to `input`, given the level the knee is
+/// centered on, its width and the slope of the curve beyond it relative to unity. A slope of
+/// `1 / ratio - 1` describes a downward compressor above the threshold.
+pub fn knee(input: Gain, threshold: Gain, width: Gain, slope: f64) -> Gain {
+    let overshoot = input - threshold;
+    if width > 0.0 && overshoot.abs() <= width / 2.0 {
+        slope * (overshoot + width / 2.0).powi(2) / (2.0 * width)
+    } else if overshoot > 0.0 {
+        slope * overshoot
+    } else {
+        0.0
+    }
+}
+
+/// A dynamics processor shown as an editable transfer curve, with a threshold handle, a ratio
+/// handle and an optional side chain filter.
+pub trait TransferCurveModel: GainComputer + Sized {
+    type Parameter: Clone;
+
+    /// The lowest level shown in the transfer curve plot.
+    fn min_level(&self) -> Gain;
+
+    /// The highest level shown in the transfer curve plot.
+    fn max_level(&self) -> Gain;
+
+    /// The input level of the threshold handle.
+    fn threshold_level(&self) -> Gain;
+
+    fn ratio(&self) -> f64;
+
+    /// The input level of the handle used to adjust the ratio.
+    fn ratio_handle_level(&self) -> Gain;
+
+    /// The ratio that moves the curve through `output` at the ratio handle's input level.
+    fn ratio_for_handle(&self, output: Gain) -> f64;
+
+    fn sidechain(&self) -> &EqModel;
+
+    fn update(&mut self, change: Self::Parameter);
+
+    fn threshold_change(threshold: Gain) -> Self::Parameter;
+
+    fn ratio_change(ratio: f64) -> Self::Parameter;
+
+    fn sidechain_change(index: usize, change: Parameter) -> Self::Parameter;
+
+    fn sidechain_active_change(active: bool) -> Self::Parameter;
+
+    /// The change made by one mouse wheel step in the direction of `step`, depending on the
+    /// modifier keys held. `None` if the parameter is already at its limit.
+    fn wheel_change(&self, step: f64, shift: bool, alt: bool) -> Option<Self::Parameter>;
+
+    fn x_to_level_converter(&self, width: f64) -> (PixelScale, GainScale) {
+        let x_scale = PixelScale::new(0.0, width);
+        let level_scale = GainScale::new(self.min_level(), self.max_level());
+        (x_scale, level_scale)
+    }
+
+    fn y_to_level_converter(&self, height: f64) -> (PixelScale, GainScale) {
+        let y_scale = PixelScale::inverted(0.0, height);
+        let level_scale = GainScale::new(self.min_level(), self.max_level());
+        (y_scale, level_scale)
+    }
+
+    /// Scales for input (horizontal) and output (vertical) levels with markers every 10 dB.
+    fn level_scales(&self) -> (ScaleModel<GainScale>, ScaleModel<GainScale>) {
+        let (min, max) = (self.min_level(), self.max_level());
+        let markers = |offset: f64| -> Vec<Gain> {
+            let count = ((max - min - offset) / 10.0).floor() as usize;
+            (0..=count)
+                .map(|i| max - offset - i as f64 * 10.0)
+                .filter(|m| *m > min && *m < max)
+                .rev()
+                .collect()
+        };
+        let input = ScaleModel::new(
+            GainScale::new(min, max),
+            Layout::Horizontal(HorizontalPosition::Bottom),
+            None,
+            markers(0.0),
+            markers(5.0),
+        );
+        let output = ScaleModel::new(
+            GainScale::new(min, max),
+            Layout::Vertical(VerticalPosition::Left),
+            None,
+            markers(0.0),
+            markers(5.0),
+        );
+        (input, output)
+    }
+
+    fn plot(&self, width: f64, height: f64) -> TransferGraph {
+        plot_transfer_curve(self, self.min_level(), self.max_level(), width, height)
+    }
+}
@@ -0,0 +1,218 @@
+pub use crate::utils::{to_amplitude, to_db};
+use crate::*;
+
+/// Gain reduction below this is considered fully released.
+const RELEASED: Gain = -0.1;
+/// Sustained reduction of this length stretches program-dependent release to its maximum.
+const SUSTAIN_TIME: Seconds = 1.0;
+/// The factor program-dependent release stretches the release time by at most.
+const MAX_RELEASE_STRETCH: f64 = 5.0;
+/// The averaging time of the RMS detector and the decay time of the peak detector.
+const DETECTOR_WINDOW: Seconds = 0.01;
+
+#[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Detector {
+    Peak,
+    Rms,
+}
+
+/// Attack, hold and release behaviour of a dynamics processor's side chain.
+#[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TimeConstants {
+    pub attack: Seconds,
+    /// Time the gain reduction is held before release starts.
+    pub hold: Seconds,
+    pub release: Seconds,
+    /// Stretches the release after sustained gain reduction, up to five times the release time
+    /// after a second or more, while short transients still release quickly.
+    pub program_dependent_release: bool,
+}
+
+impl Default for TimeConstants {
+    fn default() -> Self {
+        TimeConstants {
+            attack: 0.01,
+            hold: 0.0,
+            release: 0.1,
+            program_dependent_release: false,
+        }
+    }
+}
+
+/// A level detector followed by a gain smoother working in the log domain. Feed it samples and
+/// the static gain the gain computer demands for the detected level, it returns the smoothed
+/// gain change in dB.
+#[derive(Debug, PartialEq, Clone)]
+pub struct EnvelopeFollower {
+    pub sample_rate: f64,
+    pub detector: Detector,
+    pub time_constants: TimeConstants,
+    envelope: f64,
+    gain: Gain,
+    hold: usize,
+    sustain: Seconds,
+}
+
+impl EnvelopeFollower {
+    pub fn new(
+        sample_rate: f64,
+        detector: Detector,
+        time_constants: TimeConstants,
+    ) -> EnvelopeFollower {
+        EnvelopeFollower {
+            sample_rate,
+            detector,
+            time_constants,
+            envelope: 0.0,
+            gain: 0.0,
+            hold: 0,
+            sustain: 0.0,
+        }
+    }
+
+    /// The level in dBFS the detector reads after processing the sample.
+    pub fn detect(&mut self, sample: Sample) -> Gain {
+        let sample = sample as f64;
+        let coefficient = coefficient(DETECTOR_WINDOW, self.sample_rate);
+        match self.detector {
+            Detector::Peak => {
+                // instant attack, so the detector follows the peaks of the waveform
+                self.envelope = sample.abs().max(self.envelope * (1.0 - coefficient));
+                to_db(self.envelope)
+            }
+            Detector::Rms => {
+                // the envelope holds the mean square
+                self.envelope += (sample * sample - self.envelope) * coefficient;
+                // the factor 2 makes a sine read its peak level, like the peak detector
+                to_db((2.0 * self.envelope).sqrt())
+            }
+        }
+    }
+
+    /// Moves the smoothed gain towards the `target` gain change for one sample.
+    pub fn smooth(&mut self, target: Gain) -> Gain {
+        let time = &self.time_constants;
+        let period = 1.0 / self.sample_rate;
+
+        if target < self.gain {
+            self.gain += (target - self.gain) * coefficient(time.attack, self.sample_rate);
+            self.hold = (time.hold * self.sample_rate).round() as usize;
+        } else if self.hold > 0 {
+            self.hold -= 1;
+        } else {
+            let release = if time.program_dependent_release {
+                let stretch = (self.sustain / SUSTAIN_TIME).min(1.0);
+                time.release * (1.0 + (MAX_RELEASE_STRETCH - 1.0) * stretch)
+            } else {
+                time.release
+            };
+            self.gain += (target - self.gain) * coefficient(release, self.sample_rate);
+        }
+
+        if self.gain < RELEASED {
+            if target < RELEASED {
+                self.sustain += period;
+            }
+        } else {
+            self.sustain = 0.0;
+        }
+
+        self.gain
+    }
+
+    pub fn gain(&self) -> Gain {
+        self.gain
+    }
+
+    pub fn reset(&mut self) {
+        self.envelope = 0.0;
+        self.gain = 0.0;
+        self.hold = 0;
+        self.sustain = 0.0;
+    }
+}
+
+/// The one-pole coefficient reaching 1 - 1/e of a step after `time`.
+fn coefficient(time: Seconds, sample_rate: f64) -> f64 {
+    if time > 0.0 {
+        1.0 - (-1.0 / (time * sample_rate)).exp()
+    } else {
+        1.0
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_attack_hold_release() {
+        let time_constants = TimeConstants {
+            attack: 0.01,
+            hold: 0.005,
+            release: 0.1,
+            program_dependent_release: false,
+        };
+        let mut follower = EnvelopeFollower::new(1_000.0, Detector::Peak, time_constants);
+
+        for _ in 0..10 {
+            follower.smooth(-10.0);
+        }
+        assert!((follower.gain() - -10.0 * (1.0 - (-1.0f64).exp())).abs() < 1e-9);
+
+        for _ in 0..5 {
+            follower.smooth(0.0);
+        }
+        assert!((follower.gain() - -6.3212).abs() < 1e-3);
+
+        for _ in 0..100 {
+            follower.smooth(0.0);
+        }
+        assert!((follower.gain() - -6.3212 * (-1.0f64).exp()).abs() < 1e-3);
+    }
+
+    #[test]
+    fn test_program_dependent_release() {
+        let time_constants = TimeConstants {
+            attack: 0.0,
+            hold: 0.0,
+            release: 0.1,
+            program_dependent_release: true,
+        };
+        let mut transient = EnvelopeFollower::new(1_000.0, Detector::Peak, time_constants);
+        let mut sustained = transient.clone();
+
+        for _ in 0..5 {
+            transient.smooth(-10.0);
+        }
+        for _ in 0..2_000 {
+            sustained.smooth(-10.0);
+        }
+        for _ in 0..100 {
+            transient.smooth(0.0);
+            sustained.smooth(0.0);
+        }
+
+        assert!((transient.gain() - -10.0 * (-1.0f64).exp()).abs() < 0.1);
+        assert!(sustained.gain() < -7.0);
+    }
+
+    #[test]
+    fn test_detectors() {
+        let mut peak = EnvelopeFollower::new(48_000.0, Detector::Peak, TimeConstants::default());
+        let mut rms = peak.clone();
+        rms.detector = Detector::Rms;
+
+        let mut rms_level = f64::NEG_INFINITY;
+        for i in 0..4_800 {
+            let sample = 0.5 * (2.0 * std::f32::consts::PI * i as f32 / 48.0).sin();
+            rms_level = rms.detect(sample);
+            if i == 12 {
+                assert!((peak.detect(sample) - to_db(0.5)).abs() < 1e-6);
+            }
+        }
+        assert!((rms_level - to_db(0.5)).abs() < 0.2);
+    }
+}
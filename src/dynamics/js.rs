@@ -0,0 +1,221 @@
+use crate::dynamics::common::*;
+use crate::dynamics::simulator::*;
+use crate::js_utils::*;
+use crate::utils::*;
+use crate::*;
+use std::f64::consts::PI;
+use web_sys::CanvasRenderingContext2d;
+use web_sys::HtmlCanvasElement;
+
+const HANDLE_RADIUS: f64 = 5.0;
+const LEVEL_RADIUS: f64 = 4.0;
+
+/// Draws the transfer curve of any gain computer along with draggable handles and the current
+/// input/output level.
+pub struct CanvasTransferCurveRenderer {
+    pub context: CanvasRenderingContext2d,
+    pub style: Style,
+    pub bounds: Bounds,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Style {
+    unity_stroke: Option<String>,
+    threshold_stroke: Option<String>,
+    close_threshold_stroke: Option<String>,
+    curve_stroke: Option<String>,
+    handle_fill: Option<String>,
+    level_fill: Option<String>,
+}
+
+impl CanvasTransferCurveRenderer {
+    pub fn new(canvas: HtmlCanvasElement) -> Option<CanvasTransferCurveRenderer> {
+        let context = get_context_2d(&canvas)?;
+        let style = get_styles(&canvas);
+
+        let bounds = canvas.get_bounding_client_rect().into();
+
+        let unity_stroke = get_style("--unity-stroke", &style, Some("#333"));
+        let threshold_stroke = get_style("--threshold-stroke", &style, Some("#444"));
+        let close_threshold_stroke = get_style("--close-threshold-stroke", &style, Some("#444"));
+        let curve_stroke = get_style("--curve-stroke", &style, Some("#88f"));
+        let handle_fill = get_style("--handle-fill", &style, Some("#88f6"));
+        let level_fill = get_style("--level-fill", &style, Some("orange"));
+
+        let style = Style {
+            unity_stroke,
+            threshold_stroke,
+            close_threshold_stroke,
+            curve_stroke,
+            handle_fill,
+            level_fill,
+        };
+
+        Some(CanvasTransferCurveRenderer {
+            context,
+            style,
+            bounds,
+        })
+    }
+
+    pub fn render_to_canvas(
+        &self,
+        graph: &TransferGraph,
+        handles: &[(X, Y)],
+        level: Option<(X, Y)>,
+    ) {
+        let width = self.bounds.width;
+        let height = self.bounds.height;
+
+        let context = &self.context;
+
+        context.clear_rect(0.0, 0.0, width, height);
+
+        let unity = &graph.unity;
+        context.begin_path();
+        set_stroke(context, self.style.unity_stroke.as_ref());
+        context.move_to(unity.x_start, unity.y_start);
+        context.line_to(unity.x_end, unity.y_end);
+        context.stroke();
+
+        let markers = [
+            (&graph.threshold, &self.style.threshold_stroke),
+            (&graph.close_threshold, &self.style.close_threshold_stroke),
+        ];
+        for (marker, stroke) in markers.iter() {
+            if let Some(marker) = marker {
+                context.begin_path();
+                set_stroke(context, stroke.as_ref());
+                context.move_to(marker.x_start + 0.5, marker.y_start);
+                context.line_to(marker.x_end + 0.5, marker.y_end);
+                context.stroke();
+            }
+        }
+
+        let mut points = graph.curve.iter();
+        if let Some((x, y)) = points.next() {
+            context.begin_path();
+            set_stroke(context, self.style.curve_stroke.as_ref());
+            context.move_to(*x, *y);
+            for (x, y) in points {
+                context.line_to(*x, *y);
+            }
+            context.stroke();
+        }
+
+        set_fill(context, self.style.handle_fill.as_ref());
+        for (x, y) in handles {
+            self.fill_circle(*x, *y, HANDLE_RADIUS);
+        }
+
+        if let Some((x, y)) = level {
+            set_fill(context, self.style.level_fill.as_ref());
+            self.fill_circle(x, y, LEVEL_RADIUS);
+        }
+    }
+
+    fn fill_circle(&self, x: X, y: Y, radius: Radius) {
+        let context = &self.context;
+        context.begin_path();
+        context
+            .arc(x, y, radius, 0.0, 2.0 * PI)
+            .expect("arc failed");
+        context.fill();
+    }
+}
+
+/// Draws the gain envelope of a simulated test signal over a time grid.
+pub struct CanvasEnvelopeRenderer {
+    pub context: CanvasRenderingContext2d,
+    pub style: EnvelopeStyle,
+    pub bounds: Bounds,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct EnvelopeStyle {
+    grid_stroke: Option<String>,
+    grid_label_fill: Option<String>,
+    grid_font: Option<String>,
+    input_stroke: Option<String>,
+    output_stroke: Option<String>,
+    gain_stroke: Option<String>,
+}
+
+impl CanvasEnvelopeRenderer {
+    pub fn new(canvas: HtmlCanvasElement) -> Option<CanvasEnvelopeRenderer> {
+        let context = get_context_2d(&canvas)?;
+        let style = get_styles(&canvas);
+
+        let bounds = canvas.get_bounding_client_rect().into();
+
+        let grid_stroke = get_style("--grid-stroke", &style, Some("#333"));
+        let grid_label_fill = get_style("--grid-label-fill", &style, Some("#777"));
+        let grid_font = get_style("--grid-font", &style, Some("9px sans-serif"));
+        let input_stroke = get_style("--input-stroke", &style, Some("#555"));
+        let output_stroke = get_style("--output-stroke", &style, Some("#88f"));
+        let gain_stroke = get_style("--gain-stroke", &style, Some("orange"));
+
+        let style = EnvelopeStyle {
+            grid_stroke,
+            grid_label_fill,
+            grid_font,
+            input_stroke,
+            output_stroke,
+            gain_stroke,
+        };
+
+        Some(CanvasEnvelopeRenderer {
+            context,
+            style,
+            bounds,
+        })
+    }
+
+    pub fn render_to_canvas(&self, graph: &EnvelopeGraph) {
+        let width = self.bounds.width;
+        let height = self.bounds.height;
+
+        let context = &self.context;
+
+        context.clear_rect(0.0, 0.0, width, height);
+
+        context.begin_path();
+        set_stroke(context, self.style.grid_stroke.as_ref());
+        for line in &graph.time_grid {
+            context.move_to(line.x_start + 0.5, line.y_start);
+            context.line_to(line.x_end + 0.5, line.y_end);
+        }
+        context.stroke();
+
+        if let Some(font) = &self.style.grid_font {
+            context.set_font(font);
+        }
+        context.set_text_align("left");
+        context.set_text_baseline("bottom");
+        set_fill(context, self.style.grid_label_fill.as_ref());
+        for line in &graph.time_grid {
+            let text = format!("{} ms", (line.value * 1_000.0).round());
+            context
+                .fill_text(&text, line.x_start + 3.0, line.y_end - 2.0)
+                .ignore();
+        }
+
+        self.stroke_trace(&graph.input, self.style.input_stroke.as_ref());
+        self.stroke_trace(&graph.output, self.style.output_stroke.as_ref());
+        self.stroke_trace(&graph.gain, self.style.gain_stroke.as_ref());
+    }
+
+    fn stroke_trace(&self, trace: &[(X, Y)], stroke: Option<&String>) {
+        let context = &self.context;
+        let mut points = trace.iter();
+        if let Some((x, y)) = points.next() {
+            context.begin_path();
+            set_stroke(context, stroke);
+            context.move_to(*x, *y);
+            for (x, y) in points {
+                context.line_to(*x, *y);
+            }
+            context.stroke();
+        }
+    }
+}
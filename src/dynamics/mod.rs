@@ -0,0 +1,19 @@
+mod common;
+mod detector;
+mod plotter;
+mod simulator;
+
+pub use crate::dynamics::common::*;
+pub use crate::dynamics::detector::*;
+pub use crate::dynamics::plotter::*;
+pub use crate::dynamics::simulator::*;
+
+#[cfg(feature = "js")]
+mod js;
+#[cfg(feature = "js")]
+pub use crate::dynamics::js::*;
+
+#[cfg(feature = "yew-components")]
+mod yew_component;
+#[cfg(feature = "yew-components")]
+pub use crate::dynamics::yew_component::*;
@@ -0,0 +1,54 @@
+use crate::dynamics::common::*;
+use crate::*;
+use scales::prelude::*;
+
+/// Plots the transfer curve of a gain computer for input and output levels between `min` and
+/// `max`, sampling the curve once per pixel column.
+pub fn plot_transfer_curve(
+    computer: &impl GainComputer,
+    min: Gain,
+    max: Gain,
+    width: f64,
+    height: f64,
+) -> TransferGraph {
+    let x_conv = (PixelScale::new(0.0, width), GainScale::new(min, max));
+    let y_conv = (PixelScale::inverted(0.0, height), GainScale::new(min, max));
+
+    let curve = (0..=width as usize)
+        .map(|x| {
+            let x = x as f64;
+            let output = computer.output(x_conv.convert(x)).max(min).min(max);
+            (x, y_conv.convert_back(output))
+        })
+        .collect();
+
+    let unity = Line {
+        x_start: 0.0,
+        y_start: height,
+        x_end: width,
+        y_end: 0.0,
+        value: 0.0,
+    };
+
+    let marker = |level: Option<Gain>| {
+        level.filter(|t| (min..=max).contains(t)).map(|t| {
+            let x = x_conv.convert_back(t).floor();
+            Line {
+                x_start: x,
+                y_start: 0.0,
+                x_end: x,
+                y_end: height,
+                value: t,
+            }
+        })
+    };
+    let threshold = marker(computer.threshold());
+    let close_threshold = marker(computer.close_threshold());
+
+    TransferGraph {
+        curve,
+        unity,
+        threshold,
+        close_threshold,
+    }
+}
@@ -0,0 +1,260 @@
+use crate::dynamics::common::*;
+use crate::dynamics::detector::*;
+use crate::eq::EqModel;
+use crate::*;
+use scales::prelude::*;
+use std::f64::consts::PI;
+
+/// The frequency of the tone the test signals are made of.
+pub const TEST_TONE_FREQUENCY: Frequency = 1_000.0;
+
+/// Grid line spacings for the time axis of an envelope plot.
+const TIME_GRID_STEPS: [Seconds; 12] = [
+    0.001, 0.002, 0.005, 0.01, 0.02, 0.05, 0.1, 0.2, 0.5, 1.0, 2.0, 5.0,
+];
+const MAX_TIME_GRID_LINES: f64 = 10.0;
+
+/// A synthetic test tone whose level changes over time. Levels are peak levels in dBFS.
+#[derive(Debug, PartialEq, Clone)]
+pub enum TestSignal {
+    /// Jumps from one level to another.
+    Step { from: Gain, to: Gain, at: Seconds },
+    /// Rises from a base level for a while, then falls back.
+    Burst {
+        base: Gain,
+        level: Gain,
+        start: Seconds,
+        length: Seconds,
+    },
+}
+
+impl TestSignal {
+    pub fn level(&self, time: Seconds) -> Gain {
+        match self {
+            TestSignal::Step { from, to, at } => {
+                if time < *at {
+                    *from
+                } else {
+                    *to
+                }
+            }
+            TestSignal::Burst {
+                base,
+                level,
+                start,
+                length,
+            } => {
+                if time >= *start && time < start + length {
+                    *level
+                } else {
+                    *base
+                }
+            }
+        }
+    }
+
+    pub fn sample(&self, time: Seconds) -> Sample {
+        let amplitude = to_amplitude(self.level(time));
+        (amplitude * (2.0 * PI * TEST_TONE_FREQUENCY * time).sin()) as Sample
+    }
+}
+
+impl Default for TestSignal {
+    fn default() -> Self {
+        TestSignal::Burst {
+            base: -40.0,
+            level: -6.0,
+            start: 0.1,
+            length: 0.3,
+        }
+    }
+}
+
+/// The course of a dynamics processor's gain while processing a test signal, one entry per
+/// sample.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Simulation {
+    pub sample_rate: f64,
+    /// The level of the test signal.
+    pub input: Vec<Gain>,
+    /// The smoothed gain change, excluding makeup gain.
+    pub gain: Vec<Gain>,
+}
+
+impl Simulation {
+    pub fn duration(&self) -> Seconds {
+        self.input.len() as f64 / self.sample_rate
+    }
+}
+
+/// Runs `signal` through the detector and smoother of `follower`, feeding the detected level to
+/// `computer`. Since the test signal is a single tone, a side chain filter only changes the
+/// detected level by its gain at the tone's frequency.
+pub fn simulate(
+    computer: &impl GainComputer,
+    follower: &mut EnvelopeFollower,
+    sidechain: Option<&EqModel>,
+    signal: &TestSignal,
+    duration: Seconds,
+) -> Simulation {
+    let sample_rate = follower.sample_rate;
+    let sidechain_gain = sidechain.map_or(0.0, |eq| eq.gain_at(TEST_TONE_FREQUENCY));
+    let length = (duration * sample_rate).round() as usize;
+    let mut input = Vec::with_capacity(length);
+    let mut gain = Vec::with_capacity(length);
+
+    for i in 0..length {
+        let time = i as f64 / sample_rate;
+        let level = follower.detect(signal.sample(time)) + sidechain_gain;
+        let target = if level.is_finite() {
+            computer.gain(level)
+        } else {
+            computer.gain(f64::MIN)
+        };
+        input.push(signal.level(time));
+        gain.push(follower.smooth(target));
+    }
+
+    Simulation {
+        sample_rate,
+        input,
+        gain,
+    }
+}
+
+pub struct EnvelopeGraph {
+    /// The level of the test signal on the level axis.
+    pub input: Vec<(X, Y)>,
+    /// The level after applying the gain change on the level axis.
+    pub output: Vec<(X, Y)>,
+    /// The gain change on the gain axis, 0 dB at the top.
+    pub gain: Vec<(X, Y)>,
+    /// Vertical lines at round times, their value is the time in seconds.
+    pub time_grid: Vec<Line>,
+}
+
+/// Plots a simulation with levels between `min_level` and `max_level` and gain changes down to
+/// `-gain_range`, keeping the lowest gain within each pixel column so short dips stay visible.
+pub fn plot_envelope(
+    simulation: &Simulation,
+    min_level: Gain,
+    max_level: Gain,
+    gain_range: Gain,
+    width: f64,
+    height: f64,
+) -> EnvelopeGraph {
+    let level_conv = (
+        PixelScale::inverted(0.0, height),
+        GainScale::new(min_level, max_level),
+    );
+    let gain_conv = (
+        PixelScale::inverted(0.0, height),
+        GainScale::new(-gain_range, 0.0),
+    );
+
+    let columns = width as usize;
+    let length = simulation.input.len();
+    let mut input = Vec::with_capacity(columns);
+    let mut output = Vec::with_capacity(columns);
+    let mut gain = Vec::with_capacity(columns);
+
+    for column in 0..columns {
+        let from = column * length / columns;
+        let to = ((column + 1) * length / columns).min(length);
+        if from >= to {
+            continue;
+        }
+        let (i, min_gain) = (from..to)
+            .map(|i| (i, simulation.gain[i]))
+            .fold((from, f64::INFINITY), |a, b| if b.1 < a.1 { b } else { a });
+        let level = simulation.input[i];
+        let x = column as f64;
+        input.push((
+            x,
+            level_conv.convert_back(level.max(min_level).min(max_level)),
+        ));
+        output.push((
+            x,
+            level_conv.convert_back((level + min_gain).max(min_level).min(max_level)),
+        ));
+        gain.push((
+            x,
+            gain_conv.convert_back(min_gain.max(-gain_range).min(0.0)),
+        ));
+    }
+
+    let duration = simulation.duration();
+    let step = TIME_GRID_STEPS
+        .iter()
+        .copied()
+        .find(|step| duration / step <= MAX_TIME_GRID_LINES)
+        .unwrap_or(TIME_GRID_STEPS[TIME_GRID_STEPS.len() - 1]);
+    let time_conv = (PixelScale::new(0.0, width), LinearScale::new(0.0, duration));
+    let time_grid = (1..)
+        .map(|i| i as f64 * step)
+        .take_while(|time| *time < duration)
+        .map(|time| {
+            let x = time_conv.convert_back(time).floor();
+            Line {
+                x_start: x,
+                y_start: 0.0,
+                x_end: x,
+                y_end: height,
+                value: time,
+            }
+        })
+        .collect();
+
+    EnvelopeGraph {
+        input,
+        output,
+        gain,
+        time_grid,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    struct Limit;
+
+    impl GainComputer for Limit {
+        fn gain(&self, input: Gain) -> Gain {
+            (-20.0 - input).min(0.0)
+        }
+    }
+
+    #[test]
+    fn test_burst_envelope() {
+        let signal = TestSignal::Burst {
+            base: -40.0,
+            level: -10.0,
+            start: 0.1,
+            length: 0.1,
+        };
+        let time_constants = TimeConstants {
+            attack: 0.005,
+            hold: 0.0,
+            release: 0.05,
+            program_dependent_release: false,
+        };
+        let mut follower = EnvelopeFollower::new(48_000.0, Detector::Peak, time_constants);
+        let simulation = simulate(&Limit, &mut follower, None, &signal, 0.4);
+
+        assert_eq!(19_200, simulation.input.len());
+        assert!(simulation.gain[4_799].abs() < f64::EPSILON);
+        // the gain has settled at the end of the burst and recovered well after it
+        assert!((simulation.gain[9_599] - -10.0).abs() < 0.5);
+        assert!(simulation.gain[19_199] > -0.5);
+
+        let graph = plot_envelope(&simulation, -60.0, 0.0, 20.0, 400.0, 60.0);
+        assert_eq!(400, graph.gain.len());
+        assert!((graph.input[0].1 - 40.0).abs() < 1e-9);
+        assert!((graph.input[150].1 - 10.0).abs() < 1e-9);
+        assert!((graph.output[190].1 - 20.0).abs() < 0.5);
+        assert!((graph.gain[190].1 - 30.0).abs() < 1.5);
+        assert_eq!(7, graph.time_grid.len());
+        assert!((graph.time_grid[0].x_start - 50.0).abs() < f64::EPSILON);
+    }
+}
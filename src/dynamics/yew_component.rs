@@ -0,0 +1,369 @@
+use crate::dynamics::*;
+use crate::eq::{ParamProps, Parameter, ParametricEq};
+use crate::js_utils::*;
+use crate::scale;
+use crate::*;
+use derivative::*;
+use scales::prelude::*;
+use std::fmt::Debug;
+use wasm_bindgen::prelude::*;
+use web_sys::HtmlCanvasElement;
+use yew::prelude::*;
+
+/// Handles further away from the pointer than this are not grabbed.
+const GRAB_DISTANCE: f64 = 20.0;
+
+/// An editable transfer curve of a compressor, expander or gate.
+pub struct TransferCurve<M: TransferCurveModel + Debug + Clone + PartialEq + 'static> {
+    props: Props<M>,
+    ext_props: Option<Props<M>>,
+    link: ComponentLink<Self>,
+    canvas: NodeRef,
+    active_handle: Option<Handle>,
+    last_touch: Option<(X, Y)>,
+    renderer: Option<CanvasTransferCurveRenderer>,
+    render_callback: Closure<dyn FnMut()>,
+    needs_repaint: bool,
+}
+
+#[derive(Derivative, Properties)]
+#[derivative(Debug, Clone, PartialEq)]
+pub struct Props<M: TransferCurveModel + Debug + Clone + PartialEq> {
+    pub id: String,
+    pub model: M,
+    /// The class of the surrounding element.
+    pub class: String,
+    /// The current input level, shown as a dot on the transfer curve.
+    pub input_level: Option<Gain>,
+    pub width: f64,
+    pub height: f64,
+    /// Shows an editable view of the side chain filter below the transfer curve.
+    pub show_sidechain: bool,
+    /// Additional elements after the transfer curve, e.g. indicators.
+    #[prop_or_default]
+    pub children: Children,
+    #[derivative(PartialEq = "ignore")]
+    pub on_input: Option<Callback<M::Parameter>>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Handle {
+    Threshold,
+    Ratio,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Msg {
+    MouseDown(MouseEvent),
+    MouseUp(MouseEvent),
+    MouseMove(MouseEvent),
+    TouchStart(TouchEvent),
+    TouchEnd(TouchEvent),
+    TouchMove(TouchEvent),
+    Wheel(WheelEvent),
+    Sidechain((usize, Parameter)),
+    ToggleSidechain,
+    Render,
+}
+
+impl<M: TransferCurveModel + Debug + Clone + PartialEq + 'static> Component for TransferCurve<M> {
+    type Message = Msg;
+
+    type Properties = Props<M>;
+
+    fn create(props: Self::Properties, link: ComponentLink<Self>) -> Self {
+        let cb_link = link.clone();
+        let render_callback =
+            Closure::wrap(Box::new(move || cb_link.send_message(Msg::Render)) as Box<dyn FnMut()>);
+
+        let cb_link = link.clone();
+        let mouse_moved =
+            Closure::wrap(Box::new(move |e| cb_link.send_message(Msg::MouseMove(e)))
+                as Box<dyn Fn(MouseEvent)>);
+
+        let cb_link = link.clone();
+        let mouse_up = Closure::wrap(
+            Box::new(move |e| cb_link.send_message(Msg::MouseUp(e))) as Box<dyn Fn(MouseEvent)>
+        );
+
+        register_global_listener("mousemove", &mouse_moved);
+        register_global_listener("mouseup", &mouse_up);
+
+        mouse_moved.forget();
+        mouse_up.forget();
+
+        TransferCurve {
+            props,
+            ext_props: None,
+            link,
+            canvas: NodeRef::default(),
+            active_handle: None,
+            last_touch: None,
+            renderer: None,
+            render_callback,
+            needs_repaint: false,
+        }
+    }
+
+    fn update(&mut self, msg: Self::Message) -> ShouldRender {
+        match msg {
+            Msg::MouseDown(e) => self.handle_mouse_down(e),
+            Msg::MouseUp(_) => self.handle_up(),
+            Msg::MouseMove(e) => self.handle_mouse_move(e),
+            Msg::TouchStart(e) => self.handle_touch_start(e),
+            Msg::TouchEnd(_) => self.handle_up(),
+            Msg::TouchMove(e) => self.handle_touch_move(e),
+            Msg::Wheel(e) => self.handle_wheel(e),
+            Msg::Sidechain((index, change)) => {
+                self.apply(M::sidechain_change(index, change));
+                return true;
+            }
+            Msg::ToggleSidechain => {
+                let active = self.props.model.sidechain().active;
+                self.apply(M::sidechain_active_change(!active));
+                return true;
+            }
+            Msg::Render => self.render(),
+        }
+        false
+    }
+
+    fn change(&mut self, props: Self::Properties) -> ShouldRender {
+        let resized = props.width != self.props.width || props.height != self.props.height;
+        let children_changed = props.children != self.props.children;
+        // don't accept external changes while a handle is being dragged
+        if self.active_handle.is_some() {
+            self.props.input_level = props.input_level;
+            self.props.children = props.children.clone();
+            self.ext_props = Some(props);
+            self.schedule_repaint();
+        } else if props != self.props {
+            self.props = props;
+            self.schedule_repaint();
+        }
+        if resized {
+            self.renderer = None;
+        }
+        resized || children_changed
+    }
+
+    fn view(&self) -> Html {
+        let mouse_down_callback = self.link.callback(Msg::MouseDown);
+        let touch_start_callback = self.link.callback(Msg::TouchStart);
+        let touch_end_callback = self.link.callback(Msg::TouchEnd);
+        let touch_move_callback = self.link.callback(Msg::TouchMove);
+        let wheel_callback = self.link.callback(Msg::Wheel);
+
+        let width = self.props.width;
+        let height = self.props.height;
+
+        let (input_scale, output_scale) = self.props.model.level_scales();
+        let pixel_scale_x = PixelScale::new(0.0, width);
+        let pixel_scale_y = PixelScale::inverted(0.0, height);
+
+        html! {
+            <div class={self.props.class.clone()}>
+                <svg class="scale" width={width} height={height}>
+                    <scale::Scale<GainScale> scale={input_scale} pixel_scale={pixel_scale_x} label_format={Some(LabelFormat::GainShort(false))} width={height} />
+                    <scale::Scale<GainScale> scale={output_scale} pixel_scale={pixel_scale_y} label_format={Some(LabelFormat::GainShort(false))} width={width} />
+                </svg>
+                <canvas
+                    id={self.props.id.clone()}
+                    ref=self.canvas.clone()
+                    width={width}
+                    height={height}
+                    onmousedown={mouse_down_callback}
+                    ontouchstart={touch_start_callback}
+                    ontouchend={touch_end_callback}
+                    ontouchmove={touch_move_callback}
+                    onwheel={wheel_callback}
+                />
+                { self.sidechain_view() }
+                { self.props.children.clone() }
+            </div>
+        }
+    }
+
+    fn rendered(&mut self, _first_render: bool) {
+        if self.renderer.is_none() {
+            self.renderer = self
+                .canvas
+                .cast::<HtmlCanvasElement>()
+                .and_then(CanvasTransferCurveRenderer::new);
+        }
+
+        self.schedule_repaint();
+    }
+}
+
+impl<M: TransferCurveModel + Debug + Clone + PartialEq + 'static> TransferCurve<M> {
+    fn render(&mut self) {
+        self.needs_repaint = false;
+        if let Some(renderer) = self.renderer.as_ref() {
+            let graph = self.props.model.plot(self.props.width, self.props.height);
+            let handles = [
+                self.position(Handle::Threshold),
+                self.position(Handle::Ratio),
+            ];
+            let level = self.props.input_level.map(|input| self.point(input));
+            renderer.render_to_canvas(&graph, &handles, level);
+        }
+    }
+
+    fn sidechain_view(&self) -> Html {
+        if !self.props.show_sidechain {
+            return html! {};
+        }
+        let sidechain = self.props.model.sidechain();
+        let id = format!("{}-sidechain", self.props.id);
+        let props = ParamProps::compact(id, sidechain.clone(), self.link.callback(Msg::Sidechain));
+        let class = if sidechain.active {
+            "sidechain active"
+        } else {
+            "sidechain"
+        };
+        html! {
+            <div class={class}>
+                <button class="sidechain-toggle" onclick={self.link.callback(|_| Msg::ToggleSidechain)}>
+                    {"SC"}
+                </button>
+                <ParametricEq with props />
+            </div>
+        }
+    }
+
+    fn schedule_repaint(&mut self) {
+        if !self.needs_repaint {
+            self.needs_repaint = true;
+            request_animation_frame(&self.render_callback);
+        }
+    }
+
+    fn handle_mouse_down(&mut self, e: MouseEvent) {
+        if e.button() != 0 {
+            return;
+        }
+        self.handle_down(e.offset_x() as f64, e.offset_y() as f64);
+    }
+
+    fn handle_mouse_move(&mut self, e: MouseEvent) {
+        if let Some(handle) = self.active_handle {
+            self.handle_move(e.movement_x() as f64, e.movement_y() as f64, handle);
+        }
+    }
+
+    fn handle_touch_start(&mut self, e: TouchEvent) {
+        if e.target_touches().length() != 1 {
+            return;
+        }
+        if let (Some(touch), Some(canvas)) = (
+            e.changed_touches().get(0),
+            self.canvas.cast::<HtmlCanvasElement>(),
+        ) {
+            let rect = canvas.get_bounding_client_rect();
+            let x = touch.client_x() as f64;
+            let y = touch.client_y() as f64;
+            self.last_touch = Some((x, y));
+            self.handle_down(x - rect.x(), y - rect.y());
+        }
+    }
+
+    fn handle_touch_move(&mut self, e: TouchEvent) {
+        if e.target_touches().length() != 1 {
+            return;
+        }
+        if let (Some(handle), Some(touch)) = (self.active_handle, e.changed_touches().get(0)) {
+            let x = touch.client_x() as f64;
+            let y = touch.client_y() as f64;
+            if let Some((last_x, last_y)) = self.last_touch {
+                self.handle_move(x - last_x, y - last_y, handle);
+            }
+            self.last_touch = Some((x, y));
+        }
+        e.prevent_default();
+    }
+
+    fn handle_wheel(&mut self, e: WheelEvent) {
+        let step = -e.delta_y().signum();
+        if let Some(change) = self
+            .props
+            .model
+            .wheel_change(step, e.shift_key(), e.alt_key())
+        {
+            self.apply(change);
+        }
+        e.prevent_default();
+    }
+
+    fn handle_down(&mut self, x: X, y: Y) {
+        self.active_handle = [Handle::Threshold, Handle::Ratio]
+            .iter()
+            .map(|handle| {
+                let (h_x, h_y) = self.position(*handle);
+                (*handle, ((x - h_x).powi(2) + (y - h_y).powi(2)).sqrt())
+            })
+            .filter(|(_, distance)| *distance <= GRAB_DISTANCE)
+            .min_by(|a, b| a.1.partial_cmp(&b.1).unwrap_or(std::cmp::Ordering::Equal))
+            .map(|(handle, _)| handle);
+    }
+
+    fn handle_up(&mut self) {
+        self.active_handle = None;
+        self.last_touch = None;
+        if let Some(props) = self.ext_props.take() {
+            self.props = props;
+            self.schedule_repaint();
+        }
+    }
+
+    fn handle_move(&mut self, d_x: X, d_y: Y, handle: Handle) {
+        let model = &self.props.model;
+        match handle {
+            Handle::Threshold => {
+                let x_conv = model.x_to_level_converter(self.props.width);
+                let threshold = model.threshold_level();
+                let new_threshold = x_conv.add_external_clamped(d_x, threshold);
+                if (new_threshold - threshold).abs() > f64::EPSILON {
+                    self.apply(M::threshold_change(new_threshold));
+                }
+            }
+            Handle::Ratio => {
+                let y_conv = model.y_to_level_converter(self.props.height);
+                let output = model.output(model.ratio_handle_level());
+                let new_output = y_conv.add_external_clamped(d_y, output);
+                let ratio = model.ratio();
+                let new_ratio = model.ratio_for_handle(new_output);
+                if (new_ratio - ratio).abs() > f64::EPSILON {
+                    self.apply(M::ratio_change(new_ratio));
+                }
+            }
+        }
+    }
+
+    fn apply(&mut self, change: M::Parameter) {
+        self.props.model.update(change.clone());
+        self.schedule_repaint();
+        if let Some(Callback::Callback(fun)) = &self.props.on_input {
+            fun(change);
+        }
+    }
+
+    fn position(&self, handle: Handle) -> (X, Y) {
+        let model = &self.props.model;
+        let input = match handle {
+            Handle::Threshold => model.threshold_level(),
+            Handle::Ratio => model.ratio_handle_level(),
+        };
+        self.point(input)
+    }
+
+    fn point(&self, input: Gain) -> (X, Y) {
+        let model = &self.props.model;
+        let x_conv = model.x_to_level_converter(self.props.width);
+        let y_conv = model.y_to_level_converter(self.props.height);
+        let (min, max) = (model.min_level(), model.max_level());
+        let input = input.max(min).min(max);
+        let output = model.output(input).max(min).min(max);
+        (x_conv.convert_back(input), y_conv.convert_back(output))
+    }
+}
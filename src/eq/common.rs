@@ -1,4 +1,5 @@
 use crate::eq::plotter;
+use crate::utils::*;
 use crate::*;
 use scales::prelude::*;
 
@@ -13,6 +14,7 @@ pub const MINOR_FREQUENCY_MARKERS: [f64; 41] = [
 ];
 
 #[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct EqModel {
     pub bands: Vec<(EqBand, Active)>,
     pub min_gain: Gain,
@@ -51,6 +53,19 @@ impl EqModel {
         plotter::plot_eq(self, width, height, invert_y)
     }
 
+    /// The summed gain of all active bands at `frequency`, 0 dB if the EQ is inactive.
+    pub fn gain_at(&self, frequency: Frequency) -> Gain {
+        if !self.active {
+            return 0.0;
+        }
+        self.bands
+            .iter()
+            .filter(|(_, active)| *active)
+            .filter_map(|(band, _)| band.plot(std::iter::once(frequency)).next())
+            .map(|(_, gain)| gain)
+            .sum()
+    }
+
     pub fn calc_major_frequency_grid_markers(&self, width: f64) -> Vec<X> {
         let x_conv = self.x_to_frequency_converter(width);
 
@@ -129,19 +144,19 @@ impl EqModel {
     }
 
     pub fn gain_markers(&self, incl: bool) -> (Vec<Gain>, Vec<Gain>) {
-        let major = filter(&MAJOR_GAIN_MARKERS, self.min_gain, self.max_gain, incl);
-        let minor = filter(&MINOR_GAIN_MARKERS, self.min_gain, self.max_gain, incl);
+        let major = filter_markers(&MAJOR_GAIN_MARKERS, self.min_gain, self.max_gain, incl);
+        let minor = filter_markers(&MINOR_GAIN_MARKERS, self.min_gain, self.max_gain, incl);
         (major, minor)
     }
 
     pub fn frequency_markers(&self, incl: bool) -> (Vec<Gain>, Vec<Gain>) {
-        let major = filter(
+        let major = filter_markers(
             &MAJOR_FREQUENCY_MARKERS,
             self.min_frequency,
             self.max_frequency,
             incl,
         );
-        let minor = filter(
+        let minor = filter_markers(
             &MINOR_FREQUENCY_MARKERS,
             self.min_frequency,
             self.max_frequency,
@@ -207,6 +222,46 @@ impl Default for EqModel {
 }
 
 impl EqModel {
+    /// A side chain filter for dynamics processors with a high pass, a bell and a low pass band.
+    /// The filter starts out inactive.
+    pub fn sidechain() -> EqModel {
+        let bands = vec![
+            (
+                EqBand::HighPass {
+                    frequency: 80.0,
+                    slope: 12,
+                },
+                true,
+            ),
+            (
+                EqBand::Bell {
+                    frequency: 3_000.0,
+                    gain: 0.0,
+                    q: 1.0,
+                },
+                true,
+            ),
+            (
+                EqBand::LowPass {
+                    frequency: 16_000.0,
+                    slope: 12,
+                },
+                false,
+            ),
+        ];
+
+        EqModel {
+            active: false,
+            bands,
+            max_frequency: 24_000.0,
+            min_frequency: 20.0,
+            max_gain: 18.0,
+            min_gain: -18.0,
+            max_q: 10.0,
+            min_q: 0.1,
+        }
+    }
+
     pub fn graphic(num: usize) -> EqModel {
         let bands = (0..num)
             .map(|_| {
@@ -235,6 +290,7 @@ impl EqModel {
 }
 
 #[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum EqBand {
     Bell {
         frequency: Frequency,
@@ -355,20 +411,3 @@ fn update_band(band: (EqBand, bool), change: Parameter) -> Option<(EqBand, bool)
         _ => None,
     }
 }
-
-fn filter(markers: &[f64], min: f64, max: f64, incl: bool) -> Vec<f64> {
-    let iter = markers.iter();
-    if incl {
-        iter.filter_map(|m| {
-            if &min <= m && m <= &max {
-                Some(*m)
-            } else {
-                None
-            }
-        })
-        .collect()
-    } else {
-        iter.filter_map(|m| if &min < m && m < &max { Some(*m) } else { None })
-            .collect()
-    }
-}
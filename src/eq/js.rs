@@ -1,13 +1,22 @@
 use crate::eq::common::*;
 use crate::js_utils::*;
+use crate::spectrum::*;
 use crate::*;
 use scales::prelude::*;
 use web_sys::CanvasRenderingContext2d;
 use web_sys::HtmlCanvasElement;
 
+/// The level range spectra are drawn in unless configured otherwise.
+pub const DEFAULT_SPECTRUM_RANGE: (Gain, Gain) = (-90.0, 0.0);
+
 pub struct CanvasEqRenderer {
     pub context: CanvasRenderingContext2d,
     pub band_curves: bool,
+    /// Draws analyzer spectra behind the EQ curve if any are passed to
+    /// `render_to_canvas_with_spectra`.
+    pub spectra: bool,
+    /// The level range in dB mapped onto the canvas' height when drawing spectra.
+    pub spectrum_range: (Gain, Gain),
     pub style: Style,
     pub bounds: Bounds,
 }
@@ -22,6 +31,8 @@ pub struct Style {
     band_disabled_fill: Option<String>,
     sum_stroke: Option<String>,
     sum_fill: Option<String>,
+    spectrum_pre_fill: Option<String>,
+    spectrum_post_stroke: Option<String>,
 }
 
 impl CanvasEqRenderer {
@@ -49,6 +60,8 @@ impl CanvasEqRenderer {
         let band_disabled_fill = get_style("--band-disabled-fill", &style, Some("#88f6"));
         let sum_stroke = get_style("--sum-stroke", &style, Some("#88f"));
         let sum_fill = get_style("--sum-fill", &style, Some("#88f6"));
+        let spectrum_pre_fill = get_style("--spectrum-pre-fill", &style, Some("#fff2"));
+        let spectrum_post_stroke = get_style("--spectrum-post-stroke", &style, Some("#fff6"));
 
         let style = Style {
             band_stroke,
@@ -59,17 +72,31 @@ impl CanvasEqRenderer {
             band_disabled_fill,
             sum_stroke,
             sum_fill,
+            spectrum_pre_fill,
+            spectrum_post_stroke,
         };
 
         Some(CanvasEqRenderer {
             context,
             band_curves,
+            spectra: true,
+            spectrum_range: DEFAULT_SPECTRUM_RANGE,
             bounds,
             style,
         })
     }
 
     pub fn render_to_canvas(&self, eq: &EqModel) {
+        self.render_to_canvas_with_spectra(eq, None, None);
+    }
+
+    /// Renders the EQ on top of the spectra measured before (filled) and after (stroked) it.
+    pub fn render_to_canvas_with_spectra(
+        &self,
+        eq: &EqModel,
+        pre: Option<&Spectrum>,
+        post: Option<&Spectrum>,
+    ) {
         let width = self.bounds.width;
         let height = self.bounds.height;
 
@@ -83,6 +110,29 @@ impl CanvasEqRenderer {
 
         context.clear_rect(0.0, 0.0, width, height);
 
+        if self.spectra {
+            let (min, max) = self.spectrum_range;
+            let spectrum_conv = (PixelScale::inverted(0.0, height), GainScale::new(min, max));
+
+            if let Some(pre) = pre {
+                let curve = plot_spectrum(pre, &x_conv, &spectrum_conv);
+                context.begin_path();
+                set_fill(context, self.style.spectrum_pre_fill.as_ref());
+                stroke_curve(&curve, context);
+                context.line_to(width, height);
+                context.line_to(0.0, height);
+                context.fill();
+            }
+
+            if let Some(post) = post {
+                let curve = plot_spectrum(post, &x_conv, &spectrum_conv);
+                context.begin_path();
+                set_stroke(context, self.style.spectrum_post_stroke.as_ref());
+                stroke_curve(&curve, context);
+                context.stroke();
+            }
+        }
+
         if self.band_curves {
             for (i, (band, active)) in graph.band_curves.iter().enumerate() {
                 context.begin_path();
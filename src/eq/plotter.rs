@@ -146,7 +146,7 @@ fn plot_high_pass(
     Box::new(range.map(move |f| (f, calc_high_pass_gain(f, frequency, slope))))
 }
 
-fn calc_high_pass_gain(f: Frequency, frequency: Frequency, slope: Slope) -> Gain {
+pub(crate) fn calc_high_pass_gain(f: Frequency, frequency: Frequency, slope: Slope) -> Gain {
     let f0 = frequency / f;
     let f1 = f0.powi(2);
     let f2 = f1.powi(2);
@@ -174,7 +174,7 @@ fn plot_low_pass(
     Box::new(range.map(move |f| (f, calc_low_pass_gain(f, frequency, slope))))
 }
 
-fn calc_low_pass_gain(f: Frequency, frequency: Frequency, slope: Slope) -> Gain {
+pub(crate) fn calc_low_pass_gain(f: Frequency, frequency: Frequency, slope: Slope) -> Gain {
     let f0 = frequency / f;
     let f1 = (1.0 / f0).powi(2);
     let f2 = f1.powi(2);
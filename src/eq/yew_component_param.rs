@@ -1,6 +1,7 @@
 use crate::eq::*;
 use crate::js_utils::*;
 use crate::scale::*;
+use crate::spectrum::Spectrum;
 use crate::utils::*;
 use crate::*;
 use derivative::*;
@@ -36,6 +37,10 @@ pub struct ParamProps {
     pub on_input: Option<Callback<(usize, Parameter)>>,
     pub show_band_curves: bool,
     pub show_tooltip: bool,
+    /// Analyzer spectrum of the EQ's input, drawn filled behind the curve.
+    pub pre_spectrum: Option<Spectrum>,
+    /// Analyzer spectrum of the EQ's output, drawn as a line behind the curve.
+    pub post_spectrum: Option<Spectrum>,
 }
 
 impl ParamProps {
@@ -46,6 +51,26 @@ impl ParamProps {
             on_input: None,
             show_band_curves: false,
             show_tooltip: false,
+            pre_spectrum: None,
+            post_spectrum: None,
+        }
+    }
+
+    /// An editable EQ without band curves and tooltip, e.g. for side chain filters embedded in
+    /// other widgets.
+    pub fn compact<S: AsRef<str>>(
+        id: S,
+        eq: EqModel,
+        on_input: Callback<(usize, Parameter)>,
+    ) -> ParamProps {
+        ParamProps {
+            id: Some(id.as_ref().to_owned()),
+            eq,
+            on_input: Some(on_input),
+            show_band_curves: false,
+            show_tooltip: false,
+            pre_spectrum: None,
+            post_spectrum: None,
         }
     }
 
@@ -60,6 +85,8 @@ impl ParamProps {
             on_input: Some(on_input),
             show_band_curves: true,
             show_tooltip: true,
+            pre_spectrum: None,
+            post_spectrum: None,
         }
     }
 }
@@ -255,7 +282,11 @@ impl ParametricEq {
 
     fn render(&mut self) {
         if let Some(renderer) = &self.renderer {
-            renderer.render_to_canvas(&self.props.eq);
+            renderer.render_to_canvas_with_spectra(
+                &self.props.eq,
+                self.props.pre_spectrum.as_ref(),
+                self.props.post_spectrum.as_ref(),
+            );
         }
     }
 
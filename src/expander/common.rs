@@ -0,0 +1,341 @@
+use crate::dynamics::*;
+use crate::eq::{EqModel, Parameter};
+use crate::*;
+
+/// Ratios at or above this act as a gate.
+pub const MAX_RATIO: f64 = 40.0;
+pub const MAX_RANGE: Gain = 80.0;
+pub const MAX_HYSTERESIS: Gain = 20.0;
+pub const MAX_KNEE: Gain = 24.0;
+pub const MAX_ATTACK: Seconds = 0.5;
+pub const MAX_HOLD: Seconds = 2.0;
+pub const MAX_RELEASE: Seconds = 5.0;
+
+/// A downward expander, attenuating signals below the threshold. With a high ratio it acts as
+/// a gate.
+#[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ExpanderModel {
+    pub threshold: Gain,
+    pub ratio: f64,
+    /// The maximum attenuation in dB.
+    pub range: Gain,
+    /// How far in dB the level has to fall below the threshold for an open gate to close.
+    pub hysteresis: Gain,
+    /// Width of the soft knee in dB, centered on the threshold. 0 means hard knee.
+    pub knee: Gain,
+    pub detector: Detector,
+    pub time_constants: TimeConstants,
+    /// Filters the signal feeding the detector, e.g. to keep low frequencies from triggering
+    /// gain changes.
+    pub sidechain: EqModel,
+    /// The lowest level shown in the transfer curve plot.
+    pub min_level: Gain,
+    /// The highest level shown in the transfer curve plot.
+    pub max_level: Gain,
+}
+
+impl ExpanderModel {
+    pub fn new(threshold: Gain, ratio: f64, range: Gain, hysteresis: Gain) -> ExpanderModel {
+        ExpanderModel {
+            threshold,
+            ratio,
+            range,
+            hysteresis,
+            knee: 0.0,
+            detector: Detector::Peak,
+            time_constants: TimeConstants {
+                attack: 0.001,
+                hold: 0.05,
+                release: 0.1,
+                program_dependent_release: false,
+            },
+            sidechain: EqModel::sidechain(),
+            min_level: -80.0,
+            max_level: 0.0,
+        }
+    }
+
+    /// A gate attenuating everything below the threshold by `range`.
+    pub fn gate(threshold: Gain, range: Gain, hysteresis: Gain) -> ExpanderModel {
+        ExpanderModel::new(threshold, MAX_RATIO, range, hysteresis)
+    }
+
+    /// The static gain change in dB at the given input level.
+    pub fn gain_computer(&self, input: Gain) -> Gain {
+        let ratio = self.ratio.max(1.0);
+        // the knee is mirrored to act below the threshold
+        let gain = knee(-input, -self.threshold, self.knee, ratio - 1.0);
+        (-gain).max(-self.range)
+    }
+
+    /// Whether a gate that was `open` before is open at `level`. It opens at the threshold and
+    /// only closes once the level has fallen below the threshold minus the hysteresis.
+    pub fn is_open(&self, level: Gain, open: bool) -> bool {
+        if open {
+            level >= self.threshold - self.hysteresis
+        } else {
+            level >= self.threshold
+        }
+    }
+
+    pub fn update(&mut self, change: ExpanderParameter) {
+        match change {
+            ExpanderParameter::Threshold(threshold) => {
+                self.threshold = threshold.max(self.min_level).min(self.max_level)
+            }
+            ExpanderParameter::Ratio(ratio) => self.ratio = ratio.clamp(1.0, MAX_RATIO),
+            ExpanderParameter::Range(range) => self.range = range.clamp(0.0, MAX_RANGE),
+            ExpanderParameter::Hysteresis(hysteresis) => {
+                self.hysteresis = hysteresis.clamp(0.0, MAX_HYSTERESIS)
+            }
+            ExpanderParameter::Knee(knee) => self.knee = knee.clamp(0.0, MAX_KNEE),
+            ExpanderParameter::Detector(detector) => self.detector = detector,
+            ExpanderParameter::Attack(attack) => {
+                self.time_constants.attack = attack.clamp(0.0, MAX_ATTACK)
+            }
+            ExpanderParameter::Hold(hold) => self.time_constants.hold = hold.clamp(0.0, MAX_HOLD),
+            ExpanderParameter::Release(release) => {
+                self.time_constants.release = release.clamp(0.0, MAX_RELEASE)
+            }
+            ExpanderParameter::Sidechain(index, change) => self.sidechain.update(index, change),
+            ExpanderParameter::SidechainActive(active) => self.sidechain.active = active,
+        }
+    }
+
+    /// The input level of the handle used to adjust the ratio, halfway between the threshold and
+    /// the bottom of the plot.
+    pub fn ratio_handle_level(&self) -> Gain {
+        self.threshold - (self.threshold - self.min_level) / 2.0
+    }
+
+    /// The ratio that moves the curve through `output` at the ratio handle's input level,
+    /// including the knee. Keeps the current ratio if the handle's output does not depend on the
+    /// ratio, and picks the lowest ratio reaching the range if `output` lies below it.
+    pub fn ratio_for_handle(&self, output: Gain) -> f64 {
+        let input = self.ratio_handle_level();
+        // below the range the gain change is -(ratio - 1) times a shape only depending on the knee
+        let shape = knee(-input, -self.threshold, self.knee, 1.0);
+        if shape <= f64::EPSILON {
+            return self.ratio;
+        }
+        let gain = output - input;
+        if gain <= -self.range {
+            let floored = 1.0 + self.range / shape;
+            if self.ratio >= floored {
+                self.ratio
+            } else {
+                floored.clamp(1.0, MAX_RATIO)
+            }
+        } else {
+            (1.0 - gain / shape).clamp(1.0, MAX_RATIO)
+        }
+    }
+
+    pub fn envelope_follower(&self, sample_rate: f64) -> EnvelopeFollower {
+        EnvelopeFollower::new(
+            sample_rate,
+            self.detector.clone(),
+            self.time_constants.clone(),
+        )
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ExpanderParameter {
+    Threshold(Gain),
+    Ratio(f64),
+    Range(Gain),
+    Hysteresis(Gain),
+    Knee(Gain),
+    Detector(Detector),
+    Attack(Seconds),
+    Hold(Seconds),
+    Release(Seconds),
+    /// A change to a band of the side chain filter.
+    Sidechain(usize, Parameter),
+    SidechainActive(bool),
+}
+
+impl GainComputer for ExpanderModel {
+    fn gain(&self, input: Gain) -> Gain {
+        self.gain_computer(input)
+    }
+
+    fn threshold(&self) -> Option<Gain> {
+        Some(self.threshold)
+    }
+
+    fn close_threshold(&self) -> Option<Gain> {
+        if self.hysteresis > 0.0 {
+            Some(self.threshold - self.hysteresis)
+        } else {
+            None
+        }
+    }
+}
+
+impl TransferCurveModel for ExpanderModel {
+    type Parameter = ExpanderParameter;
+
+    fn min_level(&self) -> Gain {
+        self.min_level
+    }
+
+    fn max_level(&self) -> Gain {
+        self.max_level
+    }
+
+    fn threshold_level(&self) -> Gain {
+        self.threshold
+    }
+
+    fn ratio(&self) -> f64 {
+        self.ratio
+    }
+
+    fn ratio_handle_level(&self) -> Gain {
+        self.ratio_handle_level()
+    }
+
+    fn ratio_for_handle(&self, output: Gain) -> f64 {
+        self.ratio_for_handle(output)
+    }
+
+    fn sidechain(&self) -> &EqModel {
+        &self.sidechain
+    }
+
+    fn update(&mut self, change: ExpanderParameter) {
+        self.update(change)
+    }
+
+    fn threshold_change(threshold: Gain) -> ExpanderParameter {
+        ExpanderParameter::Threshold(threshold)
+    }
+
+    fn ratio_change(ratio: f64) -> ExpanderParameter {
+        ExpanderParameter::Ratio(ratio)
+    }
+
+    fn sidechain_change(index: usize, change: Parameter) -> ExpanderParameter {
+        ExpanderParameter::Sidechain(index, change)
+    }
+
+    fn sidechain_active_change(active: bool) -> ExpanderParameter {
+        ExpanderParameter::SidechainActive(active)
+    }
+
+    /// The mouse wheel adjusts the range, with shift held the hysteresis and with alt held the
+    /// knee.
+    fn wheel_change(&self, step: f64, shift: bool, alt: bool) -> Option<ExpanderParameter> {
+        if shift {
+            let hysteresis = (self.hysteresis + step).clamp(0.0, MAX_HYSTERESIS);
+            Some(hysteresis)
+                .filter(|h| (h - self.hysteresis).abs() > f64::EPSILON)
+                .map(ExpanderParameter::Hysteresis)
+        } else if alt {
+            let knee = (self.knee + step).clamp(0.0, MAX_KNEE);
+            Some(knee)
+                .filter(|k| (k - self.knee).abs() > f64::EPSILON)
+                .map(ExpanderParameter::Knee)
+        } else {
+            let range = (self.range + step).clamp(0.0, MAX_RANGE);
+            Some(range)
+                .filter(|r| (r - self.range).abs() > f64::EPSILON)
+                .map(ExpanderParameter::Range)
+        }
+    }
+}
+
+impl Default for ExpanderModel {
+    fn default() -> Self {
+        ExpanderModel::new(-40.0, 2.0, 40.0, 0.0)
+    }
+}
+
+pub type ExpanderGraph = TransferGraph;
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_expansion_and_range() {
+        let expander = ExpanderModel::new(-40.0, 2.0, 30.0, 0.0);
+
+        assert!(expander.gain_computer(-20.0).abs() < f64::EPSILON);
+        assert!((expander.output(-50.0) - -60.0).abs() < 1e-9);
+        // the attenuation is limited to the range
+        assert!((expander.gain_computer(-80.0) - -30.0).abs() < 1e-9);
+
+        let gate = ExpanderModel::gate(-40.0, 60.0, 0.0);
+        assert!((gate.gain_computer(-41.0) - -39.0).abs() < 1e-9);
+        assert!((gate.gain_computer(-42.0) - -60.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_soft_knee_is_continuous() {
+        let mut expander = ExpanderModel::new(-40.0, 3.0, 80.0, 0.0);
+        expander.update(ExpanderParameter::Knee(10.0));
+
+        assert!(expander.gain_computer(-35.0).abs() < 1e-9);
+        assert!((expander.gain_computer(-45.0) - -10.0).abs() < 1e-9);
+        let curve: Vec<Gain> = (-800..=0)
+            .map(|i| expander.output(i as f64 / 10.0))
+            .collect();
+        assert!(curve.windows(2).all(|w| w[1] > w[0]));
+    }
+
+    #[test]
+    fn test_hysteresis() {
+        let gate = ExpanderModel::gate(-40.0, 60.0, 6.0);
+
+        assert!(!gate.is_open(-42.0, false));
+        assert!(gate.is_open(-40.0, false));
+        assert!(gate.is_open(-45.0, true));
+        assert!(!gate.is_open(-47.0, true));
+
+        let graph = gate.plot(80.0, 80.0);
+        let close = graph.close_threshold.expect("no close threshold");
+        assert!((close.x_start - 34.0).abs() < f64::EPSILON);
+    }
+
+    #[test]
+    fn test_ratio_handle() {
+        let mut expander = ExpanderModel::new(-40.0, 2.0, 80.0, 0.0);
+
+        assert!((expander.ratio_handle_level() - -60.0).abs() < f64::EPSILON);
+        let output = expander.output(expander.ratio_handle_level());
+        assert!((expander.ratio_for_handle(output) - 2.0).abs() < 1e-9);
+        assert!((expander.ratio_for_handle(-50.0) - 1.0).abs() < f64::EPSILON);
+
+        expander.update(ExpanderParameter::Ratio(100.0));
+        assert!((expander.ratio - MAX_RATIO).abs() < f64::EPSILON);
+    }
+
+    #[test]
+    fn test_ratio_handle_with_knee_and_range() {
+        let mut expander = ExpanderModel::new(-40.0, 3.0, 30.0, 0.0);
+        expander.update(ExpanderParameter::Knee(10.0));
+
+        // the handle sits on the range floor, where every ratio of at least 2.5 ends up
+        assert!((expander.output(-60.0) - -90.0).abs() < 1e-9);
+        assert!((expander.ratio_for_handle(-90.0) - 3.0).abs() < f64::EPSILON);
+        assert!((expander.ratio_for_handle(-70.0) - 1.5).abs() < 1e-9);
+        expander.update(ExpanderParameter::Ratio(2.0));
+        assert!((expander.ratio_for_handle(-100.0) - 2.5).abs() < 1e-9);
+
+        // within a wide knee
+        let mut expander = ExpanderModel::new(-40.0, 2.0, 80.0, 0.0);
+        expander.update(ExpanderParameter::Knee(24.0));
+        expander.min_level = -50.0;
+        let output = expander.output(expander.ratio_handle_level());
+        assert!((expander.ratio_for_handle(output) - 2.0).abs() < 1e-9);
+
+        // with the threshold at the bottom of the plot the handle does not change the ratio
+        expander.update(ExpanderParameter::Knee(0.0));
+        expander.update(ExpanderParameter::Threshold(-50.0));
+        assert!((expander.ratio_for_handle(-80.0) - 2.0).abs() < f64::EPSILON);
+    }
+}
@@ -1 +1,8 @@
+mod common;
 
+pub use crate::expander::common::*;
+
+#[cfg(feature = "yew-components")]
+mod yew_component;
+#[cfg(feature = "yew-components")]
+pub use crate::expander::yew_component::*;
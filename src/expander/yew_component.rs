@@ -0,0 +1,72 @@
+use crate::dynamics::*;
+use crate::expander::*;
+use crate::*;
+use derivative::*;
+use yew::prelude::*;
+
+/// The transfer curve of an expander or gate, with an indicator showing whether the gate is
+/// open.
+pub struct Expander {
+    props: Props,
+}
+
+#[derive(Derivative, Properties)]
+#[derivative(Debug, Clone, PartialEq)]
+pub struct Props {
+    pub id: String,
+    pub expander: ExpanderModel,
+    /// The current input level, shown as a dot on the transfer curve.
+    pub input_level: Option<Gain>,
+    /// Whether the gate is currently open, shown by an indicator.
+    pub gate_open: Option<bool>,
+    pub width: f64,
+    pub height: f64,
+    /// Shows an editable view of the side chain filter below the transfer curve.
+    #[prop_or_default]
+    pub show_sidechain: bool,
+    #[derivative(PartialEq = "ignore")]
+    pub on_input: Option<Callback<ExpanderParameter>>,
+}
+
+impl Component for Expander {
+    type Message = ();
+
+    type Properties = Props;
+
+    fn create(props: Self::Properties, _link: ComponentLink<Self>) -> Self {
+        Expander { props }
+    }
+
+    fn update(&mut self, _msg: Self::Message) -> ShouldRender {
+        false
+    }
+
+    fn change(&mut self, props: Self::Properties) -> ShouldRender {
+        let changed = props != self.props;
+        self.props = props;
+        changed
+    }
+
+    fn view(&self) -> Html {
+        let indicator_class = match self.props.gate_open {
+            Some(true) => "gate-indicator open",
+            Some(false) => "gate-indicator closed",
+            None => "gate-indicator",
+        };
+
+        html! {
+            <TransferCurve<ExpanderModel>
+                id={self.props.id.clone()}
+                model={self.props.expander.clone()}
+                class="expander"
+                input_level={self.props.input_level}
+                width={self.props.width}
+                height={self.props.height}
+                show_sidechain={self.props.show_sidechain}
+                on_input={self.props.on_input.clone()}
+            >
+                <div class={indicator_class} />
+            </TransferCurve<ExpanderModel>>
+        }
+    }
+}
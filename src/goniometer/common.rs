@@ -0,0 +1,144 @@
+use crate::goniometer::plotter;
+use crate::*;
+use std::collections::VecDeque;
+
+/// Points whose intensity falls below this value are dropped from the buffer.
+const MIN_INTENSITY: Intensity = 1.0 / 256.0;
+/// The fraction of the plot radius auto-gain aims to fill with the loudest recent sample.
+const AUTO_GAIN_TARGET: f64 = 0.9;
+
+/// A single point of the vectorscope trace, already rotated into M/S space so that mono signals
+/// end up on the vertical axis and signals only present on the left channel point to the upper
+/// left.
+#[derive(Debug, PartialEq, Clone)]
+pub struct GoniometerPoint {
+    pub side: f64,
+    pub mid: f64,
+    pub intensity: Intensity,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct GoniometerModel {
+    pub points: VecDeque<GoniometerPoint>,
+    pub capacity: usize,
+    /// Number of samples after which a point has faded to 1/e of its initial intensity.
+    pub persistence: f64,
+    pub auto_gain: bool,
+    /// Manual gain in dB, used when auto-gain is disabled.
+    pub gain: Gain,
+    pub max_gain: Gain,
+    /// Number of samples after which the auto-gain envelope has released to 1/e.
+    pub auto_gain_release: f64,
+    envelope: f64,
+}
+
+impl GoniometerModel {
+    pub fn new(capacity: usize, persistence: f64) -> GoniometerModel {
+        GoniometerModel {
+            points: VecDeque::with_capacity(capacity),
+            capacity,
+            persistence,
+            auto_gain: true,
+            gain: 0.0,
+            max_gain: 24.0,
+            auto_gain_release: persistence * 10.0,
+            envelope: 0.0,
+        }
+    }
+
+    pub fn push_block(&mut self, left: &[Sample], right: &[Sample]) {
+        let samples = left.iter().zip(right.iter()).map(|(l, r)| (*l, *r));
+        self.push_samples(samples.collect());
+    }
+
+    pub fn push_interleaved(&mut self, block: &[Sample]) {
+        let samples = block.chunks_exact(2).map(|s| (s[0], s[1]));
+        self.push_samples(samples.collect());
+    }
+
+    pub fn clear(&mut self) {
+        self.points.clear();
+        self.envelope = 0.0;
+    }
+
+    /// The gain in dB that is currently applied when projecting points, either the manual gain or
+    /// the one determined by auto-gain.
+    pub fn effective_gain(&self) -> Gain {
+        if self.auto_gain {
+            let gain = 20.0 * (AUTO_GAIN_TARGET / self.envelope).log10();
+            gain.max(0.0).min(self.max_gain)
+        } else {
+            self.gain
+        }
+    }
+
+    pub fn plot(&self, width: f64, height: f64) -> GoniometerGraph {
+        plotter::plot_goniometer(self, width, height)
+    }
+
+    fn push_samples(&mut self, samples: Vec<(Sample, Sample)>) {
+        let len = samples.len();
+        if len == 0 {
+            return;
+        }
+
+        let decay = decay_factor(self.persistence);
+        let release = decay_factor(self.auto_gain_release);
+
+        for point in self.points.iter_mut() {
+            point.intensity *= decay.powi(len as i32);
+        }
+
+        for (i, (l, r)) in samples.into_iter().enumerate() {
+            let (side, mid) = rotate(l as f64, r as f64);
+
+            let radius = (side.powi(2) + mid.powi(2)).sqrt();
+            self.envelope = radius.max(self.envelope * release);
+
+            let intensity = decay.powi((len - 1 - i) as i32);
+            if intensity >= MIN_INTENSITY {
+                self.points.push_back(GoniometerPoint {
+                    side,
+                    mid,
+                    intensity,
+                });
+            }
+        }
+
+        while let Some(point) = self.points.front() {
+            if point.intensity >= MIN_INTENSITY {
+                break;
+            }
+            self.points.pop_front();
+        }
+
+        while self.points.len() > self.capacity {
+            self.points.pop_front();
+        }
+    }
+}
+
+impl Default for GoniometerModel {
+    fn default() -> Self {
+        GoniometerModel::new(4_096, 1_024.0)
+    }
+}
+
+pub struct GoniometerGraph {
+    pub axes: Vec<Line>,
+    pub points: Vec<(X, Y, Intensity)>,
+}
+
+fn rotate(left: f64, right: f64) -> (f64, f64) {
+    let side = (right - left) * std::f64::consts::FRAC_1_SQRT_2;
+    let mid = (left + right) * std::f64::consts::FRAC_1_SQRT_2;
+    (side, mid)
+}
+
+fn decay_factor(time_constant: f64) -> f64 {
+    if time_constant > 0.0 {
+        (-1.0 / time_constant).exp()
+    } else {
+        0.0
+    }
+}
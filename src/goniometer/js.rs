@@ -0,0 +1,72 @@
+use crate::goniometer::common::*;
+use crate::js_utils::*;
+use crate::*;
+use web_sys::CanvasRenderingContext2d;
+use web_sys::HtmlCanvasElement;
+
+pub struct CanvasGoniometerRenderer {
+    pub context: CanvasRenderingContext2d,
+    pub style: Style,
+    pub bounds: Bounds,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Style {
+    background_fill: Option<String>,
+    axis_stroke: Option<String>,
+    trace_fill: Option<String>,
+}
+
+impl CanvasGoniometerRenderer {
+    pub fn new(canvas: HtmlCanvasElement) -> Option<CanvasGoniometerRenderer> {
+        let context = get_context_2d(&canvas)?;
+        let style = get_styles(&canvas);
+
+        let bounds = canvas.get_bounding_client_rect().into();
+
+        let background_fill = get_style("--background-fill", &style, Some("black"));
+        let axis_stroke = get_style("--axis-stroke", &style, Some("#333"));
+        let trace_fill = get_style("--trace-fill", &style, Some("#8fd7e0"));
+
+        let style = Style {
+            background_fill,
+            axis_stroke,
+            trace_fill,
+        };
+
+        Some(CanvasGoniometerRenderer {
+            context,
+            style,
+            bounds,
+        })
+    }
+
+    pub fn render_to_canvas(&self, goniometer: &GoniometerModel) {
+        let width = self.bounds.width;
+        let height = self.bounds.height;
+
+        let context = &self.context;
+
+        let graph = goniometer.plot(width, height);
+
+        context.clear_rect(0.0, 0.0, width, height);
+
+        set_fill(context, self.style.background_fill.as_ref());
+        context.fill_rect(0.0, 0.0, width, height);
+
+        context.begin_path();
+        set_stroke(context, self.style.axis_stroke.as_ref());
+        for axis in &graph.axes {
+            context.move_to(axis.x_start.floor() + 0.5, axis.y_start.floor() + 0.5);
+            context.line_to(axis.x_end.floor() + 0.5, axis.y_end.floor() + 0.5);
+        }
+        context.stroke();
+
+        set_fill(context, self.style.trace_fill.as_ref());
+        for (x, y, intensity) in graph.points {
+            context.set_global_alpha(intensity);
+            context.fill_rect(x - 0.75, y - 0.75, 1.5, 1.5);
+        }
+        context.set_global_alpha(1.0);
+    }
+}
@@ -0,0 +1,15 @@
+mod common;
+mod plotter;
+
+pub use crate::goniometer::common::*;
+pub use crate::goniometer::plotter::*;
+
+#[cfg(feature = "js")]
+mod js;
+#[cfg(feature = "js")]
+pub use crate::goniometer::js::*;
+
+#[cfg(feature = "yew-components")]
+mod yew_component;
+#[cfg(feature = "yew-components")]
+pub use crate::goniometer::yew_component::*;
@@ -0,0 +1,123 @@
+use crate::goniometer::common::*;
+use crate::utils::to_amplitude;
+use crate::*;
+use std::f64::consts::FRAC_1_SQRT_2;
+
+pub fn plot_goniometer(goniometer: &GoniometerModel, width: f64, height: f64) -> GoniometerGraph {
+    let center_x = width / 2.0;
+    let center_y = height / 2.0;
+    let radius = center_x.min(center_y);
+
+    let axes = axes(center_x, center_y, radius);
+
+    let factor = to_amplitude(goniometer.effective_gain()) * radius;
+
+    let points = goniometer
+        .points
+        .iter()
+        .map(|p| {
+            let x = center_x + p.side * factor;
+            let y = center_y - p.mid * factor;
+            (x, y, p.intensity)
+        })
+        .filter(|(x, y, _)| (0.0..=width).contains(x) && (0.0..=height).contains(y))
+        .collect();
+
+    GoniometerGraph { axes, points }
+}
+
+fn axes(center_x: X, center_y: Y, radius: f64) -> Vec<Line> {
+    let diagonal = radius * FRAC_1_SQRT_2;
+
+    let left = Line {
+        x_start: center_x - diagonal,
+        y_start: center_y - diagonal,
+        x_end: center_x + diagonal,
+        y_end: center_y + diagonal,
+        value: 0.0,
+    };
+    let right = Line {
+        x_start: center_x + diagonal,
+        y_start: center_y - diagonal,
+        x_end: center_x - diagonal,
+        y_end: center_y + diagonal,
+        value: 0.0,
+    };
+    let mid = Line {
+        x_start: center_x,
+        y_start: center_y - radius,
+        x_end: center_x,
+        y_end: center_y + radius,
+        value: 0.0,
+    };
+    let side = Line {
+        x_start: center_x - radius,
+        y_start: center_y,
+        x_end: center_x + radius,
+        y_end: center_y,
+        value: 0.0,
+    };
+
+    vec![left, right, mid, side]
+}
+
+#[cfg(test)]
+mod test {
+
+    use super::*;
+
+    fn manual_gain_model() -> GoniometerModel {
+        let mut model = GoniometerModel::new(16, 1_000.0);
+        model.auto_gain = false;
+        model
+    }
+
+    #[test]
+    fn test_mono_signal_is_vertical() {
+        let mut model = manual_gain_model();
+        model.push_block(&[0.5], &[0.5]);
+
+        let graph = model.plot(200.0, 200.0);
+        let (x, y, _) = graph.points[0];
+
+        assert!((x - 100.0).abs() < 1e-9);
+        assert!(y < 100.0);
+    }
+
+    #[test]
+    fn test_left_only_points_to_upper_left() {
+        let mut model = manual_gain_model();
+        model.push_block(&[0.5], &[0.0]);
+
+        let graph = model.plot(200.0, 200.0);
+        let (x, y, _) = graph.points[0];
+
+        assert!(x < 100.0);
+        assert!(y < 100.0);
+        assert!(((100.0 - x) - (100.0 - y)).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_points_fade_and_are_bounded() {
+        let mut model = GoniometerModel::new(8, 2.0);
+        model.push_block(&[0.1; 4], &[0.2; 4]);
+
+        assert_eq!(4, model.points.len());
+        assert!(model.points[0].intensity < model.points[3].intensity);
+        assert!((model.points[3].intensity - 1.0).abs() < f64::EPSILON);
+
+        model.push_block(&[0.1; 32], &[0.2; 32]);
+        assert_eq!(8, model.points.len());
+    }
+
+    #[test]
+    fn test_auto_gain_fills_plot() {
+        let mut model = GoniometerModel::new(16, 1_000.0);
+        model.push_block(&[0.1], &[0.1]);
+
+        let graph = model.plot(200.0, 200.0);
+        let (_, y, _) = graph.points[0];
+
+        assert!(y < 50.0);
+    }
+}
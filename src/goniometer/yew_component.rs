@@ -0,0 +1,97 @@
+use crate::goniometer::*;
+use crate::js_utils::*;
+use derivative::*;
+use wasm_bindgen::prelude::*;
+use web_sys::HtmlCanvasElement;
+use yew::prelude::*;
+
+pub struct Goniometer {
+    props: Props,
+    canvas: NodeRef,
+    renderer: Option<CanvasGoniometerRenderer>,
+    render_callback: Closure<dyn FnMut()>,
+    needs_repaint: bool,
+}
+
+#[derive(Derivative, Properties)]
+#[derivative(Debug, Clone, PartialEq)]
+pub struct Props {
+    pub id: String,
+    /// Not compared on changes, its point buffer is large and changes with every block anyway.
+    /// A new goniometer is always repainted.
+    #[derivative(PartialEq = "ignore")]
+    pub goniometer: GoniometerModel,
+    pub width: f64,
+    pub height: f64,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Msg {
+    Render,
+}
+
+impl Component for Goniometer {
+    type Message = Msg;
+
+    type Properties = Props;
+
+    fn create(props: Self::Properties, link: ComponentLink<Self>) -> Self {
+        let render_callback =
+            Closure::wrap(Box::new(move || link.send_message(Msg::Render)) as Box<dyn FnMut()>);
+        Goniometer {
+            props,
+            canvas: NodeRef::default(),
+            renderer: None,
+            render_callback,
+            needs_repaint: false,
+        }
+    }
+
+    fn update(&mut self, msg: Self::Message) -> ShouldRender {
+        match msg {
+            Msg::Render => self.render(),
+        }
+        false
+    }
+
+    fn change(&mut self, props: Self::Properties) -> ShouldRender {
+        let changed = props != self.props;
+        self.props = props;
+        self.schedule_repaint();
+        changed
+    }
+
+    fn view(&self) -> Html {
+        html! {
+            <canvas id={self.props.id.clone()} class="goniometer" ref=self.canvas.clone() width={self.props.width}
+            height={self.props.height}/>
+        }
+    }
+
+    fn rendered(&mut self, first_render: bool) {
+        if first_render {
+            self.renderer = self
+                .canvas
+                .cast::<HtmlCanvasElement>()
+                .and_then(CanvasGoniometerRenderer::new);
+        }
+
+        self.schedule_repaint();
+    }
+}
+
+impl Goniometer {
+    fn render(&mut self) {
+        self.needs_repaint = false;
+        if let Some(renderer) = self.renderer.as_ref() {
+            renderer.render_to_canvas(&self.props.goniometer);
+        }
+    }
+
+    fn schedule_repaint(&mut self) {
+        if !self.needs_repaint {
+            self.needs_repaint = true;
+            request_animation_frame(&self.render_callback);
+        }
+    }
+}
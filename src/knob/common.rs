@@ -0,0 +1,175 @@
+use crate::knob::plotter;
+use crate::scale::ScaleModel;
+use crate::*;
+use scales::prelude::*;
+use std::f64::consts::PI;
+use std::fmt::Debug;
+
+/// The default sweep in degrees, leaving a gap of a quarter turn at the bottom.
+pub const DEFAULT_SWEEP: f64 = 270.0;
+pub const MIN_SWEEP: f64 = 30.0;
+pub const MAX_SWEEP: f64 = 360.0;
+
+/// Where the value arc starts.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum KnobArc {
+    /// The arc runs from the minimum to the value.
+    Unipolar,
+    /// The arc runs from the scale's default value, or its middle, to the value.
+    Bipolar,
+}
+
+/// How dragging the pointer changes the value.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DragMode {
+    /// Dragging up increases the value, regardless of where the knob is grabbed.
+    Vertical,
+    /// The knob turns to point at the pointer.
+    Circular,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct KnobModel<S: Scale<f64>> {
+    pub min: KnobValue,
+    pub max: KnobValue,
+    pub value: KnobValue,
+    pub scale: ScaleModel<S>,
+    /// The angle in degrees between the minimum and the maximum position.
+    pub sweep: f64,
+    pub arc: KnobArc,
+    pub drag_mode: DragMode,
+}
+
+impl<S: Scale<f64>> KnobModel<S> {
+    pub fn new(scale: ScaleModel<S>) -> KnobModel<S> {
+        KnobModel {
+            min: scale.min(),
+            max: scale.max(),
+            value: scale.default_value.unwrap_or_else(|| scale.min()),
+            scale,
+            sweep: DEFAULT_SWEEP,
+            arc: KnobArc::Unipolar,
+            drag_mode: DragMode::Vertical,
+        }
+    }
+
+    /// Creates a knob for values around a centre, e.g. pan or the gain of an EQ band.
+    pub fn bipolar(scale: ScaleModel<S>) -> KnobModel<S> {
+        let mut knob = KnobModel::new(scale);
+        knob.arc = KnobArc::Bipolar;
+        knob.value = knob.origin();
+        knob
+    }
+
+    pub fn update(&mut self, value: KnobValue) {
+        self.value = value;
+    }
+
+    pub fn set_sweep(&mut self, sweep: f64) {
+        self.sweep = sweep.clamp(MIN_SWEEP, MAX_SWEEP);
+    }
+
+    /// The value the arc starts at.
+    pub fn origin(&self) -> KnobValue {
+        match self.arc {
+            KnobArc::Unipolar => self.min,
+            KnobArc::Bipolar => self
+                .scale
+                .default_value
+                .unwrap_or_else(|| self.scale.to_absolute(0.5)),
+        }
+    }
+
+    /// The canvas angle in radians of the minimum position. Angles grow clockwise from the
+    /// positive x axis and the sweep is centred around the top.
+    pub fn start_angle(&self) -> f64 {
+        -PI / 2.0 - self.sweep.to_radians() / 2.0
+    }
+
+    /// The canvas angle in radians the knob points at for `value`.
+    pub fn angle(&self, value: KnobValue) -> f64 {
+        let relative = self.scale.to_relative(value).clamp(0.0, 1.0);
+        self.start_angle() + relative * self.sweep.to_radians()
+    }
+
+    /// The value the knob has when pointing at the canvas angle `angle`. Angles in the gap
+    /// outside of the sweep give the closer end of the scale.
+    pub fn value_at_angle(&self, angle: f64) -> KnobValue {
+        let sweep = self.sweep.to_radians();
+        let offset = (angle - self.start_angle()).rem_euclid(2.0 * PI);
+        let relative = if offset <= sweep {
+            offset / sweep
+        } else if offset - sweep < 2.0 * PI - offset {
+            1.0
+        } else {
+            0.0
+        };
+        self.scale.to_absolute(relative)
+    }
+
+    pub fn plot(&self, width: f64, height: f64) -> KnobGraph {
+        plotter::plot_knob(self, width, height)
+    }
+}
+
+/// An arc around the knob's centre between two canvas angles.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AngleRange {
+    pub start_angle: f64,
+    pub end_angle: f64,
+}
+
+pub struct KnobGraph {
+    pub x: X,
+    pub y: Y,
+    pub radius: Radius,
+    /// The whole sweep.
+    pub track: AngleRange,
+    /// The part of the sweep between the origin and the value.
+    pub value: AngleRange,
+    pub pointer: Line,
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::scale::{Layout, VerticalPosition};
+
+    fn gain_scale() -> ScaleModel<GainScale> {
+        ScaleModel::new(
+            GainScale::new(-12.0, 12.0),
+            Layout::Vertical(VerticalPosition::Left),
+            Some(0.0),
+            vec![-12.0, 0.0, 12.0],
+            vec![],
+        )
+    }
+
+    #[test]
+    fn test_angles() {
+        let knob = KnobModel::new(gain_scale());
+        assert!(knob.value.abs() < f64::EPSILON);
+        assert!((knob.angle(-12.0) - -1.25 * PI).abs() < 1e-9);
+        assert!((knob.angle(0.0) - -0.5 * PI).abs() < 1e-9);
+        assert!((knob.angle(12.0) - 0.25 * PI).abs() < 1e-9);
+
+        assert!(knob.value_at_angle(-0.5 * PI).abs() < 1e-9);
+        assert!((knob.value_at_angle(0.75 * PI) - -12.0).abs() < 1e-9);
+        // the gap at the bottom snaps to the closer end
+        assert!((knob.value_at_angle(0.45 * PI) - 12.0).abs() < 1e-9);
+        assert!((knob.value_at_angle(0.55 * PI) - -12.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_arc_origin() {
+        let mut knob = KnobModel::bipolar(gain_scale());
+        assert_eq!(KnobArc::Bipolar, knob.arc);
+        assert!(knob.origin().abs() < f64::EPSILON);
+
+        knob.arc = KnobArc::Unipolar;
+        assert!((knob.origin() - -12.0).abs() < f64::EPSILON);
+
+        knob.set_sweep(720.0);
+        assert!((knob.sweep - MAX_SWEEP).abs() < f64::EPSILON);
+    }
+}
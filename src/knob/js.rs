@@ -0,0 +1,121 @@
+use crate::js_utils::*;
+use crate::knob::common::*;
+use crate::*;
+use std::f64::consts::PI;
+use web_sys::CanvasRenderingContext2d;
+use web_sys::HtmlCanvasElement;
+
+/// The width of the track and value arcs at the rim.
+const ARC_WIDTH: f64 = 4.0;
+/// The gap between the arcs and the knob's body.
+const BODY_INSET: f64 = 3.0;
+const POINTER_WIDTH: f64 = 2.0;
+
+pub struct CanvasKnobRenderer {
+    pub context: CanvasRenderingContext2d,
+    pub style: Style,
+    pub bounds: Bounds,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Style {
+    track_stroke: Option<String>,
+    value_stroke: Option<String>,
+    body_fill: Option<String>,
+    pointer_stroke: Option<String>,
+}
+
+impl CanvasKnobRenderer {
+    pub fn new(canvas: HtmlCanvasElement) -> Option<CanvasKnobRenderer> {
+        let context = get_context_2d(&canvas)?;
+        let style = get_styles(&canvas);
+
+        let bounds = canvas.get_bounding_client_rect().into();
+
+        let track_stroke = get_style("--track-stroke", &style, Some("#222"));
+        let value_stroke = get_style("--value-stroke", &style, Some("#f59b38"));
+        let body_fill = get_style("--body-fill", &style, Some("#555"));
+        let pointer_stroke = get_style("--pointer-stroke", &style, Some("#ccc"));
+
+        let style = Style {
+            track_stroke,
+            value_stroke,
+            body_fill,
+            pointer_stroke,
+        };
+
+        Some(CanvasKnobRenderer {
+            context,
+            style,
+            bounds,
+        })
+    }
+
+    pub fn render_to_canvas(&self, graph: &KnobGraph) {
+        let width = self.bounds.width;
+        let height = self.bounds.height;
+
+        let context = &self.context;
+
+        context.clear_rect(0.0, 0.0, width, height);
+
+        let arc_radius = (graph.radius - ARC_WIDTH / 2.0).max(0.0);
+        context.set_line_width(ARC_WIDTH);
+        self.stroke_arc(
+            graph,
+            arc_radius,
+            &graph.track,
+            self.style.track_stroke.as_ref(),
+        );
+        self.stroke_arc(
+            graph,
+            arc_radius,
+            &graph.value,
+            self.style.value_stroke.as_ref(),
+        );
+
+        let body_radius = (graph.radius - ARC_WIDTH - BODY_INSET).max(0.0);
+        context.begin_path();
+        set_fill(context, self.style.body_fill.as_ref());
+        context
+            .arc(graph.x, graph.y, body_radius, 0.0, 2.0 * PI)
+            .expect("arc failed");
+        context.fill();
+
+        // the pointer ends at the body's rim
+        let pointer = &graph.pointer;
+        let scale = body_radius / graph.radius;
+        context.begin_path();
+        context.set_line_width(POINTER_WIDTH);
+        set_stroke(context, self.style.pointer_stroke.as_ref());
+        context.move_to(
+            graph.x + (pointer.x_start - graph.x) * scale,
+            graph.y + (pointer.y_start - graph.y) * scale,
+        );
+        context.line_to(
+            graph.x + (pointer.x_end - graph.x) * scale,
+            graph.y + (pointer.y_end - graph.y) * scale,
+        );
+        context.stroke();
+        context.set_line_width(1.0);
+    }
+
+    fn stroke_arc(
+        &self,
+        graph: &KnobGraph,
+        radius: Radius,
+        arc: &AngleRange,
+        stroke: Option<&String>,
+    ) {
+        if arc.end_angle - arc.start_angle <= f64::EPSILON {
+            return;
+        }
+        let context = &self.context;
+        context.begin_path();
+        set_stroke(context, stroke);
+        context
+            .arc(graph.x, graph.y, radius, arc.start_angle, arc.end_angle)
+            .expect("arc failed");
+        context.stroke();
+    }
+}
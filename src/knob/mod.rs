@@ -0,0 +1,15 @@
+mod common;
+mod plotter;
+
+pub use crate::knob::common::*;
+pub use crate::knob::plotter::*;
+
+#[cfg(feature = "js")]
+mod js;
+#[cfg(feature = "js")]
+pub use crate::knob::js::*;
+
+#[cfg(feature = "yew-components")]
+mod yew_component;
+#[cfg(feature = "yew-components")]
+pub use crate::knob::yew_component::*;
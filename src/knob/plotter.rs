@@ -0,0 +1,70 @@
+use crate::knob::common::*;
+use crate::*;
+use scales::prelude::*;
+
+/// The pointer starts this far from the centre, relative to the radius.
+const POINTER_INSET: f64 = 0.4;
+
+pub fn plot_knob<S: Scale<f64>>(knob: &KnobModel<S>, width: f64, height: f64) -> KnobGraph {
+    let x = width / 2.0;
+    let y = height / 2.0;
+    let radius = x.min(y);
+
+    let track = AngleRange {
+        start_angle: knob.start_angle(),
+        end_angle: knob.angle(knob.max),
+    };
+
+    let origin = knob.angle(knob.origin());
+    let angle = knob.angle(knob.value);
+    let value = AngleRange {
+        start_angle: origin.min(angle),
+        end_angle: origin.max(angle),
+    };
+
+    let (sin, cos) = angle.sin_cos();
+    let pointer = Line {
+        x_start: x + cos * radius * POINTER_INSET,
+        y_start: y + sin * radius * POINTER_INSET,
+        x_end: x + cos * radius,
+        y_end: y + sin * radius,
+        value: knob.value,
+    };
+
+    KnobGraph {
+        x,
+        y,
+        radius,
+        track,
+        value,
+        pointer,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::scale::{Layout, ScaleModel, VerticalPosition};
+
+    #[test]
+    fn test_plot_knob() {
+        let scale = ScaleModel::new(
+            LinearScale::new(-100.0, 100.0),
+            Layout::Vertical(VerticalPosition::Left),
+            None,
+            vec![],
+            vec![],
+        );
+        let mut knob = KnobModel::bipolar(scale);
+        knob.update(-100.0);
+
+        let graph = knob.plot(60.0, 40.0);
+
+        assert!((graph.radius - 20.0).abs() < f64::EPSILON);
+        assert!((graph.value.start_angle - graph.track.start_angle).abs() < 1e-9);
+        assert!((graph.value.end_angle - -std::f64::consts::FRAC_PI_2).abs() < 1e-9);
+        // at the minimum the pointer points to the lower left
+        assert!(graph.pointer.x_end < graph.x);
+        assert!(graph.pointer.y_end > graph.y);
+    }
+}
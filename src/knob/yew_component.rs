@@ -0,0 +1,314 @@
+use crate::js_utils::*;
+use crate::knob::*;
+use crate::*;
+use derivative::*;
+use scales::prelude::*;
+use std::fmt::Debug;
+use wasm_bindgen::prelude::*;
+use web_sys::HtmlCanvasElement;
+use yew::prelude::*;
+
+/// Dragging this many pixels vertically moves the knob across its whole range.
+const VERTICAL_DRAG_RANGE: f64 = 200.0;
+/// Holding shift while dragging divides the movement by this.
+const FINE_ADJUSTMENT: f64 = 10.0;
+
+pub struct Knob<KnobScale: Scale<f64> + Debug + Clone + PartialEq + 'static> {
+    props: Props<KnobScale>,
+    ext_props: Option<Props<KnobScale>>,
+    link: ComponentLink<Self>,
+    canvas: NodeRef,
+    dragging: bool,
+    last_touch: Option<(X, Y)>,
+    renderer: Option<CanvasKnobRenderer>,
+    render_callback: Closure<dyn FnMut()>,
+    needs_repaint: bool,
+}
+
+#[derive(Derivative, Properties)]
+#[derivative(Debug, Clone, PartialEq)]
+pub struct Props<KnobScale: Scale<f64> + Clone + PartialEq> {
+    pub id: String,
+    pub knob: KnobModel<KnobScale>,
+    pub label: String,
+    /// The format of the value shown below the knob.
+    pub value_format: LabelFormat,
+    /// The width and height of the knob.
+    pub size: f64,
+    #[derivative(PartialEq = "ignore")]
+    pub on_input: Option<Callback<KnobValue>>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Msg {
+    MouseDown(MouseEvent),
+    MouseUp(MouseEvent),
+    MouseMove(MouseEvent),
+    TouchStart(TouchEvent),
+    TouchEnd(TouchEvent),
+    TouchMove(TouchEvent),
+    Wheel(WheelEvent),
+    DoubleClick(MouseEvent),
+    Render,
+}
+
+impl<KnobScale: Scale<f64> + Debug + Clone + PartialEq + 'static> Component for Knob<KnobScale> {
+    type Message = Msg;
+
+    type Properties = Props<KnobScale>;
+
+    fn create(props: Self::Properties, link: ComponentLink<Self>) -> Self {
+        let cb_link = link.clone();
+        let render_callback =
+            Closure::wrap(Box::new(move || cb_link.send_message(Msg::Render)) as Box<dyn FnMut()>);
+
+        let cb_link = link.clone();
+        let mouse_moved =
+            Closure::wrap(Box::new(move |e| cb_link.send_message(Msg::MouseMove(e)))
+                as Box<dyn Fn(MouseEvent)>);
+
+        let cb_link = link.clone();
+        let mouse_up = Closure::wrap(
+            Box::new(move |e| cb_link.send_message(Msg::MouseUp(e))) as Box<dyn Fn(MouseEvent)>
+        );
+
+        register_global_listener("mousemove", &mouse_moved);
+        register_global_listener("mouseup", &mouse_up);
+
+        mouse_moved.forget();
+        mouse_up.forget();
+
+        Knob {
+            props,
+            ext_props: None,
+            link,
+            canvas: NodeRef::default(),
+            dragging: false,
+            last_touch: None,
+            renderer: None,
+            render_callback,
+            needs_repaint: false,
+        }
+    }
+
+    fn update(&mut self, msg: Self::Message) -> ShouldRender {
+        let knob = self.props.knob.clone();
+        match msg {
+            Msg::MouseDown(e) => self.handle_mouse_down(e),
+            Msg::MouseUp(_) => self.handle_up(),
+            Msg::MouseMove(e) => self.handle_mouse_move(e),
+            Msg::TouchStart(e) => self.handle_touch_start(e),
+            Msg::TouchEnd(_) => self.handle_up(),
+            Msg::TouchMove(e) => self.handle_touch_move(e),
+            Msg::Wheel(e) => self.handle_wheel(e),
+            Msg::DoubleClick(_) => self.handle_double_click(),
+            Msg::Render => self.render(),
+        }
+        // the value display needs updating
+        self.props.knob != knob
+    }
+
+    fn change(&mut self, props: Self::Properties) -> ShouldRender {
+        let resized = props.size != self.props.size;
+        // don't accept external changes while the knob is being dragged
+        if self.dragging {
+            self.ext_props = Some(props);
+            return false;
+        }
+        if props == self.props {
+            return false;
+        }
+        self.props = props;
+        if resized {
+            self.renderer = None;
+        }
+        self.schedule_repaint();
+        true
+    }
+
+    fn view(&self) -> Html {
+        let mouse_down_callback = self.link.callback(Msg::MouseDown);
+        let touch_start_callback = self.link.callback(Msg::TouchStart);
+        let touch_end_callback = self.link.callback(Msg::TouchEnd);
+        let touch_move_callback = self.link.callback(Msg::TouchMove);
+        let wheel_callback = self.link.callback(Msg::Wheel);
+        let double_click_callback = self.link.callback(Msg::DoubleClick);
+
+        let size = self.props.size;
+        let value = self.props.value_format.format(self.props.knob.value);
+
+        let class = match self.props.knob.drag_mode {
+            DragMode::Vertical => "knob",
+            DragMode::Circular => "knob circular",
+        };
+
+        html! {
+            <div class={class}>
+                <canvas
+                    id={self.props.id.clone()}
+                    ref=self.canvas.clone()
+                    width={size}
+                    height={size}
+                    onmousedown={mouse_down_callback}
+                    ontouchstart={touch_start_callback}
+                    ontouchend={touch_end_callback}
+                    ontouchmove={touch_move_callback}
+                    onwheel={wheel_callback}
+                    ondblclick={double_click_callback}
+                />
+                <span class="knob-value">{value}</span>
+                <span class="knob-label">{&self.props.label}</span>
+            </div>
+        }
+    }
+
+    fn rendered(&mut self, _first_render: bool) {
+        if self.renderer.is_none() {
+            self.renderer = self
+                .canvas
+                .cast::<HtmlCanvasElement>()
+                .and_then(CanvasKnobRenderer::new);
+        }
+
+        self.schedule_repaint();
+    }
+}
+
+impl<KnobScale: Scale<f64> + Debug + Clone + PartialEq + 'static> Knob<KnobScale> {
+    fn render(&mut self) {
+        self.needs_repaint = false;
+        if let Some(renderer) = self.renderer.as_ref() {
+            let graph = self.props.knob.plot(self.props.size, self.props.size);
+            renderer.render_to_canvas(&graph);
+        }
+    }
+
+    fn schedule_repaint(&mut self) {
+        if !self.needs_repaint {
+            self.needs_repaint = true;
+            request_animation_frame(&self.render_callback);
+        }
+    }
+
+    fn handle_mouse_down(&mut self, e: MouseEvent) {
+        if e.button() != 0 {
+            return;
+        }
+        self.handle_down(e.client_x() as f64, e.client_y() as f64);
+    }
+
+    fn handle_mouse_move(&mut self, e: MouseEvent) {
+        if !self.dragging {
+            return;
+        }
+        let d_y = if e.shift_key() {
+            e.movement_y() as f64 / FINE_ADJUSTMENT
+        } else {
+            e.movement_y() as f64
+        };
+        self.handle_move(e.client_x() as f64, e.client_y() as f64, d_y);
+    }
+
+    fn handle_touch_start(&mut self, e: TouchEvent) {
+        if e.target_touches().length() != 1 {
+            return;
+        }
+        if let Some(touch) = e.changed_touches().get(0) {
+            let x = touch.client_x() as f64;
+            let y = touch.client_y() as f64;
+            self.last_touch = Some((x, y));
+            self.handle_down(x, y);
+        }
+    }
+
+    fn handle_touch_move(&mut self, e: TouchEvent) {
+        if e.target_touches().length() != 1 {
+            return;
+        }
+        if let (true, Some(touch)) = (self.dragging, e.changed_touches().get(0)) {
+            let x = touch.client_x() as f64;
+            let y = touch.client_y() as f64;
+            if let Some((_, last_y)) = self.last_touch {
+                self.handle_move(x, y, y - last_y);
+            }
+            self.last_touch = Some((x, y));
+        }
+        e.prevent_default();
+    }
+
+    fn handle_wheel(&mut self, e: WheelEvent) {
+        let knob = &self.props.knob;
+        let conv = (&PixelScale::inverted(0.0, VERTICAL_DRAG_RANGE), &knob.scale);
+        let dampening = 2.0;
+        let delta = e.delta_y().signum() * dampening;
+        let value = knob.value;
+        let new_value = conv.add_external_clamped(delta, value);
+        if (new_value - value).abs() > f64::EPSILON {
+            self.apply(new_value);
+        }
+        e.prevent_default();
+    }
+
+    /// Double clicking resets the knob to its default value, or its minimum if it has none.
+    fn handle_double_click(&mut self) {
+        let knob = &self.props.knob;
+        let value = knob.scale.default_value.unwrap_or_else(|| knob.origin());
+        self.apply(value);
+    }
+
+    fn handle_down(&mut self, client_x: X, client_y: Y) {
+        self.dragging = true;
+        if self.props.knob.drag_mode == DragMode::Circular {
+            self.turn_to(client_x, client_y);
+        }
+    }
+
+    fn handle_up(&mut self) {
+        self.dragging = false;
+        self.last_touch = None;
+        if let Some(props) = self.ext_props.take() {
+            if props.size != self.props.size {
+                self.renderer = None;
+            }
+            self.props = props;
+            self.schedule_repaint();
+        }
+    }
+
+    fn handle_move(&mut self, client_x: X, client_y: Y, d_y: Y) {
+        match self.props.knob.drag_mode {
+            DragMode::Vertical => {
+                let knob = &self.props.knob;
+                let conv = (&PixelScale::inverted(0.0, VERTICAL_DRAG_RANGE), &knob.scale);
+                let value = knob.value;
+                let new_value = conv.add_external_clamped(d_y, value);
+                if (new_value - value).abs() > f64::EPSILON {
+                    self.apply(new_value);
+                }
+            }
+            DragMode::Circular => self.turn_to(client_x, client_y),
+        }
+    }
+
+    /// Turns the knob to point at a position in client coordinates.
+    fn turn_to(&mut self, client_x: X, client_y: Y) {
+        if let Some(canvas) = self.canvas.cast::<HtmlCanvasElement>() {
+            let rect = canvas.get_bounding_client_rect();
+            let x = client_x - rect.x() - rect.width() / 2.0;
+            let y = client_y - rect.y() - rect.height() / 2.0;
+            let value = self.props.knob.value;
+            let new_value = self.props.knob.value_at_angle(y.atan2(x));
+            if (new_value - value).abs() > f64::EPSILON {
+                self.apply(new_value);
+            }
+        }
+    }
+
+    fn apply(&mut self, value: KnobValue) {
+        self.props.knob.update(value);
+        self.schedule_repaint();
+        if let Some(Callback::Callback(fun)) = &self.props.on_input {
+            fun(value);
+        }
+    }
+}
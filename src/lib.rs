@@ -4,12 +4,21 @@ use scales::prelude::LinearScale;
 use scales::prelude::LogarithmicScale;
 
 pub mod compressor;
+pub mod deesser;
+pub mod dynamics;
 pub mod eq;
 pub mod expander;
 pub mod fader;
+pub mod goniometer;
+pub mod knob;
+pub mod limiter;
+pub mod loudness;
 pub mod meter;
+pub mod multiband;
+pub mod oscilloscope;
 pub mod scale;
 pub mod slider;
+pub mod spectrum;
 
 mod utils;
 
@@ -28,11 +37,17 @@ pub type X = f64;
 pub type Y = f64;
 pub type Radius = f64;
 pub type FaderValue = f64;
+pub type SliderValue = f64;
+pub type KnobValue = f64;
 pub type MeterValue = f64;
 pub type PeakValue = f64;
 pub type Update = (MeterValue, PeakValue);
 pub type ScaleValue = f64;
 pub type ShowUnit = bool;
+pub type Sample = f32;
+pub type Intensity = f64;
+pub type Seconds = f64;
+pub type Loudness = f64;
 pub type FreqScale = LogarithmicScale<Frequency>;
 pub type GainScale = LinearScale<Gain>;
 pub type QScale = LogarithmicScale<Q>;
@@ -70,6 +85,8 @@ pub enum LabelFormat {
     Gain(ShowUnit),
     GainShort(ShowUnit),
     Q,
+    /// Time in seconds, switching to minutes and hours where needed.
+    Time(ShowUnit),
 }
 
 impl LabelFormat {
@@ -80,6 +97,7 @@ impl LabelFormat {
             LabelFormat::Gain(unit) => utils::format_gain(value, *unit),
             LabelFormat::GainShort(unit) => utils::format_gain_short(value, *unit),
             LabelFormat::Q => utils::format_q(value),
+            LabelFormat::Time(unit) => utils::format_time(value, *unit),
         }
     }
 }
@@ -0,0 +1,205 @@
+use crate::dynamics::*;
+use crate::limiter::plotter;
+use crate::meter::{LinearMeterModel, MeterModel};
+use crate::*;
+use std::collections::VecDeque;
+
+pub const MAX_INPUT_GAIN: Gain = 24.0;
+pub const MAX_LOOKAHEAD: Seconds = 0.02;
+pub const MIN_RELEASE: Seconds = 0.001;
+pub const MAX_RELEASE: Seconds = 2.0;
+
+/// A brickwall limiter. Peaks of the input, raised by the input gain, are held at the threshold
+/// and the result is moved to the ceiling, so the output never exceeds the ceiling.
+#[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct LimiterModel {
+    pub input_gain: Gain,
+    pub threshold: Gain,
+    /// The highest output level.
+    pub ceiling: Gain,
+    /// How far ahead the limiter looks, which delays the output by the same time.
+    pub lookahead: Seconds,
+    pub release: Seconds,
+    /// Detects peaks between samples, so the output stays below the ceiling after conversion to
+    /// analog or sample rate conversion.
+    pub true_peak: bool,
+    /// The lowest level shown by the meters and the history.
+    pub min_level: Gain,
+    /// The strongest gain reduction shown by the meters and the history.
+    pub gain_reduction_range: Gain,
+}
+
+impl LimiterModel {
+    pub fn new(threshold: Gain, ceiling: Gain) -> LimiterModel {
+        LimiterModel {
+            input_gain: 0.0,
+            threshold,
+            ceiling,
+            lookahead: 0.005,
+            release: 0.1,
+            true_peak: false,
+            min_level: -48.0,
+            gain_reduction_range: 24.0,
+        }
+    }
+
+    pub fn update(&mut self, change: LimiterParameter) {
+        match change {
+            LimiterParameter::InputGain(gain) => {
+                self.input_gain = gain.clamp(-MAX_INPUT_GAIN, MAX_INPUT_GAIN)
+            }
+            LimiterParameter::Threshold(threshold) => {
+                self.threshold = threshold.max(self.min_level).min(0.0)
+            }
+            LimiterParameter::Ceiling(ceiling) => {
+                self.ceiling = ceiling.max(self.min_level).min(0.0)
+            }
+            LimiterParameter::Lookahead(lookahead) => {
+                self.lookahead = lookahead.clamp(0.0, MAX_LOOKAHEAD)
+            }
+            LimiterParameter::Release(release) => {
+                self.release = release.clamp(MIN_RELEASE, MAX_RELEASE)
+            }
+            LimiterParameter::TruePeak(true_peak) => self.true_peak = true_peak,
+        }
+    }
+
+    pub fn y_to_level_converter(&self, height: f64) -> (PixelScale, GainScale) {
+        let y_scale = PixelScale::inverted(0.0, height);
+        let level_scale = GainScale::new(self.min_level, 0.0);
+        (y_scale, level_scale)
+    }
+
+    pub fn y_to_gain_reduction_converter(&self, height: f64) -> (PixelScale, GainScale) {
+        let y_scale = PixelScale::inverted(0.0, height);
+        let gain_scale = GainScale::new(-self.gain_reduction_range, 0.0);
+        (y_scale, gain_scale)
+    }
+
+    /// Meters for input level, gain reduction and output level, in that order.
+    pub fn meters(&self) -> (LinearMeterModel, LinearMeterModel, LinearMeterModel) {
+        (
+            MeterModel::linear(self.min_level, 0.0),
+            MeterModel::gain_reduction(self.gain_reduction_range),
+            MeterModel::linear(self.min_level, 0.0),
+        )
+    }
+
+    pub fn plot(&self, history: &LimiterHistory, width: f64, height: f64) -> LimiterGraph {
+        plotter::plot_limiter(self, history, width, height)
+    }
+}
+
+impl GainComputer for LimiterModel {
+    /// Input gain plus the reduction needed to hold the raised input at the threshold.
+    fn gain(&self, input: Gain) -> Gain {
+        self.input_gain + (self.threshold - input - self.input_gain).min(0.0)
+    }
+
+    fn makeup(&self) -> Gain {
+        self.ceiling - self.threshold
+    }
+
+    fn threshold(&self) -> Option<Gain> {
+        Some(self.threshold - self.input_gain)
+    }
+}
+
+impl Default for LimiterModel {
+    fn default() -> Self {
+        LimiterModel::new(-6.0, -0.3)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum LimiterParameter {
+    InputGain(Gain),
+    Threshold(Gain),
+    Ceiling(Gain),
+    Lookahead(Seconds),
+    Release(Seconds),
+    TruePeak(bool),
+}
+
+/// Input and output peaks and the strongest gain reduction over one block, all in dB.
+#[derive(Debug, PartialEq, Clone)]
+pub struct LimiterReading {
+    pub input: PeakValue,
+    pub output: PeakValue,
+    pub gain_reduction: Gain,
+}
+
+/// The most recent limiter readings, the newest one at the back.
+#[derive(Debug, PartialEq, Clone)]
+pub struct LimiterHistory {
+    pub readings: VecDeque<LimiterReading>,
+    pub capacity: usize,
+}
+
+impl LimiterHistory {
+    pub fn new(capacity: usize) -> LimiterHistory {
+        LimiterHistory {
+            readings: VecDeque::with_capacity(capacity),
+            capacity,
+        }
+    }
+
+    pub fn push(&mut self, reading: LimiterReading) {
+        self.readings.push_back(reading);
+        while self.readings.len() > self.capacity {
+            self.readings.pop_front();
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.readings.clear();
+    }
+
+    pub fn last(&self) -> Option<&LimiterReading> {
+        self.readings.back()
+    }
+}
+
+/// The limiter history, one reading per pixel column with the newest at the right edge.
+pub struct LimiterGraph {
+    pub input: Vec<(X, Y)>,
+    pub output: Vec<(X, Y)>,
+    /// The gain reduction on its own scale, 0 dB at the top.
+    pub gain_reduction: Vec<(X, Y)>,
+    pub threshold: Line,
+    pub ceiling: Line,
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_gain_computer() {
+        let mut limiter = LimiterModel::new(-6.0, -1.0);
+
+        assert!((limiter.output(-20.0) - -15.0).abs() < 1e-9);
+        assert!((limiter.output(0.0) - -1.0).abs() < 1e-9);
+        assert!((limiter.gain(0.0) - -6.0).abs() < 1e-9);
+
+        limiter.update(LimiterParameter::InputGain(6.0));
+        assert!((limiter.output(-20.0) - -9.0).abs() < 1e-9);
+        assert!((limiter.output(-6.0) - -1.0).abs() < 1e-9);
+        assert_eq!(Some(-12.0), limiter.threshold());
+    }
+
+    #[test]
+    fn test_history_capacity() {
+        let mut history = LimiterHistory::new(2);
+        for i in 0..3 {
+            history.push(LimiterReading {
+                input: i as f64,
+                output: 0.0,
+                gain_reduction: 0.0,
+            });
+        }
+        assert_eq!(2, history.readings.len());
+        assert!((history.readings[0].input - 1.0).abs() < f64::EPSILON);
+    }
+}
@@ -0,0 +1,104 @@
+use crate::js_utils::*;
+use crate::limiter::common::*;
+use crate::*;
+use web_sys::CanvasRenderingContext2d;
+use web_sys::HtmlCanvasElement;
+
+/// Draws the limiter history: gain reduction hanging from the top, input and output peaks and
+/// lines for threshold and ceiling.
+pub struct CanvasLimiterRenderer {
+    pub context: CanvasRenderingContext2d,
+    pub style: Style,
+    pub bounds: Bounds,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Style {
+    input_stroke: Option<String>,
+    output_stroke: Option<String>,
+    gain_reduction_fill: Option<String>,
+    threshold_stroke: Option<String>,
+    ceiling_stroke: Option<String>,
+}
+
+impl CanvasLimiterRenderer {
+    pub fn new(canvas: HtmlCanvasElement) -> Option<CanvasLimiterRenderer> {
+        let context = get_context_2d(&canvas)?;
+        let style = get_styles(&canvas);
+
+        let bounds = canvas.get_bounding_client_rect().into();
+
+        let input_stroke = get_style("--input-stroke", &style, Some("#555"));
+        let output_stroke = get_style("--output-stroke", &style, Some("#88f"));
+        let gain_reduction_fill = get_style("--gain-reduction-fill", &style, Some("#ffa50088"));
+        let threshold_stroke = get_style("--threshold-stroke", &style, Some("#777"));
+        let ceiling_stroke = get_style("--ceiling-stroke", &style, Some("red"));
+
+        let style = Style {
+            input_stroke,
+            output_stroke,
+            gain_reduction_fill,
+            threshold_stroke,
+            ceiling_stroke,
+        };
+
+        Some(CanvasLimiterRenderer {
+            context,
+            style,
+            bounds,
+        })
+    }
+
+    pub fn render_to_canvas(&self, graph: &LimiterGraph) {
+        let width = self.bounds.width;
+        let height = self.bounds.height;
+
+        let context = &self.context;
+
+        context.clear_rect(0.0, 0.0, width, height);
+
+        if let (Some((first_x, _)), Some((last_x, _))) =
+            (graph.gain_reduction.first(), graph.gain_reduction.last())
+        {
+            context.begin_path();
+            set_fill(context, self.style.gain_reduction_fill.as_ref());
+            context.move_to(*first_x, 0.0);
+            for (x, y) in &graph.gain_reduction {
+                context.line_to(*x, *y);
+            }
+            context.line_to(*last_x, 0.0);
+            context.close_path();
+            context.fill();
+        }
+
+        self.stroke_trace(&graph.input, self.style.input_stroke.as_ref());
+        self.stroke_trace(&graph.output, self.style.output_stroke.as_ref());
+
+        for (line, stroke) in [
+            (&graph.threshold, &self.style.threshold_stroke),
+            (&graph.ceiling, &self.style.ceiling_stroke),
+        ]
+        .iter()
+        {
+            context.begin_path();
+            set_stroke(context, stroke.as_ref());
+            context.move_to(line.x_start, line.y_start + 0.5);
+            context.line_to(line.x_end, line.y_end + 0.5);
+            context.stroke();
+        }
+    }
+
+    fn stroke_trace(&self, trace: &[(X, Y)], stroke: Option<&String>) {
+        let context = &self.context;
+        let mut points = trace.iter();
+        if let Some((x, y)) = points.next() {
+            context.begin_path();
+            set_stroke(context, stroke);
+            context.move_to(*x, *y);
+            for (x, y) in points {
+                context.line_to(*x, *y);
+            }
+            context.stroke();
+        }
+    }
+}
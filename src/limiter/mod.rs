@@ -0,0 +1,17 @@
+mod common;
+mod plotter;
+mod processor;
+
+pub use crate::limiter::common::*;
+pub use crate::limiter::plotter::*;
+pub use crate::limiter::processor::*;
+
+#[cfg(feature = "js")]
+mod js;
+#[cfg(feature = "js")]
+pub use crate::limiter::js::*;
+
+#[cfg(feature = "yew-components")]
+mod yew_component;
+#[cfg(feature = "yew-components")]
+pub use crate::limiter::yew_component::*;
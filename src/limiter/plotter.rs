@@ -0,0 +1,79 @@
+use crate::limiter::common::*;
+use crate::*;
+use scales::prelude::*;
+
+pub fn plot_limiter(
+    limiter: &LimiterModel,
+    history: &LimiterHistory,
+    width: f64,
+    height: f64,
+) -> LimiterGraph {
+    let level_conv = limiter.y_to_level_converter(height);
+    let gain_conv = limiter.y_to_gain_reduction_converter(height);
+    let level = |value: Gain| level_conv.convert_back(value.max(limiter.min_level).min(0.0));
+
+    let columns = width as usize;
+    let skip = history.readings.len().saturating_sub(columns);
+    let offset = columns.saturating_sub(history.readings.len());
+
+    let mut input = Vec::new();
+    let mut output = Vec::new();
+    let mut gain_reduction = Vec::new();
+    for (i, reading) in history.readings.iter().skip(skip).enumerate() {
+        let x = (offset + i) as f64;
+        input.push((x, level(reading.input)));
+        output.push((x, level(reading.output)));
+        let gr = reading
+            .gain_reduction
+            .max(-limiter.gain_reduction_range)
+            .min(0.0);
+        gain_reduction.push((x, gain_conv.convert_back(gr)));
+    }
+
+    let line = |value: Gain| {
+        let y = level(value).floor();
+        Line {
+            x_start: 0.0,
+            y_start: y,
+            x_end: width,
+            y_end: y,
+            value,
+        }
+    };
+
+    LimiterGraph {
+        input,
+        output,
+        gain_reduction,
+        threshold: line(limiter.threshold - limiter.input_gain),
+        ceiling: line(limiter.ceiling),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_plot_limiter() {
+        let limiter = LimiterModel::new(-12.0, -6.0);
+        let mut history = LimiterHistory::new(100);
+        for i in 0..60 {
+            history.push(LimiterReading {
+                input: -(i as f64),
+                output: -6.0,
+                gain_reduction: -12.0,
+            });
+        }
+
+        let graph = limiter.plot(&history, 50.0, 48.0);
+
+        assert_eq!(50, graph.input.len());
+        // the oldest readings are cut off, the newest is at the right edge
+        assert!((graph.input[0].1 - 10.0).abs() < 1e-9);
+        assert!((graph.input[49].0 - 49.0).abs() < f64::EPSILON);
+        assert!((graph.output[0].1 - 6.0).abs() < 1e-9);
+        assert!((graph.gain_reduction[0].1 - 24.0).abs() < 1e-9);
+        assert!((graph.threshold.y_start - 12.0).abs() < f64::EPSILON);
+    }
+}
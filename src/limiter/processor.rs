@@ -0,0 +1,218 @@
+use crate::dynamics::*;
+use crate::limiter::common::*;
+use crate::*;
+use std::collections::VecDeque;
+
+/// Points interpolated between two samples when detecting true peaks.
+const OVERSAMPLING: usize = 4;
+
+/// Computes limiter readings from blocks of samples, so hosts without their own metering can
+/// feed the limiter widget. All channels are limited together.
+#[derive(Debug, PartialEq, Clone)]
+pub struct LimiterProcessor {
+    pub sample_rate: f64,
+    limiter: LimiterModel,
+    follower: EnvelopeFollower,
+    /// The last samples of each channel, oldest first, for true peak interpolation.
+    channel_history: Vec<[f64; 3]>,
+    /// Detected levels not yet released to the output, oldest first.
+    delay: VecDeque<Gain>,
+    /// Candidates for the lowest required gain within the lookahead window as (sample index,
+    /// gain), increasing in both.
+    window: VecDeque<(usize, Gain)>,
+    position: usize,
+}
+
+impl LimiterProcessor {
+    pub fn new(limiter: &LimiterModel, channels: usize, sample_rate: f64) -> LimiterProcessor {
+        let follower = EnvelopeFollower::new(sample_rate, Detector::Peak, time_constants(limiter));
+        LimiterProcessor {
+            sample_rate,
+            limiter: limiter.clone(),
+            follower,
+            channel_history: vec![[0.0; 3]; channels],
+            delay: VecDeque::new(),
+            window: VecDeque::new(),
+            position: 0,
+        }
+    }
+
+    /// Applies changed parameters. Changing the lookahead resets the processor.
+    pub fn set_limiter(&mut self, limiter: &LimiterModel) {
+        let lookahead_changed = (limiter.lookahead - self.limiter.lookahead).abs() > f64::EPSILON;
+        self.limiter = limiter.clone();
+        self.follower.time_constants = time_constants(limiter);
+        if lookahead_changed {
+            self.reset();
+        }
+    }
+
+    /// Processes one block of non-interleaved channels of equal length. Channels beyond the
+    /// ones the processor was created for are added.
+    pub fn process_block(&mut self, block: &[&[Sample]]) -> LimiterReading {
+        if block.len() > self.channel_history.len() {
+            self.channel_history.resize(block.len(), [0.0; 3]);
+        }
+        let frames = block.iter().map(|c| c.len()).min().unwrap_or(0);
+        let mut reading = LimiterReading {
+            input: f64::NEG_INFINITY,
+            output: f64::NEG_INFINITY,
+            gain_reduction: 0.0,
+        };
+
+        for frame in 0..frames {
+            let mut peak: f64 = 0.0;
+            for (channel, samples) in block.iter().enumerate() {
+                let sample = samples[frame] as f64;
+                peak = peak.max(self.detect(channel, sample));
+            }
+            let (output, gain_reduction) = self.process(to_db(peak));
+            reading.input = reading.input.max(to_db(peak));
+            reading.output = reading.output.max(output);
+            reading.gain_reduction = reading.gain_reduction.min(gain_reduction);
+        }
+
+        reading
+    }
+
+    pub fn reset(&mut self) {
+        for history in &mut self.channel_history {
+            *history = [0.0; 3];
+        }
+        self.follower.reset();
+        self.delay.clear();
+        self.window.clear();
+        self.position = 0;
+    }
+
+    fn lookahead_samples(&self) -> usize {
+        (self.limiter.lookahead * self.sample_rate).round() as usize
+    }
+
+    /// The absolute peak of a channel's sample, interpolating between the previous samples in
+    /// true peak mode. Interpolation delays true peaks by one sample.
+    fn detect(&mut self, channel: usize, sample: f64) -> f64 {
+        let history = &mut self.channel_history[channel];
+        let [p0, p1, p2] = *history;
+        *history = [p1, p2, sample];
+        if !self.limiter.true_peak {
+            return sample.abs();
+        }
+        (0..OVERSAMPLING)
+            .map(|i| catmull_rom(p0, p1, p2, sample, i as f64 / OVERSAMPLING as f64).abs())
+            .fold(0.0, f64::max)
+    }
+
+    /// Processes one detected level in dBFS, returning the output level and the applied gain
+    /// reduction.
+    fn process(&mut self, level: Gain) -> (Gain, Gain) {
+        let limiter = &self.limiter;
+        let required = (limiter.threshold - level - limiter.input_gain).min(0.0);
+
+        while self.window.back().is_some_and(|(_, g)| *g >= required) {
+            self.window.pop_back();
+        }
+        self.window.push_back((self.position, required));
+        let lookahead = self.lookahead_samples();
+        while self
+            .window
+            .front()
+            .is_some_and(|(i, _)| i + lookahead < self.position)
+        {
+            self.window.pop_front();
+        }
+        let target = self.window.front().map_or(0.0, |(_, g)| *g);
+        self.position += 1;
+
+        self.delay.push_back(level);
+        let delayed = if self.delay.len() > lookahead {
+            self.delay.pop_front().unwrap_or(f64::NEG_INFINITY)
+        } else {
+            f64::NEG_INFINITY
+        };
+
+        // the smoothed gain may lag behind, but never lets the delayed level exceed the threshold
+        let delayed_required = (limiter.threshold - delayed - limiter.input_gain).min(0.0);
+        let gain_reduction = self.follower.smooth(target).min(delayed_required);
+        let output = delayed + limiter.input_gain + gain_reduction + limiter.makeup();
+        (output, gain_reduction)
+    }
+}
+
+/// Attack over the lookahead time, so the gain has mostly settled when a peak reaches the
+/// output.
+fn time_constants(limiter: &LimiterModel) -> TimeConstants {
+    TimeConstants {
+        attack: limiter.lookahead / 4.0,
+        hold: 0.0,
+        release: limiter.release,
+        program_dependent_release: false,
+    }
+}
+
+/// Interpolates between `p1` and `p2` at `t` in 0..1.
+fn catmull_rom(p0: f64, p1: f64, p2: f64, p3: f64, t: f64) -> f64 {
+    let a = -0.5 * p0 + 1.5 * p1 - 1.5 * p2 + 0.5 * p3;
+    let b = p0 - 2.5 * p1 + 2.0 * p2 - 0.5 * p3;
+    let c = -0.5 * p0 + 0.5 * p2;
+    ((a * t + b) * t + c) * t + p1
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn sine(amplitude: f64, period: f64, phase: f64, length: usize) -> Vec<Sample> {
+        (0..length)
+            .map(|i| {
+                let angle = 2.0 * std::f64::consts::PI * i as f64 / period + phase;
+                (amplitude * angle.sin()) as Sample
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_output_stays_below_ceiling() {
+        let limiter = LimiterModel::new(-6.0, -1.0);
+        let mut processor = LimiterProcessor::new(&limiter, 1, 48_000.0);
+
+        let quiet = sine(0.05, 48.0, 0.0, 4_800);
+        let loud = sine(1.0, 48.0, 0.0, 4_800);
+        let reading = processor.process_block(&[&quiet]);
+        assert!(reading.gain_reduction.abs() < f64::EPSILON);
+        assert!((reading.output - (to_db(0.05) + 5.0)).abs() < 0.01);
+
+        let reading = processor.process_block(&[&loud]);
+        assert!(reading.input.abs() < 0.01);
+        assert!(reading.output <= -1.0 + 1e-6);
+        assert!((reading.gain_reduction - -6.0).abs() < 0.01);
+    }
+
+    #[test]
+    fn test_true_peak_detection() {
+        // sampled 22.5 degrees off the peaks, the samples only reach -0.69 dB
+        let samples = sine(1.0, 8.0, std::f64::consts::PI / 8.0, 800);
+        let mut limiter = LimiterModel::new(-6.0, -6.0);
+        let mut processor = LimiterProcessor::new(&limiter, 1, 48_000.0);
+        let reading = processor.process_block(&[&samples]);
+        assert!((reading.input - -0.69).abs() < 0.01);
+
+        limiter.update(LimiterParameter::TruePeak(true));
+        processor.set_limiter(&limiter);
+        let reading = processor.process_block(&[&samples]);
+        assert!(reading.input > -0.1);
+        assert!(reading.output <= -6.0 + 1e-6);
+    }
+
+    #[test]
+    fn test_extra_channels() {
+        let limiter = LimiterModel::new(-6.0, -1.0);
+        let mut processor = LimiterProcessor::new(&limiter, 1, 48_000.0);
+
+        let quiet = sine(0.05, 48.0, 0.0, 4_800);
+        let loud = sine(1.0, 48.0, 0.0, 4_800);
+        let reading = processor.process_block(&[&quiet, &loud]);
+        assert!(reading.input.abs() < 0.01);
+        assert!(reading.output <= -1.0 + 1e-6);
+    }
+}
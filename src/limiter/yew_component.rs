@@ -0,0 +1,407 @@
+use crate::js_utils::*;
+use crate::limiter::*;
+use crate::meter::{
+    default_zones, gain_reduction_zones, LinearMeterModel, Meter, PeakStyle, RenderMode,
+};
+use crate::scale::{Layout, VerticalPosition};
+use crate::*;
+use derivative::*;
+use scales::prelude::*;
+use wasm_bindgen::prelude::*;
+use web_sys::HtmlCanvasElement;
+use yew::prelude::*;
+
+/// Lines further away from the pointer than this are not grabbed.
+const GRAB_DISTANCE: f64 = 8.0;
+const METER_WIDTH: f64 = 36.0;
+const METER_BAR_WIDTH: f64 = 8.0;
+
+pub struct Limiter {
+    props: Props,
+    ext_props: Option<Props>,
+    link: ComponentLink<Self>,
+    canvas: NodeRef,
+    active_handle: Option<Handle>,
+    last_touch: Option<Y>,
+    renderer: Option<CanvasLimiterRenderer>,
+    render_callback: Closure<dyn FnMut()>,
+    needs_repaint: bool,
+    /// Input, gain reduction and output meters, kept across renders so clips latch.
+    meters: Meters,
+}
+
+type Meters = (LinearMeterModel, LinearMeterModel, LinearMeterModel);
+
+#[derive(Derivative, Properties)]
+#[derivative(Debug, Clone, PartialEq)]
+pub struct Props {
+    pub id: String,
+    pub limiter: LimiterModel,
+    /// Recent readings, the last one is shown by the meters.
+    pub history: LimiterHistory,
+    /// The size of the history graph, the meters are placed next to it.
+    pub width: f64,
+    pub height: f64,
+    #[derivative(PartialEq = "ignore")]
+    pub on_input: Option<Callback<LimiterParameter>>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Handle {
+    Threshold,
+    Ceiling,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Msg {
+    MouseDown(MouseEvent),
+    MouseUp(MouseEvent),
+    MouseMove(MouseEvent),
+    TouchStart(TouchEvent),
+    TouchEnd(TouchEvent),
+    TouchMove(TouchEvent),
+    Wheel(WheelEvent),
+    ResetMeters,
+    Render,
+}
+
+impl Component for Limiter {
+    type Message = Msg;
+
+    type Properties = Props;
+
+    fn create(props: Self::Properties, link: ComponentLink<Self>) -> Self {
+        let cb_link = link.clone();
+        let render_callback =
+            Closure::wrap(Box::new(move || cb_link.send_message(Msg::Render)) as Box<dyn FnMut()>);
+
+        let cb_link = link.clone();
+        let mouse_moved =
+            Closure::wrap(Box::new(move |e| cb_link.send_message(Msg::MouseMove(e)))
+                as Box<dyn Fn(MouseEvent)>);
+
+        let cb_link = link.clone();
+        let mouse_up = Closure::wrap(
+            Box::new(move |e| cb_link.send_message(Msg::MouseUp(e))) as Box<dyn Fn(MouseEvent)>
+        );
+
+        register_global_listener("mousemove", &mouse_moved);
+        register_global_listener("mouseup", &mouse_up);
+
+        mouse_moved.forget();
+        mouse_up.forget();
+
+        let meters = next_meters(&props.limiter.meters(), &props, true);
+
+        Limiter {
+            props,
+            ext_props: None,
+            link,
+            canvas: NodeRef::default(),
+            active_handle: None,
+            last_touch: None,
+            renderer: None,
+            render_callback,
+            needs_repaint: false,
+            meters,
+        }
+    }
+
+    fn update(&mut self, msg: Self::Message) -> ShouldRender {
+        match msg {
+            Msg::MouseDown(e) => self.handle_mouse_down(e),
+            Msg::MouseUp(_) => self.handle_up(),
+            Msg::MouseMove(e) => self.handle_mouse_move(e),
+            Msg::TouchStart(e) => self.handle_touch_start(e),
+            Msg::TouchEnd(_) => self.handle_up(),
+            Msg::TouchMove(e) => self.handle_touch_move(e),
+            Msg::Wheel(e) => self.handle_wheel(e),
+            Msg::ResetMeters => {
+                for meter in &mut [&mut self.meters.0, &mut self.meters.2] {
+                    meter.reset_clip();
+                    meter.reset_max_hold();
+                }
+                return true;
+            }
+            Msg::Render => self.render(),
+        }
+        false
+    }
+
+    fn change(&mut self, props: Self::Properties) -> ShouldRender {
+        let resized = props.width != self.props.width || props.height != self.props.height;
+        let history_changed = props.history != self.props.history;
+        self.meters = next_meters(&self.meters, &props, history_changed);
+        // don't accept external changes while a line is being dragged
+        if self.active_handle.is_some() {
+            self.props.history = props.history.clone();
+            self.ext_props = Some(props);
+            self.schedule_repaint();
+        } else if props != self.props {
+            self.props = props;
+            self.schedule_repaint();
+        }
+        if resized {
+            self.renderer = None;
+        }
+        // the meters are child components and only update when the view is rendered
+        resized || history_changed
+    }
+
+    fn view(&self) -> Html {
+        let mouse_down_callback = self.link.callback(Msg::MouseDown);
+        let touch_start_callback = self.link.callback(Msg::TouchStart);
+        let touch_end_callback = self.link.callback(Msg::TouchEnd);
+        let touch_move_callback = self.link.callback(Msg::TouchMove);
+        let wheel_callback = self.link.callback(Msg::Wheel);
+
+        let (input, gain_reduction, output) = self.meters.clone();
+        let id = &self.props.id;
+
+        html! {
+            <div class="limiter">
+                { self.meter(format!("{}-input", id), input, false) }
+                <canvas
+                    id={id.clone()}
+                    ref=self.canvas.clone()
+                    width={self.props.width}
+                    height={self.props.height}
+                    onmousedown={mouse_down_callback}
+                    ontouchstart={touch_start_callback}
+                    ontouchend={touch_end_callback}
+                    ontouchmove={touch_move_callback}
+                    onwheel={wheel_callback}
+                />
+                { self.meter(format!("{}-gain-reduction", id), gain_reduction, true) }
+                { self.meter(format!("{}-output", id), output, false) }
+            </div>
+        }
+    }
+
+    fn rendered(&mut self, _first_render: bool) {
+        if self.renderer.is_none() {
+            self.renderer = self
+                .canvas
+                .cast::<HtmlCanvasElement>()
+                .and_then(CanvasLimiterRenderer::new);
+        }
+
+        self.schedule_repaint();
+    }
+}
+
+impl Limiter {
+    fn render(&mut self) {
+        self.needs_repaint = false;
+        if let Some(renderer) = self.renderer.as_ref() {
+            let props = &self.props;
+            let graph = props
+                .limiter
+                .plot(&props.history, props.width, props.height);
+            renderer.render_to_canvas(&graph);
+        }
+    }
+
+    fn schedule_repaint(&mut self) {
+        if !self.needs_repaint {
+            self.needs_repaint = true;
+            request_animation_frame(&self.render_callback);
+        }
+    }
+
+    fn meter(&self, id: String, meter: LinearMeterModel, gain_reduction: bool) -> Html {
+        let zones = if gain_reduction {
+            gain_reduction_zones()
+        } else {
+            default_zones()
+        };
+        html! {
+            <Meter<LinearScale<f64>>
+                id={id}
+                meter={meter}
+                width={METER_WIDTH}
+                height={self.props.height}
+                bar_width={METER_BAR_WIDTH}
+                layout={Layout::Vertical(VerticalPosition::Right)}
+                inverted={false}
+                zones={zones}
+                peak_style={PeakStyle::Off}
+                render_mode={RenderMode::Continuous}
+                show_clip_indicator={!gain_reduction}
+                show_scale={true}
+                show_readout={false}
+                bus={None}
+                on_reset={Some(self.link.callback(|_| Msg::ResetMeters))}
+            />
+        }
+    }
+
+    fn handle_mouse_down(&mut self, e: MouseEvent) {
+        if e.button() != 0 {
+            return;
+        }
+        self.handle_down(e.offset_y() as f64);
+    }
+
+    fn handle_mouse_move(&mut self, e: MouseEvent) {
+        if let Some(handle) = self.active_handle {
+            self.handle_move(e.movement_y() as f64, handle);
+        }
+    }
+
+    fn handle_touch_start(&mut self, e: TouchEvent) {
+        if e.target_touches().length() != 1 {
+            return;
+        }
+        if let (Some(touch), Some(canvas)) = (
+            e.changed_touches().get(0),
+            self.canvas.cast::<HtmlCanvasElement>(),
+        ) {
+            let rect = canvas.get_bounding_client_rect();
+            let y = touch.client_y() as f64;
+            self.last_touch = Some(y);
+            self.handle_down(y - rect.y());
+        }
+    }
+
+    fn handle_touch_move(&mut self, e: TouchEvent) {
+        if e.target_touches().length() != 1 {
+            return;
+        }
+        if let (Some(handle), Some(touch)) = (self.active_handle, e.changed_touches().get(0)) {
+            let y = touch.client_y() as f64;
+            if let Some(last_y) = self.last_touch {
+                self.handle_move(y - last_y, handle);
+            }
+            self.last_touch = Some(y);
+        }
+        e.prevent_default();
+    }
+
+    /// Scrolling adjusts the release time, with shift held the input gain.
+    fn handle_wheel(&mut self, e: WheelEvent) {
+        let limiter = &self.props.limiter;
+        let step = -e.delta_y().signum();
+        let change = if e.shift_key() {
+            LimiterParameter::InputGain(limiter.input_gain + step)
+        } else {
+            LimiterParameter::Release(limiter.release * 1.1f64.powf(step))
+        };
+        self.apply(change);
+        e.prevent_default();
+    }
+
+    fn handle_down(&mut self, y: Y) {
+        self.active_handle = [Handle::Threshold, Handle::Ceiling]
+            .iter()
+            .map(|handle| (*handle, (y - self.position(*handle)).abs()))
+            .filter(|(_, distance)| *distance <= GRAB_DISTANCE)
+            .min_by(|a, b| a.1.partial_cmp(&b.1).unwrap_or(std::cmp::Ordering::Equal))
+            .map(|(handle, _)| handle);
+    }
+
+    fn handle_up(&mut self) {
+        self.active_handle = None;
+        self.last_touch = None;
+        if let Some(props) = self.ext_props.take() {
+            self.props = props;
+            self.schedule_repaint();
+        }
+    }
+
+    fn handle_move(&mut self, d_y: Y, handle: Handle) {
+        let limiter = &self.props.limiter;
+        let y_conv = limiter.y_to_level_converter(self.props.height);
+        let change = match handle {
+            Handle::Threshold => {
+                let threshold = limiter.threshold - limiter.input_gain;
+                let new_threshold = y_conv.add_external_clamped(d_y, threshold);
+                LimiterParameter::Threshold(new_threshold + limiter.input_gain)
+            }
+            Handle::Ceiling => {
+                LimiterParameter::Ceiling(y_conv.add_external_clamped(d_y, limiter.ceiling))
+            }
+        };
+        self.apply(change);
+    }
+
+    fn apply(&mut self, change: LimiterParameter) {
+        let before = self.props.limiter.clone();
+        self.props.limiter.update(change.clone());
+        if self.props.limiter == before {
+            return;
+        }
+        self.schedule_repaint();
+        if let Some(Callback::Callback(fun)) = &self.props.on_input {
+            fun(change);
+        }
+    }
+
+    /// The vertical position of the line drawn for a handle.
+    fn position(&self, handle: Handle) -> Y {
+        let limiter = &self.props.limiter;
+        let level = match handle {
+            Handle::Threshold => limiter.threshold - limiter.input_gain,
+            Handle::Ceiling => limiter.ceiling,
+        };
+        let y_conv = limiter.y_to_level_converter(self.props.height);
+        y_conv.convert_back(level.max(limiter.min_level).min(0.0))
+    }
+}
+
+/// The meters for new props, keeping latched clips and max hold values. The newest reading is
+/// only applied if the history has changed.
+fn next_meters(meters: &Meters, props: &Props, history_changed: bool) -> Meters {
+    let (input, gain_reduction, output) = props.limiter.meters();
+    let meters = (
+        meters.0.reconfigured(&input),
+        meters.1.reconfigured(&gain_reduction),
+        meters.2.reconfigured(&output),
+    );
+    match props.history.last().filter(|_| history_changed) {
+        Some(reading) => (
+            meters.0.update(reading.input, reading.input),
+            meters
+                .1
+                .update(reading.gain_reduction, reading.gain_reduction),
+            meters.2.update(reading.output, reading.output),
+        ),
+        None => meters,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_clip_latches_across_readings() {
+        let mut props = Props {
+            id: "limiter".to_owned(),
+            limiter: LimiterModel::default(),
+            history: LimiterHistory::new(4),
+            width: 100.0,
+            height: 100.0,
+            on_input: None,
+        };
+        let meters = next_meters(&props.limiter.meters(), &props, true);
+
+        props.history.push(LimiterReading {
+            input: 0.0,
+            output: -0.3,
+            gain_reduction: -6.0,
+        });
+        let meters = next_meters(&meters, &props, true);
+        assert!(meters.0.clip.is_clipped());
+
+        props.history.push(LimiterReading {
+            input: -20.0,
+            output: -14.3,
+            gain_reduction: 0.0,
+        });
+        let meters = next_meters(&meters, &props, true);
+        assert!(meters.0.clip.is_clipped());
+        assert!((meters.0.value - -20.0).abs() < f64::EPSILON);
+        assert!(!meters.2.clip.is_clipped());
+    }
+}
@@ -0,0 +1,202 @@
+use crate::loudness::plotter;
+use crate::scale::{HorizontalPosition, Layout, ScaleGraph, ScaleModel, VerticalPosition};
+use crate::*;
+use scales::prelude::*;
+use std::collections::VecDeque;
+
+pub const MIN_VISIBLE_DURATION: Seconds = 10.0;
+pub const MAX_VISIBLE_DURATION: Seconds = 3.0 * 3600.0;
+
+/// Major and minor marker spacing of the time axis, picked so that no more than
+/// `MAX_TIME_MARKERS` major markers are visible.
+const TIME_STEPS: [(Seconds, Seconds); 13] = [
+    (1.0, 0.5),
+    (2.0, 1.0),
+    (5.0, 1.0),
+    (10.0, 5.0),
+    (15.0, 5.0),
+    (30.0, 10.0),
+    (60.0, 15.0),
+    (120.0, 30.0),
+    (300.0, 60.0),
+    (600.0, 120.0),
+    (900.0, 300.0),
+    (1800.0, 600.0),
+    (3600.0, 900.0),
+];
+const MAX_TIME_MARKERS: f64 = 6.0;
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct LoudnessPoint {
+    pub time: Seconds,
+    /// Short-term loudness in LUFS.
+    pub short_term: Loudness,
+    /// True peak in dBTP.
+    pub true_peak: PeakValue,
+}
+
+/// A bounded history of short-term loudness and true-peak readings. The most recent reading is
+/// drawn at the right edge, `visible_duration` determines how far the graph reaches back.
+#[derive(Debug, PartialEq, Clone)]
+pub struct LoudnessHistoryModel {
+    pub points: VecDeque<LoudnessPoint>,
+    pub capacity: usize,
+    pub visible_duration: Seconds,
+    /// The loudness target in LUFS, e.g. -23 for EBU R128.
+    pub target: Loudness,
+    /// Allowed deviation from the target, shaded in the graph.
+    pub tolerance: Loudness,
+    pub loudness_scale: LinearScale<f64>,
+}
+
+impl LoudnessHistoryModel {
+    pub fn new(capacity: usize, target: Loudness, tolerance: Loudness) -> LoudnessHistoryModel {
+        LoudnessHistoryModel {
+            points: VecDeque::with_capacity(capacity),
+            capacity,
+            visible_duration: 60.0,
+            target,
+            tolerance,
+            loudness_scale: LinearScale::new(-50.0, 0.0),
+        }
+    }
+
+    /// Appends a reading. Readings older than the last one restart the history, e.g. after the
+    /// host has reset its time base.
+    pub fn push(&mut self, time: Seconds, short_term: Loudness, true_peak: PeakValue) {
+        if self.points.back().map(|p| time < p.time).unwrap_or(false) {
+            self.points.clear();
+        }
+        self.points.push_back(LoudnessPoint {
+            time,
+            short_term,
+            true_peak,
+        });
+        while self.points.len() > self.capacity {
+            self.points.pop_front();
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.points.clear();
+    }
+
+    /// The time of the most recent reading.
+    pub fn now(&self) -> Seconds {
+        self.points.back().map(|p| p.time).unwrap_or(0.0)
+    }
+
+    /// Multiplies the visible duration by `factor`, so factors below 1 zoom in.
+    pub fn zoom(&mut self, factor: f64) {
+        let duration = self.visible_duration * factor;
+        self.visible_duration = duration.clamp(MIN_VISIBLE_DURATION, MAX_VISIBLE_DURATION);
+    }
+
+    /// The horizontal time axis, in seconds relative to the most recent reading.
+    pub fn time_scale(&self) -> ScaleModel<LinearScale<f64>> {
+        let duration = self.visible_duration;
+        let (major, minor) = TIME_STEPS
+            .iter()
+            .find(|(major, _)| duration / major <= MAX_TIME_MARKERS)
+            .unwrap_or(&TIME_STEPS[TIME_STEPS.len() - 1]);
+        let major_scale_markers = markers(duration, *major, None);
+        let minor_scale_markers = markers(duration, *minor, Some(*major));
+        ScaleModel::new(
+            LinearScale::new(-duration, 0.0),
+            Layout::Horizontal(HorizontalPosition::Bottom),
+            None,
+            major_scale_markers,
+            minor_scale_markers,
+        )
+    }
+
+    /// The vertical loudness axis, with the target as default value.
+    pub fn loudness_scale(&self) -> ScaleModel<LinearScale<f64>> {
+        let scale = self.loudness_scale.clone();
+        let range = scale.max() - scale.min();
+        let major_scale_markers = (0..=(range / 10.0) as usize)
+            .map(|i| scale.max() - i as f64 * 10.0)
+            .rev()
+            .collect();
+        let minor_scale_markers = (0..(range / 10.0) as usize)
+            .map(|i| scale.max() - 5.0 - i as f64 * 10.0)
+            .rev()
+            .collect();
+        ScaleModel::new(
+            scale,
+            Layout::Vertical(VerticalPosition::Left),
+            Some(self.target),
+            major_scale_markers,
+            minor_scale_markers,
+        )
+    }
+
+    pub fn plot(&self, width: f64, height: f64) -> LoudnessHistoryGraph {
+        plotter::plot_loudness_history(self, width, height)
+    }
+}
+
+impl Default for LoudnessHistoryModel {
+    fn default() -> Self {
+        // EBU R128 with 3 hours of readings at 10 Hz
+        LoudnessHistoryModel::new(108_000, -23.0, 1.0)
+    }
+}
+
+pub struct LoudnessHistoryGraph {
+    pub short_term: Vec<(X, Y)>,
+    pub true_peak: Vec<(X, Y)>,
+    pub target_range: Bounds,
+    pub time_scale: ScaleGraph,
+    pub loudness_scale: ScaleGraph,
+}
+
+/// Markers at multiples of `step` within the last `duration` seconds, leaving out those that
+/// fall on multiples of `skip`.
+fn markers(duration: Seconds, step: Seconds, skip: Option<Seconds>) -> Vec<ScaleValue> {
+    let count = (duration / step).floor() as usize;
+    (0..=count)
+        .map(|i| -(i as f64) * step)
+        .filter(|m| skip.map(|s| (m / s).fract().abs() > 1e-9).unwrap_or(true))
+        .rev()
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_history_is_bounded() {
+        let mut history = LoudnessHistoryModel::new(3, -23.0, 1.0);
+        for i in 0..5 {
+            history.push(i as f64, -23.0, -3.0);
+        }
+
+        assert_eq!(3, history.points.len());
+        assert!((history.now() - 4.0).abs() < f64::EPSILON);
+
+        history.push(1.0, -23.0, -3.0);
+        assert_eq!(1, history.points.len());
+    }
+
+    #[test]
+    fn test_time_scale_follows_zoom() {
+        let mut history = LoudnessHistoryModel::default();
+
+        let scale = history.time_scale();
+        assert_eq!(7, scale.major_scale_markers.len());
+        assert_eq!(
+            vec![-55.0, -45.0, -35.0, -25.0, -15.0, -5.0],
+            scale.minor_scale_markers
+        );
+
+        history.zoom(0.01);
+        assert!((history.visible_duration - MIN_VISIBLE_DURATION).abs() < f64::EPSILON);
+        let scale = history.time_scale();
+        assert_eq!(
+            vec![-10.0, -8.0, -6.0, -4.0, -2.0, 0.0],
+            scale.major_scale_markers
+        );
+    }
+}
@@ -0,0 +1,140 @@
+use crate::js_utils::*;
+use crate::loudness::common::*;
+use crate::utils::*;
+use crate::*;
+use web_sys::CanvasRenderingContext2d;
+use web_sys::HtmlCanvasElement;
+
+const LABEL_PADDING: f64 = 3.0;
+
+pub struct CanvasLoudnessHistoryRenderer {
+    pub context: CanvasRenderingContext2d,
+    pub style: Style,
+    pub bounds: Bounds,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Style {
+    background_fill: Option<String>,
+    grid_stroke: Option<String>,
+    target_stroke: Option<String>,
+    target_range_fill: Option<String>,
+    scale_label_fill: Option<String>,
+    scale_font: Option<String>,
+    loudness_stroke: Option<String>,
+    true_peak_stroke: Option<String>,
+}
+
+impl CanvasLoudnessHistoryRenderer {
+    pub fn new(canvas: HtmlCanvasElement) -> Option<CanvasLoudnessHistoryRenderer> {
+        let context = get_context_2d(&canvas)?;
+        let style = get_styles(&canvas);
+
+        let bounds = canvas.get_bounding_client_rect().into();
+
+        let background_fill = get_style("--background-fill", &style, Some("black"));
+        let grid_stroke = get_style("--grid-stroke", &style, Some("#333"));
+        let target_stroke = get_style("--target-stroke", &style, Some("#4a4"));
+        let target_range_fill = get_style("--target-range-fill", &style, Some("#132"));
+        let scale_label_fill = get_style("--scale-label-fill", &style, Some("#777"));
+        let scale_font = get_style("--scale-font", &style, Some("9px sans-serif"));
+        let loudness_stroke = get_style("--loudness-stroke", &style, Some("#8fd7e0"));
+        let true_peak_stroke = get_style("--true-peak-stroke", &style, Some("orange"));
+
+        let style = Style {
+            background_fill,
+            grid_stroke,
+            target_stroke,
+            target_range_fill,
+            scale_label_fill,
+            scale_font,
+            loudness_stroke,
+            true_peak_stroke,
+        };
+
+        Some(CanvasLoudnessHistoryRenderer {
+            context,
+            style,
+            bounds,
+        })
+    }
+
+    pub fn render_to_canvas(&self, history: &LoudnessHistoryModel) {
+        let width = self.bounds.width;
+        let height = self.bounds.height;
+
+        let context = &self.context;
+
+        let graph = history.plot(width, height);
+
+        context.clear_rect(0.0, 0.0, width, height);
+
+        set_fill(context, self.style.background_fill.as_ref());
+        context.fill_rect(0.0, 0.0, width, height);
+
+        let range = &graph.target_range;
+        set_fill(context, self.style.target_range_fill.as_ref());
+        context.fill_rect(range.x, range.y, range.width, range.height);
+
+        context.begin_path();
+        set_stroke(context, self.style.grid_stroke.as_ref());
+        let time_lines = graph.time_scale.major_lines.iter();
+        let loudness_lines = graph.loudness_scale.major_lines.iter();
+        for line in time_lines.chain(loudness_lines) {
+            context.move_to(line.x_start.floor() + 0.5, line.y_start.floor() + 0.5);
+            context.line_to(line.x_end.floor() + 0.5, line.y_end.floor() + 0.5);
+        }
+        context.stroke();
+
+        if let Some(target) = &graph.loudness_scale.default_value {
+            context.begin_path();
+            set_stroke(context, self.style.target_stroke.as_ref());
+            context.move_to(target.x_start, target.y_start.floor() + 0.5);
+            context.line_to(target.x_end, target.y_end.floor() + 0.5);
+            context.stroke();
+        }
+
+        self.render_trace(&graph.true_peak, self.style.true_peak_stroke.as_ref());
+        self.render_trace(&graph.short_term, self.style.loudness_stroke.as_ref());
+
+        if let Some(font) = &self.style.scale_font {
+            context.set_font(font);
+        }
+        set_fill(context, self.style.scale_label_fill.as_ref());
+
+        context.set_text_align("left");
+        context.set_text_baseline("bottom");
+        for label in &graph.loudness_scale.labels {
+            let y = label.y.max(LABEL_PADDING * 4.0);
+            context
+                .fill_text(&label.text, label.x + LABEL_PADDING, y - LABEL_PADDING)
+                .ignore();
+        }
+
+        context.set_text_align("center");
+        for label in &graph.time_scale.labels {
+            // the labels at the edges would be cut off otherwise
+            let x = label
+                .x
+                .max(LABEL_PADDING * 4.0)
+                .min(width - LABEL_PADDING * 4.0);
+            context
+                .fill_text(&label.text, x, label.y - LABEL_PADDING)
+                .ignore();
+        }
+    }
+
+    fn render_trace(&self, points: &[(X, Y)], stroke: Option<&String>) {
+        let context = &self.context;
+        let mut points = points.iter();
+        if let Some((x, y)) = points.next() {
+            context.begin_path();
+            set_stroke(context, stroke);
+            context.move_to(*x, *y);
+            for (x, y) in points {
+                context.line_to(*x, *y);
+            }
+            context.stroke();
+        }
+    }
+}
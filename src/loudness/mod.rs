@@ -0,0 +1,15 @@
+mod common;
+mod plotter;
+
+pub use crate::loudness::common::*;
+pub use crate::loudness::plotter::*;
+
+#[cfg(feature = "js")]
+mod js;
+#[cfg(feature = "js")]
+pub use crate::loudness::js::*;
+
+#[cfg(feature = "yew-components")]
+mod yew_component;
+#[cfg(feature = "yew-components")]
+pub use crate::loudness::yew_component::*;
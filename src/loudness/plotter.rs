@@ -0,0 +1,84 @@
+use crate::loudness::common::*;
+use crate::scale::plot_scale;
+use crate::*;
+use scales::prelude::*;
+
+pub fn plot_loudness_history(
+    history: &LoudnessHistoryModel,
+    width: f64,
+    height: f64,
+) -> LoudnessHistoryGraph {
+    let time_scale = history.time_scale();
+    let loudness_scale = history.loudness_scale();
+
+    let x_scale = PixelScale::new(0.0, width);
+    let y_scale = PixelScale::inverted(0.0, height);
+    let x_conv = (&time_scale, &x_scale);
+    let y_conv = (&loudness_scale, &y_scale);
+
+    let now = history.now();
+    let min = loudness_scale.min();
+    let max = loudness_scale.max();
+    let y = |value: f64| y_conv.convert(value.max(min).min(max));
+
+    // keep the first point left of the visible range so the trace reaches the left edge
+    let first = history
+        .points
+        .iter()
+        .rposition(|p| p.time - now < -history.visible_duration)
+        .unwrap_or(0);
+    let visible = history.points.iter().skip(first);
+
+    let mut short_term = vec![];
+    let mut true_peak = vec![];
+    for point in visible {
+        let x = x_conv.convert(point.time - now);
+        short_term.push((x, y(point.short_term)));
+        true_peak.push((x, y(point.true_peak)));
+    }
+
+    let upper = y(history.target + history.tolerance);
+    let lower = y(history.target - history.tolerance);
+    let target_range = Bounds {
+        x: 0.0,
+        y: upper,
+        width,
+        height: lower - upper,
+    };
+
+    let time_format = LabelFormat::Time(false);
+    let loudness_format = LabelFormat::GainShort(false);
+
+    LoudnessHistoryGraph {
+        short_term,
+        true_peak,
+        target_range,
+        time_scale: plot_scale(&time_scale, &x_scale, height, Some(&time_format)),
+        loudness_scale: plot_scale(&loudness_scale, &y_scale, width, Some(&loudness_format)),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_plot_visible_range() {
+        let mut history = LoudnessHistoryModel::new(1_000, -23.0, 1.0);
+        history.visible_duration = 10.0;
+        for i in 0..=20 {
+            history.push(i as f64, -23.0, f64::NEG_INFINITY);
+        }
+
+        let graph = history.plot(100.0, 50.0);
+
+        assert_eq!(12, graph.short_term.len());
+        assert!(graph.short_term[0].0 < 0.0);
+        assert!((graph.short_term[11].0 - 100.0).abs() < 1e-9);
+        assert!((graph.true_peak[11].1 - 50.0).abs() < 1e-9);
+
+        let (_, target_y) = graph.short_term[11];
+        let range = &graph.target_range;
+        assert!(range.y < target_y && target_y < range.y + range.height);
+    }
+}
@@ -0,0 +1,171 @@
+use crate::js_utils::*;
+use crate::loudness::*;
+use crate::*;
+use derivative::*;
+use wasm_bindgen::prelude::*;
+use web_sys::HtmlCanvasElement;
+use yew::prelude::*;
+
+/// The factor the visible duration changes by per wheel step.
+const ZOOM_STEP: f64 = 1.25;
+
+pub struct LoudnessHistory {
+    props: Props,
+    link: ComponentLink<Self>,
+    canvas: NodeRef,
+    renderer: Option<CanvasLoudnessHistoryRenderer>,
+    render_callback: Closure<dyn FnMut()>,
+    needs_repaint: bool,
+    /// The visible duration as zoomed by the user, kept when the host pushes new readings.
+    visible_duration: Seconds,
+}
+
+#[derive(Derivative, Properties)]
+#[derivative(Debug, Clone, PartialEq)]
+pub struct Props {
+    pub id: String,
+    /// Not compared on changes, its readings buffer is large and changes with every reading
+    /// anyway. A new history is always repainted.
+    #[derivative(PartialEq = "ignore")]
+    pub history: LoudnessHistoryModel,
+    pub width: f64,
+    pub height: f64,
+    /// Called with the new visible duration when the user zooms.
+    #[derivative(PartialEq = "ignore")]
+    pub on_zoom: Option<Callback<Seconds>>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Msg {
+    Render,
+    Wheel(WheelEvent),
+}
+
+impl Component for LoudnessHistory {
+    type Message = Msg;
+
+    type Properties = Props;
+
+    fn create(props: Self::Properties, link: ComponentLink<Self>) -> Self {
+        let cb_link = link.clone();
+        let render_callback =
+            Closure::wrap(Box::new(move || cb_link.send_message(Msg::Render)) as Box<dyn FnMut()>);
+        LoudnessHistory {
+            visible_duration: props.history.visible_duration,
+            props,
+            link,
+            canvas: NodeRef::default(),
+            renderer: None,
+            render_callback,
+            needs_repaint: false,
+        }
+    }
+
+    fn update(&mut self, msg: Self::Message) -> ShouldRender {
+        match msg {
+            Msg::Render => self.render(),
+            Msg::Wheel(e) => self.handle_wheel(e),
+        }
+        false
+    }
+
+    fn change(&mut self, props: Self::Properties) -> ShouldRender {
+        let changed = props != self.props;
+        self.visible_duration = next_visible_duration(self.visible_duration, &self.props, &props);
+        self.props = props;
+        self.props.history.visible_duration = self.visible_duration;
+        self.schedule_repaint();
+        changed
+    }
+
+    fn view(&self) -> Html {
+        let wheel_callback = self.link.callback(Msg::Wheel);
+        html! {
+            <canvas id={self.props.id.clone()} class="loudness-history" ref=self.canvas.clone()
+            width={self.props.width} height={self.props.height} onwheel={wheel_callback}/>
+        }
+    }
+
+    fn rendered(&mut self, first_render: bool) {
+        if first_render {
+            self.renderer = self
+                .canvas
+                .cast::<HtmlCanvasElement>()
+                .and_then(CanvasLoudnessHistoryRenderer::new);
+        }
+
+        self.schedule_repaint();
+    }
+}
+
+impl LoudnessHistory {
+    fn render(&mut self) {
+        self.needs_repaint = false;
+        if let Some(renderer) = self.renderer.as_ref() {
+            renderer.render_to_canvas(&self.props.history);
+        }
+    }
+
+    fn schedule_repaint(&mut self) {
+        if !self.needs_repaint {
+            self.needs_repaint = true;
+            request_animation_frame(&self.render_callback);
+        }
+    }
+
+    fn handle_wheel(&mut self, e: WheelEvent) {
+        let factor = if e.delta_y() > 0.0 {
+            ZOOM_STEP
+        } else {
+            1.0 / ZOOM_STEP
+        };
+        let history = &mut self.props.history;
+        let before = history.visible_duration;
+        history.zoom(factor);
+        let after = history.visible_duration;
+        self.visible_duration = after;
+
+        if (after - before).abs() > f64::EPSILON {
+            self.schedule_repaint();
+            if let Some(Callback::Callback(fun)) = &self.props.on_zoom {
+                fun(after);
+            }
+        }
+
+        e.prevent_default();
+    }
+}
+
+/// The visible duration for new props. A change of the host's visible duration is taken over,
+/// otherwise the user's zoom is kept.
+fn next_visible_duration(current: Seconds, previous: &Props, props: &Props) -> Seconds {
+    let host = props.history.visible_duration;
+    if (host - previous.history.visible_duration).abs() > f64::EPSILON {
+        host
+    } else {
+        current
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_zoom_survives_new_readings() {
+        let previous = Props {
+            id: "loudness".to_owned(),
+            history: LoudnessHistoryModel::new(100, -23.0, 1.0),
+            width: 300.0,
+            height: 100.0,
+            on_zoom: None,
+        };
+
+        let mut props = previous.clone();
+        props.history.push(0.1, -20.0, -3.0);
+        assert!((next_visible_duration(30.0, &previous, &props) - 30.0).abs() < f64::EPSILON);
+
+        props.history.visible_duration = 120.0;
+        assert!((next_visible_duration(30.0, &previous, &props) - 120.0).abs() < f64::EPSILON);
+    }
+}
@@ -0,0 +1,207 @@
+use crate::meter::common::MeterModel;
+use crate::utils::to_db;
+use crate::*;
+use scales::prelude::*;
+
+/// Peak, RMS and crest factor of one channel over the last refresh period, all in dBFS or dB.
+#[derive(Debug, PartialEq, Clone)]
+pub struct LevelReading {
+    pub peak: PeakValue,
+    pub rms: MeterValue,
+    /// The ratio of peak to RMS level in dB.
+    pub crest_factor: Gain,
+}
+
+/// Computes meter readings from blocks of samples so hosts don't have to. Sample peak is taken
+/// over each refresh period, RMS over a sliding window of configurable length.
+#[derive(Debug, PartialEq, Clone)]
+pub struct LevelAnalyzer {
+    pub sample_rate: f64,
+    channels: Vec<ChannelState>,
+    refresh_samples: usize,
+    since_refresh: usize,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+struct ChannelState {
+    squares: Vec<f64>,
+    position: usize,
+    sum: f64,
+    peak: f64,
+}
+
+impl LevelAnalyzer {
+    /// Creates an analyzer emitting `refresh_rate` readings per second, e.g. the display's frame
+    /// rate.
+    pub fn new(
+        channels: usize,
+        sample_rate: f64,
+        rms_window: Seconds,
+        refresh_rate: f64,
+    ) -> LevelAnalyzer {
+        let window = ((rms_window * sample_rate).round() as usize).max(1);
+        let refresh_samples = ((sample_rate / refresh_rate).round() as usize).max(1);
+        LevelAnalyzer {
+            sample_rate,
+            channels: vec![ChannelState::new(window); channels],
+            refresh_samples,
+            since_refresh: 0,
+        }
+    }
+
+    pub fn channels(&self) -> usize {
+        self.channels.len()
+    }
+
+    /// Processes one block per channel. Returns the readings of every refresh period completed
+    /// during the block, each holding one reading per channel.
+    pub fn process_block(&mut self, block: &[&[Sample]]) -> Vec<Vec<LevelReading>> {
+        let len = block.iter().map(|channel| channel.len()).min().unwrap_or(0);
+        let mut readings = vec![];
+        for i in 0..len {
+            for (state, channel) in self.channels.iter_mut().zip(block.iter()) {
+                state.push(channel[i]);
+            }
+            if let Some(reading) = self.advance() {
+                readings.push(reading);
+            }
+        }
+        readings
+    }
+
+    /// Like `process_block` for frames of interleaved samples.
+    pub fn process_interleaved(&mut self, block: &[Sample]) -> Vec<Vec<LevelReading>> {
+        let mut readings = vec![];
+        if self.channels.is_empty() {
+            return readings;
+        }
+        for frame in block.chunks_exact(self.channels.len()) {
+            for (state, sample) in self.channels.iter_mut().zip(frame.iter()) {
+                state.push(*sample);
+            }
+            if let Some(reading) = self.advance() {
+                readings.push(reading);
+            }
+        }
+        readings
+    }
+
+    pub fn reset(&mut self) {
+        for state in self.channels.iter_mut() {
+            *state = ChannelState::new(state.squares.len());
+        }
+        self.since_refresh = 0;
+    }
+
+    fn advance(&mut self) -> Option<Vec<LevelReading>> {
+        self.since_refresh += 1;
+        if self.since_refresh < self.refresh_samples {
+            return None;
+        }
+        self.since_refresh = 0;
+        Some(self.channels.iter_mut().map(|s| s.read()).collect())
+    }
+}
+
+impl ChannelState {
+    fn new(window: usize) -> ChannelState {
+        ChannelState {
+            squares: vec![0.0; window],
+            position: 0,
+            sum: 0.0,
+            peak: 0.0,
+        }
+    }
+
+    fn push(&mut self, sample: Sample) {
+        let sample = sample as f64;
+        let square = sample * sample;
+        self.sum += square - self.squares[self.position];
+        self.squares[self.position] = square;
+        self.position += 1;
+        if self.position == self.squares.len() {
+            self.position = 0;
+            // avoid accumulating rounding errors in the running sum
+            self.sum = self.squares.iter().sum();
+        }
+        self.peak = self.peak.max(sample.abs());
+    }
+
+    fn read(&mut self) -> LevelReading {
+        let rms = (self.sum.max(0.0) / self.squares.len() as f64).sqrt();
+        let peak = self.peak;
+        self.peak = 0.0;
+        let crest_factor = if rms > 0.0 { to_db(peak / rms) } else { 0.0 };
+        LevelReading {
+            peak: to_db(peak),
+            rms: to_db(rms),
+            crest_factor,
+        }
+    }
+}
+
+impl<S: Scale<f64> + Clone> MeterModel<S> {
+    /// Updates the meter with the RMS level as value and the sample peak as peak.
+    pub fn update_with_reading(&self, reading: &LevelReading) -> MeterModel<S> {
+        self.update(reading.rms, reading.peak)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn sine(len: usize, period: usize, amplitude: f32) -> Vec<Sample> {
+        (0..len)
+            .map(|i| {
+                let phase = 2.0 * std::f32::consts::PI * i as f32 / period as f32;
+                amplitude * phase.sin()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_sine_levels() {
+        let mut analyzer = LevelAnalyzer::new(1, 48_000.0, 0.01, 100.0);
+        let signal = sine(4_800, 48, 1.0);
+
+        let readings = analyzer.process_block(&[&signal]);
+
+        assert_eq!(10, readings.len());
+        let reading = &readings[9][0];
+        assert!(reading.peak.abs() < 0.01);
+        assert!((reading.rms - -3.0103).abs() < 0.01);
+        assert!((reading.crest_factor - 3.0103).abs() < 0.01);
+    }
+
+    #[test]
+    fn test_interleaved_matches_block() {
+        let left = sine(960, 32, 0.5);
+        let right = sine(960, 64, 0.25);
+        let interleaved: Vec<Sample> = left
+            .iter()
+            .zip(right.iter())
+            .flat_map(|(l, r)| vec![*l, *r])
+            .collect();
+
+        let mut block_analyzer = LevelAnalyzer::new(2, 48_000.0, 0.005, 200.0);
+        let mut interleaved_analyzer = block_analyzer.clone();
+
+        let by_block = block_analyzer.process_block(&[&left, &right]);
+        let by_frame = interleaved_analyzer.process_interleaved(&interleaved);
+
+        assert_eq!(4, by_block.len());
+        assert_eq!(by_block, by_frame);
+        assert!(by_block[3][0].peak > by_block[3][1].peak);
+    }
+
+    #[test]
+    fn test_silence() {
+        let mut analyzer = LevelAnalyzer::new(1, 100.0, 0.1, 10.0);
+
+        let readings = analyzer.process_block(&[&[0.0; 10]]);
+
+        assert_eq!(f64::NEG_INFINITY, readings[0][0].rms);
+        assert_eq!(0.0, readings[0][0].crest_factor);
+    }
+}
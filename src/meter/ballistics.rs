@@ -0,0 +1,121 @@
+use crate::meter::common::{MeterMode, MeterModel};
+use crate::*;
+use scales::prelude::*;
+
+/// One-pole smoothing of meter readings with separate attack and release times. Attack applies
+/// when the reading moves away from the meter's rest value, release when it falls back.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Ballistics {
+    /// Time to reach 1 - 1/e of a step away from the rest value.
+    pub attack: Seconds,
+    /// Time to fall back to 1/e of the distance towards the rest value.
+    pub release: Seconds,
+    pub mode: MeterMode,
+    pub value: MeterValue,
+}
+
+impl Ballistics {
+    pub fn new(attack: Seconds, release: Seconds, mode: MeterMode, rest: MeterValue) -> Ballistics {
+        Ballistics {
+            attack,
+            release,
+            mode,
+            value: rest,
+        }
+    }
+
+    /// Fast attack and a medium release, following gain reduction without hiding short
+    /// transients.
+    pub fn gain_reduction() -> Ballistics {
+        Ballistics::new(0.001, 0.15, MeterMode::GainReduction, 0.0)
+    }
+
+    /// Integration time of a classic VU meter.
+    pub fn vu(rest: MeterValue) -> Ballistics {
+        Ballistics::new(0.3, 0.3, MeterMode::Level, rest)
+    }
+
+    /// Moves the value towards `target` as if `elapsed` seconds had passed and returns it.
+    pub fn process(&mut self, target: MeterValue, elapsed: Seconds) -> MeterValue {
+        let attacking = match self.mode {
+            MeterMode::Level => target > self.value,
+            MeterMode::GainReduction => target < self.value,
+        };
+        let time_constant = if attacking { self.attack } else { self.release };
+        let coefficient = if time_constant > 0.0 {
+            1.0 - (-elapsed.max(0.0) / time_constant).exp()
+        } else {
+            1.0
+        };
+        self.value += (target - self.value) * coefficient;
+        self.value
+    }
+
+    pub fn reset(&mut self, rest: MeterValue) {
+        self.value = rest;
+    }
+}
+
+impl<S: Scale<f64> + Clone> MeterModel<S> {
+    /// The meter with its value moved towards the reading `target` as if `elapsed` seconds had
+    /// passed. Gain reduction meters follow `ballistics`, level meters show the reading as is.
+    pub fn with_ballistics(
+        &self,
+        ballistics: &mut Ballistics,
+        target: MeterValue,
+        elapsed: Seconds,
+    ) -> MeterModel<S> {
+        let value = match self.mode {
+            MeterMode::Level => target,
+            MeterMode::GainReduction => ballistics.process(target, elapsed),
+        };
+        MeterModel {
+            value,
+            ..self.clone()
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_gain_reduction_ballistics() {
+        let mut ballistics = Ballistics::gain_reduction();
+
+        let attacked = ballistics.process(-10.0, 0.001);
+        assert!((attacked - -10.0 * (1.0 - (-1.0f64).exp())).abs() < 1e-9);
+
+        ballistics.process(-10.0, 1.0);
+        let released = ballistics.process(0.0, 0.15);
+        assert!((released - -10.0 * (-1.0f64).exp()).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_zero_time_constant_jumps() {
+        let mut ballistics = Ballistics::new(0.0, 0.5, MeterMode::Level, -60.0);
+
+        assert!((ballistics.process(-6.0, 0.01) - -6.0).abs() < f64::EPSILON);
+        assert!(ballistics.process(-60.0, 0.01) > -60.0);
+    }
+
+    #[test]
+    fn test_gain_reduction_meter_follows_ballistics() {
+        let mut ballistics = Ballistics::gain_reduction();
+        let meter = MeterModel::gain_reduction(24.0).update(-10.0, -10.0);
+
+        let attacked = meter.with_ballistics(&mut ballistics, -10.0, 0.001);
+        assert!((attacked.value - -10.0 * (1.0 - (-1.0f64).exp())).abs() < 1e-9);
+        // the peak is shown as read
+        assert!((attacked.peak - -10.0).abs() < f64::EPSILON);
+
+        let settled = attacked.with_ballistics(&mut ballistics, -10.0, 1.0);
+        let released = settled.with_ballistics(&mut ballistics, 0.0, 0.15);
+        assert!((released.value - -10.0 * (-1.0f64).exp()).abs() < 1e-6);
+
+        let level = MeterModel::linear(-60.0, 0.0);
+        let level = level.with_ballistics(&mut Ballistics::vu(-60.0), -6.0, 0.001);
+        assert!((level.value - -6.0).abs() < f64::EPSILON);
+    }
+}
@@ -0,0 +1,176 @@
+use crate::meter::common::MeterMode;
+use crate::*;
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::Arc;
+
+/// Creates a lock-free ring buffer for meter updates with room for `capacity` pending updates.
+/// The sender is meant to live on the audio side, the receiver with the widget, which drains all
+/// pending updates once per animation frame instead of re-rendering for each of them.
+pub fn meter_bus(capacity: usize) -> (MeterSender, MeterReceiver) {
+    let bus = Arc::new(MeterBus::new(capacity.max(1)));
+    (MeterSender { bus: bus.clone() }, MeterReceiver { bus })
+}
+
+#[derive(Debug)]
+struct MeterBus {
+    values: Vec<AtomicU64>,
+    peaks: Vec<AtomicU64>,
+    /// Total number of updates written, only modified by the sender.
+    head: AtomicUsize,
+    /// Total number of updates read.
+    tail: AtomicUsize,
+    dropped: AtomicUsize,
+}
+
+impl MeterBus {
+    fn new(capacity: usize) -> MeterBus {
+        MeterBus {
+            values: (0..capacity).map(|_| AtomicU64::new(0)).collect(),
+            peaks: (0..capacity).map(|_| AtomicU64::new(0)).collect(),
+            head: AtomicUsize::new(0),
+            tail: AtomicUsize::new(0),
+            dropped: AtomicUsize::new(0),
+        }
+    }
+
+    fn capacity(&self) -> usize {
+        self.values.len()
+    }
+}
+
+/// The producing end of a meter bus. There is exactly one sender per bus, so it can't be cloned.
+#[derive(Debug)]
+pub struct MeterSender {
+    bus: Arc<MeterBus>,
+}
+
+impl MeterSender {
+    /// Pushes an update without allocating or blocking. If the receiver has fallen behind and the
+    /// buffer is full the update is dropped and `false` is returned.
+    pub fn push(&mut self, value: MeterValue, peak: PeakValue) -> bool {
+        let bus = &self.bus;
+        let head = bus.head.load(Ordering::Relaxed);
+        let tail = bus.tail.load(Ordering::Acquire);
+        if head.wrapping_sub(tail) >= bus.capacity() {
+            bus.dropped.fetch_add(1, Ordering::Relaxed);
+            return false;
+        }
+        let slot = head % bus.capacity();
+        bus.values[slot].store(value.to_bits(), Ordering::Relaxed);
+        bus.peaks[slot].store(peak.to_bits(), Ordering::Relaxed);
+        bus.head.store(head.wrapping_add(1), Ordering::Release);
+        true
+    }
+}
+
+/// The consuming end of a meter bus. Clones share the same buffer, so any update is only ever
+/// received by one of them.
+#[derive(Debug, Clone)]
+pub struct MeterReceiver {
+    bus: Arc<MeterBus>,
+}
+
+impl PartialEq for MeterReceiver {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.bus, &other.bus)
+    }
+}
+
+impl MeterReceiver {
+    /// Takes all pending updates and reduces them to the one furthest from the meter's rest
+    /// value, i.e. the maximum for level meters and the minimum for gain reduction meters.
+    /// Returns `None` if nothing has been pushed since the last call.
+    pub fn drain(&self, mode: &MeterMode) -> Option<Update> {
+        let bus = &self.bus;
+        loop {
+            let tail = bus.tail.load(Ordering::Acquire);
+            let head = bus.head.load(Ordering::Acquire);
+            if head == tail {
+                return None;
+            }
+
+            let mut update: Option<Update> = None;
+            let mut index = tail;
+            while index != head {
+                let slot = index % bus.capacity();
+                let value = f64::from_bits(bus.values[slot].load(Ordering::Relaxed));
+                let peak = f64::from_bits(bus.peaks[slot].load(Ordering::Relaxed));
+                update = Some(match (update, mode) {
+                    (None, _) => (value, peak),
+                    (Some((v, p)), MeterMode::Level) => (v.max(value), p.max(peak)),
+                    (Some((v, p)), MeterMode::GainReduction) => (v.min(value), p.min(peak)),
+                });
+                index = index.wrapping_add(1);
+            }
+
+            // another receiver got there first, its updates must not be reported twice
+            if bus
+                .tail
+                .compare_exchange(tail, head, Ordering::AcqRel, Ordering::Relaxed)
+                .is_ok()
+            {
+                return update;
+            }
+        }
+    }
+
+    /// The number of updates that were dropped because the buffer was full.
+    pub fn dropped(&self) -> usize {
+        self.bus.dropped.load(Ordering::Relaxed)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_drain_reduces_pending_updates() {
+        let (mut sender, receiver) = meter_bus(8);
+
+        assert_eq!(None, receiver.drain(&MeterMode::Level));
+
+        sender.push(-20.0, -12.0);
+        sender.push(-18.0, -14.0);
+        sender.push(-24.0, -6.0);
+
+        assert_eq!(Some((-18.0, -6.0)), receiver.drain(&MeterMode::Level));
+        assert_eq!(None, receiver.drain(&MeterMode::Level));
+
+        sender.push(-3.0, -5.0);
+        sender.push(-4.0, -2.0);
+
+        assert_eq!(
+            Some((-4.0, -5.0)),
+            receiver.drain(&MeterMode::GainReduction)
+        );
+    }
+
+    #[test]
+    fn test_full_bus_drops_updates() {
+        let (mut sender, receiver) = meter_bus(2);
+
+        assert!(sender.push(-10.0, -10.0));
+        assert!(sender.push(-9.0, -9.0));
+        assert!(!sender.push(0.0, 0.0));
+        assert_eq!(1, receiver.dropped());
+
+        assert_eq!(Some((-9.0, -9.0)), receiver.drain(&MeterMode::Level));
+        assert!(sender.push(-1.0, -1.0));
+        assert_eq!(Some((-1.0, -1.0)), receiver.drain(&MeterMode::Level));
+    }
+
+    #[test]
+    fn test_sender_on_other_thread() {
+        let (mut sender, receiver) = meter_bus(1_024);
+
+        let producer = std::thread::spawn(move || {
+            for i in 0..1_000 {
+                sender.push(-(i as f64), -(i as f64) / 2.0);
+            }
+        });
+        producer.join().expect("producer panicked");
+
+        assert_eq!(Some((0.0, 0.0)), receiver.drain(&MeterMode::Level));
+    }
+}
@@ -1,29 +1,145 @@
+use crate::meter::deflection::*;
+use crate::scale::{HorizontalPosition, Layout, ScaleModel, VerticalPosition};
+use crate::utils::*;
 use crate::*;
+use scales::prelude::*;
+
+pub const MAJOR_METER_MARKERS: [f64; 8] = [-60.0, -50.0, -40.0, -30.0, -20.0, -10.0, -5.0, 0.0];
+pub const MINOR_METER_MARKERS: [f64; 12] = [
+    -70.0, -65.0, -55.0, -45.0, -35.0, -25.0, -15.0, -3.0, 3.0, 6.0, 9.0, 12.0,
+];
+
+pub const MAJOR_GAIN_REDUCTION_MARKERS: [f64; 7] = [-30.0, -20.0, -15.0, -10.0, -6.0, -3.0, 0.0];
+pub const MINOR_GAIN_REDUCTION_MARKERS: [f64; 6] = [-25.0, -12.0, -8.0, -4.0, -2.0, -1.0];
+
+pub type LinearMeterModel = MeterModel<LinearScale<f64>>;
+pub type IecMeterModel = MeterModel<DeflectionScale>;
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum MeterMode {
+    /// Levels rest at the meter's minimum and the bar grows towards its maximum.
+    Level,
+    /// Gain reduction rests at the meter's maximum (usually 0 dB) and the bar grows towards its
+    /// minimum. The peak value is the strongest reduction.
+    GainReduction,
+}
 
 #[derive(Debug, PartialEq, Clone)]
-pub struct MeterModel {
+pub struct MeterModel<S: Scale<f64>> {
     pub min: MeterValue,
     pub max: MeterValue,
     pub value: MeterValue,
     pub peak: PeakValue,
+    /// The peak furthest from the rest value since the last reset.
+    pub max_hold: PeakValue,
+    pub mode: MeterMode,
+    pub clip: ClipIndicator,
+    pub scale: ScaleModel<S>,
 }
 
-impl MeterModel {
-    pub fn new(min: MeterValue, max: MeterValue) -> MeterModel {
+impl<S: Scale<f64> + Clone> MeterModel<S> {
+    pub fn new(scale: ScaleModel<S>) -> MeterModel<S> {
+        let min = scale.min();
+        let max = scale.max();
         MeterModel {
             min,
             max,
             value: min,
             peak: max,
+            max_hold: min,
+            mode: MeterMode::Level,
+            clip: ClipIndicator::new(max, 3),
+            scale,
         }
     }
 
-    pub fn update(&self, value: MeterValue, peak: PeakValue) -> MeterModel {
+    /// A meter in gain reduction mode. Overs are never detected in this mode.
+    pub fn new_gain_reduction(scale: ScaleModel<S>) -> MeterModel<S> {
+        let max = scale.max();
+        MeterModel {
+            value: max,
+            max_hold: max,
+            mode: MeterMode::GainReduction,
+            clip: ClipIndicator::new(f64::INFINITY, 1),
+            ..MeterModel::new(scale)
+        }
+    }
+
+    /// Updates value and peak. Since no individual samples are known, every peak that reaches the
+    /// over threshold after having been below it counts as one over.
+    pub fn update(&self, value: MeterValue, peak: PeakValue) -> MeterModel<S> {
+        let mut clip = self.clip.clone();
+        clip.register_peak(peak);
+        self.updated(value, peak, clip)
+    }
+
+    /// Updates value and peak and detects overs in the provided samples, applying the clip
+    /// indicator's consecutive sample criterion.
+    pub fn update_with_samples(
+        &self,
+        value: MeterValue,
+        peak: PeakValue,
+        samples: &[Sample],
+    ) -> MeterModel<S> {
+        let mut clip = self.clip.clone();
+        clip.register_samples(samples);
+        self.updated(value, peak, clip)
+    }
+
+    fn updated(&self, value: MeterValue, peak: PeakValue, clip: ClipIndicator) -> MeterModel<S> {
+        let max_hold = match self.mode {
+            MeterMode::Level => self.max_hold.max(peak),
+            MeterMode::GainReduction => self.max_hold.min(peak),
+        };
         MeterModel {
             min: self.min,
             max: self.max,
             value,
             peak,
+            max_hold,
+            mode: self.mode.clone(),
+            clip,
+            scale: self.scale.clone(),
+        }
+    }
+}
+
+impl<S: Scale<f64> + Clone + PartialEq> MeterModel<S> {
+    /// Takes the bounds, mode and scale of `config` while keeping the readings, max hold and clip
+    /// state of this meter. If the mode changed, the readings no longer make sense and `config`
+    /// is taken as is.
+    pub fn reconfigured(&self, config: &MeterModel<S>) -> MeterModel<S> {
+        if self.mode != config.mode {
+            return config.clone();
+        }
+        MeterModel {
+            min: config.min,
+            max: config.max,
+            scale: config.scale.clone(),
+            clip: ClipIndicator {
+                threshold: config.clip.threshold,
+                consecutive_samples: config.clip.consecutive_samples,
+                ..self.clip.clone()
+            },
+            ..self.clone()
+        }
+    }
+}
+
+impl<S: Scale<f64>> MeterModel<S> {
+    pub fn reset_clip(&mut self) {
+        self.clip.reset();
+    }
+
+    pub fn reset_max_hold(&mut self) {
+        self.max_hold = self.rest_value();
+    }
+
+    /// The value at which the meter shows no deflection.
+    pub fn rest_value(&self) -> MeterValue {
+        match self.mode {
+            MeterMode::Level => self.min,
+            MeterMode::GainReduction => self.max,
         }
     }
 
@@ -32,19 +148,605 @@ impl MeterModel {
         y_offset: f64,
         height: f64,
         inverted: bool,
-    ) -> (LinearScale<f64>, LinearScale<f64>) {
+    ) -> (PixelScale, &ScaleModel<S>) {
         let y_scale = if inverted {
-            LinearScale::inverted(y_offset, height as f64)
+            PixelScale::inverted(y_offset, y_offset + height)
         } else {
-            LinearScale::new(y_offset, height as f64)
+            PixelScale::new(y_offset, y_offset + height)
         };
-        let gain_scale = LinearScale::new(self.min, self.max);
-        (y_scale, gain_scale)
+        (y_scale, &self.scale)
     }
 }
 
-impl Default for MeterModel {
+impl MeterModel<LinearScale<f64>> {
+    /// A meter that is linear in dB between min and max.
+    pub fn linear(min: MeterValue, max: MeterValue) -> LinearMeterModel {
+        MeterModel::new(meter_scale(LinearScale::new(min, max)))
+    }
+
+    /// A gain reduction meter showing up to `range` dB of reduction.
+    pub fn gain_reduction(range: MeterValue) -> LinearMeterModel {
+        let scale = LinearScale::new(-range.abs(), 0.0);
+        let min = scale.min();
+        let max = scale.max();
+        let major_scale_markers = filter_markers(&MAJOR_GAIN_REDUCTION_MARKERS, min, max, true);
+        let minor_scale_markers = filter_markers(&MINOR_GAIN_REDUCTION_MARKERS, min, max, true);
+        let scale = ScaleModel::new(
+            scale,
+            Layout::Vertical(VerticalPosition::Right),
+            None,
+            major_scale_markers,
+            minor_scale_markers,
+        );
+        MeterModel::new_gain_reduction(scale)
+    }
+}
+
+impl MeterModel<DeflectionScale> {
+    /// A meter following the IEC 60268-18 deflection law.
+    pub fn iec_60268_18() -> IecMeterModel {
+        MeterModel::new(meter_scale(DeflectionScale::iec_60268_18()))
+    }
+
+    /// A meter with a custom piecewise linear deflection law, see
+    /// [`DeflectionScale::piecewise_linear`].
+    pub fn piecewise_linear(breakpoints: Vec<(MeterValue, f64)>) -> IecMeterModel {
+        MeterModel::new(meter_scale(DeflectionScale::piecewise_linear(breakpoints)))
+    }
+}
+
+impl Default for MeterModel<LinearScale<f64>> {
     fn default() -> Self {
-        MeterModel::new(-60.0, 0.0)
+        MeterModel::linear(-60.0, 0.0)
+    }
+}
+
+/// Wraps a meter scale into a vertical [`ScaleModel`] with the default meter markers.
+pub fn meter_scale<S: Scale<f64>>(scale: S) -> ScaleModel<S> {
+    let min = scale.min();
+    let max = scale.max();
+    let major_scale_markers = filter_markers(&MAJOR_METER_MARKERS, min, max, true);
+    let minor_scale_markers = filter_markers(&MINOR_METER_MARKERS, min, max, true);
+    ScaleModel::new(
+        scale,
+        Layout::Vertical(VerticalPosition::Right),
+        None,
+        major_scale_markers,
+        minor_scale_markers,
+    )
+}
+
+/// A range of meter values drawn in the same color. A zone extends from its `from` value up to
+/// the `from` value of the next zone.
+#[derive(Debug, PartialEq, Clone)]
+pub struct ColorZone {
+    pub from: MeterValue,
+    /// A CSS color. If none is set, the renderer falls back to the canvas' `--zone-<n>-fill`
+    /// style.
+    pub fill: Option<String>,
+}
+
+impl ColorZone {
+    pub fn new(from: MeterValue, fill: Option<&str>) -> ColorZone {
+        ColorZone {
+            from,
+            fill: fill.map(|f| f.to_owned()),
+        }
+    }
+}
+
+/// The classic three zone layout with highlight from -15 dB and warning from -9 dB.
+pub fn default_zones() -> Vec<ColorZone> {
+    vec![
+        ColorZone::new(f64::NEG_INFINITY, None),
+        ColorZone::new(-15.0, None),
+        ColorZone::new(-9.0, None),
+    ]
+}
+
+/// A single zone for gain reduction meters, falling back to the `--zone-1-fill` style.
+pub fn gain_reduction_zones() -> Vec<ColorZone> {
+    vec![ColorZone::new(f64::NEG_INFINITY, None)]
+}
+
+/// Sorts zones by their `from` value, as expected by [`zone_index`].
+pub fn sorted_zones(zones: &[ColorZone]) -> Vec<ColorZone> {
+    let mut zones = zones.to_vec();
+    zones.sort_by(|a, b| {
+        a.from
+            .partial_cmp(&b.from)
+            .unwrap_or(std::cmp::Ordering::Equal)
+    });
+    zones
+}
+
+/// Finds the index of the zone the provided value falls into, assuming zones are sorted by their
+/// `from` value.
+pub fn zone_index(zones: &[ColorZone], value: MeterValue) -> Option<usize> {
+    zones.iter().rposition(|z| z.from <= value)
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum PeakStyle {
+    Off,
+    Segment,
+    Line,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum RenderMode {
+    Continuous,
+    /// Discrete LED style segments separated by gaps of the given size in pixels.
+    Segmented {
+        segmentation: Segmentation,
+        gap: f64,
+    },
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum Segmentation {
+    /// A fixed number of segments of equal size.
+    Count(usize),
+    /// One segment per step of the given size in dB, starting at the meter's minimum.
+    DecibelsPerSegment(MeterValue),
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct Segment {
+    pub from: MeterValue,
+    pub to: MeterValue,
+    pub zone: Option<usize>,
+    pub lit: bool,
+    pub peak: bool,
+}
+
+/// Splits a meter into segments and determines which of them are lit by its current value and
+/// which one holds its peak.
+pub fn segments<S: Scale<f64>>(
+    meter: &MeterModel<S>,
+    segmentation: &Segmentation,
+    zones: &[ColorZone],
+) -> Vec<Segment> {
+    let bounds: Vec<MeterValue> = match segmentation {
+        Segmentation::Count(count) => {
+            let count = (*count).max(1);
+            (0..=count)
+                .map(|i| meter.scale.to_absolute(i as f64 / count as f64))
+                .collect()
+        }
+        Segmentation::DecibelsPerSegment(step) if *step > 0.0 => {
+            let count = ((meter.max - meter.min) / step).ceil() as usize;
+            (0..=count)
+                .map(|i| (meter.min + i as f64 * step).min(meter.max))
+                .collect()
+        }
+        Segmentation::DecibelsPerSegment(_) => vec![meter.min, meter.max],
+    };
+
+    bounds
+        .windows(2)
+        .map(|w| {
+            let (from, to) = (w[0], w[1]);
+            let (lit, peak) = match meter.mode {
+                MeterMode::Level => {
+                    let last = (to - meter.max).abs() < f64::EPSILON;
+                    let peak = meter.peak > from && (meter.peak <= to || last);
+                    (meter.value > from, peak)
+                }
+                MeterMode::GainReduction => {
+                    let first = (from - meter.min).abs() < f64::EPSILON;
+                    let peak = meter.peak < to && (meter.peak >= from || first);
+                    (meter.value < to, peak)
+                }
+            };
+            Segment {
+                from,
+                to,
+                zone: zone_index(zones, from),
+                lit,
+                peak,
+            }
+        })
+        .collect()
+}
+
+/// The regions of a meter's drawing area: the bar itself, the clip indicator LED at the bar's
+/// upper end and the scale next to it.
+#[derive(Debug, PartialEq, Clone)]
+pub struct MeterLayout {
+    /// Orientation of the meter and position of the scale relative to the bar.
+    pub layout: Layout,
+    /// If set, vertical meters fill from top to bottom and horizontal ones from right to left.
+    pub inverted: bool,
+    pub bar: Bounds,
+    pub clip_indicator: Option<Bounds>,
+    pub scale: Option<Bounds>,
+}
+
+impl MeterLayout {
+    pub fn new(
+        bounds: &Bounds,
+        layout: Layout,
+        inverted: bool,
+        bar_thickness: f64,
+        clip_indicator: bool,
+        show_scale: bool,
+    ) -> MeterLayout {
+        let vertical = is_vertical(&layout);
+
+        // everything is laid out along the meter's axis and across it first and mapped to x and y
+        // in the end
+        let (length, thickness) = if vertical {
+            (bounds.height.floor(), bounds.width.floor())
+        } else {
+            (bounds.width.floor(), bounds.height.floor())
+        };
+
+        let bar_thickness = if show_scale {
+            bar_thickness.min(thickness).floor()
+        } else {
+            thickness
+        };
+        let scale_thickness = thickness - bar_thickness;
+
+        let scale_first = matches!(
+            layout,
+            Layout::Vertical(VerticalPosition::Left) | Layout::Horizontal(HorizontalPosition::Top)
+        );
+        let (bar_across, scale_across) = if show_scale && scale_first {
+            (scale_thickness, 0.0)
+        } else {
+            (0.0, bar_thickness)
+        };
+
+        let (led_length, gap) = if clip_indicator {
+            let led_length = bar_thickness.min(length / 16.0).floor();
+            (led_length, (led_length / 4.0).max(1.0).floor())
+        } else {
+            (0.0, 0.0)
+        };
+        let bar_length = length - led_length - gap;
+
+        let upper_end_first = vertical != inverted;
+        let (led_along, bar_along) = if upper_end_first {
+            (0.0, led_length + gap)
+        } else {
+            (bar_length + gap, 0.0)
+        };
+
+        let rect = |along: f64, across: f64, along_length: f64, across_length: f64| {
+            if vertical {
+                Bounds {
+                    x: bounds.x + across,
+                    y: bounds.y + along,
+                    width: across_length,
+                    height: along_length,
+                }
+            } else {
+                Bounds {
+                    x: bounds.x + along,
+                    y: bounds.y + across,
+                    width: along_length,
+                    height: across_length,
+                }
+            }
+        };
+
+        let bar = rect(bar_along, bar_across, bar_length, bar_thickness);
+        let clip_indicator = if clip_indicator {
+            Some(rect(led_along, bar_across, led_length, bar_thickness))
+        } else {
+            None
+        };
+        let scale = if show_scale {
+            Some(rect(bar_along, scale_across, bar_length, scale_thickness))
+        } else {
+            None
+        };
+
+        MeterLayout {
+            layout,
+            inverted,
+            bar,
+            clip_indicator,
+            scale,
+        }
+    }
+
+    pub fn is_vertical(&self) -> bool {
+        is_vertical(&self.layout)
+    }
+
+    /// Maps the bar's pixel coordinates along the meter's axis to relative meter positions.
+    pub fn pixel_scale(&self) -> PixelScale {
+        let bar = &self.bar;
+        match (self.is_vertical(), self.inverted) {
+            (true, false) => PixelScale::inverted(bar.y, bar.y + bar.height),
+            (true, true) => PixelScale::new(bar.y, bar.y + bar.height),
+            (false, false) => PixelScale::new(bar.x, bar.x + bar.width),
+            (false, true) => PixelScale::inverted(bar.x, bar.x + bar.width),
+        }
+    }
+
+    /// The part of the bar between two pixel coordinates along the meter's axis.
+    pub fn span(&self, from: f64, to: f64) -> Bounds {
+        let start = from.min(to);
+        let length = (to - from).abs();
+        let bar = &self.bar;
+        if self.is_vertical() {
+            Bounds {
+                x: bar.x,
+                y: start,
+                width: bar.width,
+                height: length,
+            }
+        } else {
+            Bounds {
+                x: start,
+                y: bar.y,
+                width: length,
+                height: bar.height,
+            }
+        }
+    }
+}
+
+fn is_vertical(layout: &Layout) -> bool {
+    matches!(layout, Layout::Vertical(_))
+}
+
+/// Formats a meter reading for numeric display, showing everything at or below the meter's
+/// minimum as negative infinity.
+pub fn format_readout(value: MeterValue, min: MeterValue) -> String {
+    if value <= min {
+        "-inf".to_owned()
+    } else {
+        format_gain_with_digits(value, false, 1)
+    }
+}
+
+/// Latching over detection. Once an over has been detected the indicator stays lit until it is
+/// explicitly reset.
+#[derive(Debug, PartialEq, Clone)]
+pub struct ClipIndicator {
+    /// Level in dBFS at or above which a sample counts as over.
+    pub threshold: MeterValue,
+    /// Number of consecutive samples at or above the threshold that make up one over.
+    pub consecutive_samples: usize,
+    pub overs: usize,
+    run: usize,
+    peak_over: bool,
+}
+
+impl ClipIndicator {
+    pub fn new(threshold: MeterValue, consecutive_samples: usize) -> ClipIndicator {
+        ClipIndicator {
+            threshold,
+            consecutive_samples,
+            overs: 0,
+            run: 0,
+            peak_over: false,
+        }
+    }
+
+    pub fn is_clipped(&self) -> bool {
+        self.overs > 0
+    }
+
+    pub fn reset(&mut self) {
+        self.overs = 0;
+        self.run = 0;
+        self.peak_over = false;
+    }
+
+    pub fn register_peak(&mut self, peak: PeakValue) {
+        let over = peak >= self.threshold;
+        if over && !self.peak_over {
+            self.overs += 1;
+        }
+        self.peak_over = over;
+    }
+
+    pub fn register_samples(&mut self, samples: &[Sample]) {
+        let threshold = 10f64.powf(self.threshold / 20.0);
+        let required = self.consecutive_samples.max(1);
+        for sample in samples {
+            if (*sample as f64).abs() >= threshold {
+                self.run += 1;
+                if self.run == required {
+                    self.overs += 1;
+                }
+            } else {
+                self.run = 0;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+
+    use super::*;
+
+    #[test]
+    fn test_range_follows_deflection_law() {
+        let meter = MeterModel::piecewise_linear(vec![(0.0, 1.0), (-50.0, 0.0), (-20.0, 0.5)]);
+
+        assert!((meter.min - -50.0).abs() < f64::EPSILON);
+        assert!(meter.max.abs() < f64::EPSILON);
+        assert!((meter.value - meter.min).abs() < f64::EPSILON);
+    }
+
+    #[test]
+    fn test_clip_latches_until_reset() {
+        let meter = LinearMeterModel::default();
+        let meter = meter.update(-3.0, 0.0);
+        let mut meter = meter.update(-20.0, -12.0);
+
+        assert!(meter.clip.is_clipped());
+        assert_eq!(1, meter.clip.overs);
+
+        meter.reset_clip();
+        assert!(!meter.clip.is_clipped());
+    }
+
+    #[test]
+    fn test_max_hold() {
+        let meter = LinearMeterModel::default();
+        let meter = meter.update(-20.0, -6.0);
+        let mut meter = meter.update(-30.0, -18.0);
+
+        assert!((meter.max_hold - -6.0).abs() < f64::EPSILON);
+
+        meter.reset_max_hold();
+        assert!((meter.max_hold - meter.min).abs() < f64::EPSILON);
+    }
+
+    #[test]
+    fn test_reconfigured_keeps_readings() {
+        let meter = LinearMeterModel::default().update(-3.0, 0.5);
+        let config = LinearMeterModel::linear(-48.0, 6.0);
+
+        let meter = meter.reconfigured(&config);
+
+        assert!((meter.min - -48.0).abs() < f64::EPSILON);
+        assert_eq!(config.scale, meter.scale);
+        assert!((meter.peak - 0.5).abs() < f64::EPSILON);
+        assert!((meter.max_hold - 0.5).abs() < f64::EPSILON);
+        assert!(meter.clip.is_clipped());
+
+        let gain_reduction = LinearMeterModel::gain_reduction(20.0);
+        assert_eq!(gain_reduction, meter.reconfigured(&gain_reduction));
+    }
+
+    #[test]
+    fn test_layout_with_scale_and_clip_indicator() {
+        let bounds = Bounds {
+            x: 0.0,
+            y: 0.0,
+            width: 40.0,
+            height: 320.0,
+        };
+
+        let layout = MeterLayout::new(
+            &bounds,
+            Layout::Vertical(VerticalPosition::Left),
+            false,
+            10.0,
+            true,
+            true,
+        );
+
+        let scale = layout.scale.as_ref().expect("no scale bounds");
+        let led = layout
+            .clip_indicator
+            .as_ref()
+            .expect("no clip indicator bounds");
+
+        assert!((layout.bar.x - 30.0).abs() < f64::EPSILON);
+        assert!((scale.width - 30.0).abs() < f64::EPSILON);
+        assert!((led.height - 10.0).abs() < f64::EPSILON);
+        assert!((layout.bar.y - 12.0).abs() < f64::EPSILON);
+        assert!((scale.y - layout.bar.y).abs() < f64::EPSILON);
+        assert!((layout.bar.y + layout.bar.height - 320.0).abs() < f64::EPSILON);
+    }
+
+    #[test]
+    fn test_inverted_horizontal_layout() {
+        let bounds = Bounds {
+            x: 0.0,
+            y: 0.0,
+            width: 320.0,
+            height: 10.0,
+        };
+
+        let layout = MeterLayout::new(
+            &bounds,
+            Layout::Horizontal(HorizontalPosition::Bottom),
+            true,
+            10.0,
+            true,
+            false,
+        );
+
+        let led = layout
+            .clip_indicator
+            .as_ref()
+            .expect("no clip indicator bounds");
+        let pixel_scale = layout.pixel_scale();
+
+        assert!(led.x.abs() < f64::EPSILON);
+        assert!((layout.bar.x - 12.0).abs() < f64::EPSILON);
+        assert!((pixel_scale.to_relative(layout.bar.x) - 1.0).abs() < f64::EPSILON);
+    }
+
+    #[test]
+    fn test_zone_index() {
+        let zones = default_zones();
+
+        assert_eq!(Some(0), zone_index(&zones, -60.0));
+        assert_eq!(Some(1), zone_index(&zones, -15.0));
+        assert_eq!(Some(2), zone_index(&zones, 0.0));
+    }
+
+    #[test]
+    fn test_sorted_zones() {
+        let zones = sorted_zones(&[
+            ColorZone::new(-9.0, Some("red")),
+            ColorZone::new(f64::NEG_INFINITY, None),
+            ColorZone::new(-15.0, Some("yellow")),
+        ]);
+
+        assert_eq!(
+            vec![
+                ColorZone::new(f64::NEG_INFINITY, None),
+                ColorZone::new(-15.0, Some("yellow")),
+                ColorZone::new(-9.0, Some("red"))
+            ],
+            zones
+        );
+        assert_eq!(Some(2), zone_index(&zones, -3.0));
+    }
+
+    #[test]
+    fn test_segments() {
+        let meter = LinearMeterModel::default().update(-31.0, -14.0);
+        let zones = default_zones();
+
+        let by_step = segments(&meter, &Segmentation::DecibelsPerSegment(3.0), &zones);
+
+        assert_eq!(20, by_step.len());
+        assert_eq!(10, by_step.iter().filter(|s| s.lit).count());
+        assert_eq!(1, by_step.iter().filter(|s| s.peak).count());
+        assert_eq!(Some(2), by_step[19].zone);
+
+        let by_count = segments(&meter, &Segmentation::Count(12), &zones);
+
+        assert_eq!(12, by_count.len());
+        assert!((by_count[0].to - -55.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_gain_reduction_segments() {
+        let meter = LinearMeterModel::gain_reduction(20.0).update(-4.5, -7.0);
+        let zones = gain_reduction_zones();
+
+        let segments = segments(&meter, &Segmentation::DecibelsPerSegment(2.0), &zones);
+
+        assert_eq!(3, segments.iter().filter(|s| s.lit).count());
+        assert!(segments[9].lit);
+        assert!(segments[6].peak);
+        assert!((meter.max_hold - -7.0).abs() < f64::EPSILON);
+    }
+
+    #[test]
+    fn test_consecutive_sample_criterion() {
+        let mut clip = ClipIndicator::new(-0.1, 3);
+
+        clip.register_samples(&[1.0, 1.0, 0.5, 1.0, -1.0]);
+        assert_eq!(0, clip.overs);
+
+        clip.register_samples(&[1.0, 1.0, 1.0, 1.0, 0.0, -1.0, -1.0, -1.0]);
+        assert_eq!(2, clip.overs);
     }
 }
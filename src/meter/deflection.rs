@@ -0,0 +1,123 @@
+use crate::*;
+
+/// Breakpoints of the IEC 60268-18 meter deflection law as (level in dBFS, relative deflection).
+const IEC_60268_18: [(MeterValue, f64); 7] = [
+    (-70.0, 0.0),
+    (-60.0, 0.025),
+    (-50.0, 0.075),
+    (-40.0, 0.15),
+    (-30.0, 0.3),
+    (-20.0, 0.5),
+    (0.0, 1.0),
+];
+
+/// A meter deflection law that maps levels to relative deflection by linearly interpolating
+/// between breakpoints. Levels outside the breakpoint range are extrapolated using the outermost
+/// segments.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DeflectionScale {
+    breakpoints: Vec<(MeterValue, f64)>,
+}
+
+impl DeflectionScale {
+    /// Creates a piecewise linear deflection law from (level, relative deflection) pairs. At least
+    /// two breakpoints are required, they will be sorted by level. Both levels and deflections
+    /// have to be strictly increasing, so no segment is flat or vertical.
+    pub fn piecewise_linear(mut breakpoints: Vec<(MeterValue, f64)>) -> DeflectionScale {
+        assert!(
+            breakpoints.len() >= 2,
+            "a deflection law needs at least two breakpoints"
+        );
+        breakpoints.sort_by(|a, b| a.0.partial_cmp(&b.0).expect("invalid breakpoint"));
+        assert!(
+            breakpoints
+                .windows(2)
+                .all(|s| s[0].0 < s[1].0 && s[0].1 < s[1].1),
+            "degenerate deflection law segment"
+        );
+        DeflectionScale { breakpoints }
+    }
+
+    /// The IEC 60268-18 deflection law covering -70 dBFS to 0 dBFS.
+    pub fn iec_60268_18() -> DeflectionScale {
+        DeflectionScale::piecewise_linear(IEC_60268_18.to_vec())
+    }
+
+    fn segment_by_level(&self, level: MeterValue) -> ((f64, f64), (f64, f64)) {
+        let last = self.breakpoints.len() - 1;
+        let index = self.breakpoints[1..last]
+            .iter()
+            .position(|(l, _)| level < *l)
+            .unwrap_or(last - 1);
+        (self.breakpoints[index], self.breakpoints[index + 1])
+    }
+
+    fn segment_by_deflection(&self, deflection: f64) -> ((f64, f64), (f64, f64)) {
+        let last = self.breakpoints.len() - 1;
+        let index = self.breakpoints[1..last]
+            .iter()
+            .position(|(_, d)| deflection < *d)
+            .unwrap_or(last - 1);
+        (self.breakpoints[index], self.breakpoints[index + 1])
+    }
+}
+
+impl scales::Scale<f64> for DeflectionScale {
+    fn to_relative(&self, absolute: f64) -> f64 {
+        let ((l0, d0), (l1, d1)) = self.segment_by_level(absolute);
+        d0 + (absolute - l0) * (d1 - d0) / (l1 - l0)
+    }
+
+    fn to_absolute(&self, relative: f64) -> f64 {
+        let ((l0, d0), (l1, d1)) = self.segment_by_deflection(relative);
+        l0 + (relative - d0) * (l1 - l0) / (d1 - d0)
+    }
+
+    fn max(&self) -> f64 {
+        self.breakpoints[self.breakpoints.len() - 1].0
+    }
+
+    fn min(&self) -> f64 {
+        self.breakpoints[0].0
+    }
+}
+
+#[cfg(test)]
+mod test {
+
+    use super::*;
+    use scales::prelude::*;
+
+    #[test]
+    fn test_iec_deflection() {
+        let scale = DeflectionScale::iec_60268_18();
+
+        assert!((scale.to_relative(-70.0) - 0.0).abs() < 1e-9);
+        assert!((scale.to_relative(-45.0) - 0.1125).abs() < 1e-9);
+        assert!((scale.to_relative(-20.0) - 0.5).abs() < 1e-9);
+        assert!((scale.to_relative(-10.0) - 0.75).abs() < 1e-9);
+        assert!((scale.to_relative(0.0) - 1.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_deflection_round_trip() {
+        let scale = DeflectionScale::iec_60268_18();
+
+        for level in &[-70.0, -62.5, -50.0, -33.3, -20.0, -1.0, 0.0] {
+            let relative = scale.to_relative(*level);
+            assert!((scale.to_absolute(relative) - level).abs() < 1e-9);
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_degenerate_segment() {
+        DeflectionScale::piecewise_linear(vec![(-60.0, 0.0), (-20.0, 0.5), (-20.0, 0.8)]);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_flat_segment() {
+        DeflectionScale::piecewise_linear(vec![(-60.0, 0.0), (-20.0, 0.5), (0.0, 0.5)]);
+    }
+}
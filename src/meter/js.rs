@@ -1,134 +1,326 @@
 use crate::js_utils::*;
 use crate::meter::common::*;
+use crate::scale::{plot_scale, HorizontalPosition, Layout, ScaleModel, VerticalPosition};
+use crate::utils::*;
 use crate::*;
 use scales::prelude::*;
 use web_sys::CanvasRenderingContext2d;
 use web_sys::HtmlCanvasElement;
 
+const TICK_LENGTH: f64 = 6.0;
+const LABEL_PADDING: f64 = 2.0;
+const PEAK_LINE_THICKNESS: f64 = 2.0;
+
 pub struct CanvasMeterRenderer {
     context: CanvasRenderingContext2d,
-    highlight_threshold: MeterValue,
-    warning_threshold: MeterValue,
-    draw_peak: bool,
+    zones: Vec<ColorZone>,
+    peak_style: PeakStyle,
+    render_mode: RenderMode,
     style: Style,
-    bounds: Bounds,
+    layout: MeterLayout,
 }
 
 #[derive(Debug, Clone, PartialEq)]
 struct Style {
     background_fill: Option<String>,
     scale_stroke: Option<String>,
-    base_fill: Option<String>,
-    highlight_fill: Option<String>,
-    warning_fill: Option<String>,
+    scale_label_fill: Option<String>,
+    scale_font: Option<String>,
+    zone_fills: Vec<Option<String>>,
+    segment_off_alpha: f64,
     clip_fill: Option<String>,
-}
-
-#[derive(Debug, Clone, PartialEq)]
-struct Bounds {
-    x: f64,
-    y: f64,
-    width: f64,
-    height: f64,
+    clip_off_fill: Option<String>,
 }
 
 impl CanvasMeterRenderer {
     pub fn new(
         canvas: HtmlCanvasElement,
-        x: f64,
-        y: f64,
-        width: f64,
-        height: f64,
-        draw_peak: bool,
-        highlight_threshold: MeterValue,
-        warning_threshold: MeterValue,
+        layout: MeterLayout,
+        zones: &[ColorZone],
+        peak_style: PeakStyle,
+        render_mode: RenderMode,
     ) -> Option<CanvasMeterRenderer> {
         let context = get_context_2d(&canvas)?;
         let style = get_styles(&canvas);
-
-        let bounds = Bounds {
-            x,
-            y,
-            width,
-            height,
-        };
+        let zones = sorted_zones(zones);
 
         let background_fill = get_style("--background-fill", &style, Some("black"));
         let scale_stroke = get_style("--scale-stroke", &style, Some("#333"));
-        let base_fill = get_style("--base-fill", &style, Some("blue"));
-        let highlight_fill = get_style("--highlight-fill", &style, Some("lightblue"));
-        let warning_fill = get_style("--warning-fill", &style, Some("orange"));
+        let scale_label_fill = get_style("--scale-label-fill", &style, Some("#777"));
+        let scale_font = get_style("--scale-font", &style, Some("9px sans-serif"));
         let clip_fill = get_style("--clip-fill", &style, Some("red"));
+        let clip_off_fill = get_style("--clip-off-fill", &style, Some("#300"));
+        let segment_off_alpha = get_style("--segment-off-alpha", &style, None)
+            .and_then(|a| a.trim().parse().ok())
+            .unwrap_or(0.2);
+
+        // the first three zones fall back to the styles of the classic three zone layout
+        let fallback_fills = [
+            get_style("--base-fill", &style, Some("blue")),
+            get_style("--highlight-fill", &style, Some("lightblue")),
+            get_style("--warning-fill", &style, Some("orange")),
+        ];
+        let zone_fills = zones
+            .iter()
+            .enumerate()
+            .map(|(i, zone)| {
+                let style_name = format!("--zone-{}-fill", (i + 1));
+                zone.fill
+                    .clone()
+                    .or_else(|| get_style(style_name, &style, None))
+                    .or_else(|| fallback_fills[i.min(fallback_fills.len() - 1)].clone())
+            })
+            .collect();
 
         let style = Style {
             background_fill,
             scale_stroke,
-            base_fill,
-            highlight_fill,
-            warning_fill,
+            scale_label_fill,
+            scale_font,
+            zone_fills,
+            segment_off_alpha,
             clip_fill,
+            clip_off_fill,
         };
 
         Some(CanvasMeterRenderer {
             context,
-            highlight_threshold,
-            warning_threshold,
-            bounds,
+            zones,
+            peak_style,
+            render_mode,
+            layout,
             style,
-            draw_peak,
         })
     }
 
-    pub fn render_to_canvas(&self, meter: &MeterModel) {
+    pub fn layout(&self) -> &MeterLayout {
+        &self.layout
+    }
+
+    pub fn render_to_canvas<S: Scale<f64>>(&self, meter: &MeterModel<S>) {
         let context = &self.context;
 
-        let left = self.bounds.x.floor();
-        let width = self.bounds.width.floor();
-        let height = self.bounds.height.floor();
-        let top = self.bounds.y.floor();
-        let bottom = (self.bounds.y + height).floor();
+        if let Some(led) = &self.layout.clip_indicator {
+            let led_fill = if meter.clip.is_clipped() {
+                self.style.clip_fill.as_ref()
+            } else {
+                self.style.clip_off_fill.as_ref()
+            };
+            context.clear_rect(led.x, led.y, led.width, led.height);
+            set_fill(context, led_fill);
+            context.fill_rect(led.x, led.y, led.width, led.height);
+        }
 
-        let y_conv = meter.y_to_gain_converter(self.bounds.y, height, true);
+        let bar = &self.layout.bar;
+        context.clear_rect(bar.x, bar.y, bar.width, bar.height);
 
-        let peak_height = (width / 2.0).min(height / 32.0).floor();
-        let y_peak = y_conv.convert_back(meter.peak).floor();
-        let y_value = y_conv
-            .convert_back(meter.value)
-            .floor()
-            .max(y_peak + peak_height);
-        let y_highlight = y_conv.convert_back(self.highlight_threshold).floor();
-        let y_warning = y_conv.convert_back(self.warning_threshold).floor();
+        let conv = (self.layout.pixel_scale(), &meter.scale);
+        let position = |value: MeterValue| {
+            let value = value.max(meter.min).min(meter.max);
+            conv.convert_back(value).floor()
+        };
 
-        context.clear_rect(left, top, width, bottom - top);
+        match &self.render_mode {
+            RenderMode::Continuous => self.render_continuous(meter, &position),
+            RenderMode::Segmented { segmentation, gap } => {
+                self.render_segments(meter, segmentation, *gap, &position)
+            }
+        }
 
-        if meter.value > meter.min {
-            set_fill(&context, self.style.base_fill.as_ref());
-            context.fill_rect(left, y_highlight, width, bottom - y_highlight);
+        self.render_scale(meter);
+    }
 
-            set_fill(&context, self.style.highlight_fill.as_ref());
-            context.fill_rect(left, y_warning, width, y_highlight - y_warning);
+    fn render_continuous<S: Scale<f64>>(
+        &self,
+        meter: &MeterModel<S>,
+        position: &impl Fn(f64) -> f64,
+    ) {
+        let context = &self.context;
 
-            set_fill(&context, self.style.warning_fill.as_ref());
-            context.fill_rect(left, top, width, y_warning - top);
+        let value = meter.value.max(meter.min).min(meter.max);
+        // gain reduction meters grow from their maximum towards their minimum
+        let (lit_from, lit_to) = match meter.mode {
+            MeterMode::Level => (meter.min, value),
+            MeterMode::GainReduction => (value, meter.max),
+        };
+
+        for (i, zone) in self.zones.iter().enumerate() {
+            let lower = zone.from.max(lit_from);
+            let upper = self
+                .zones
+                .get(i + 1)
+                .map_or(lit_to, |next| next.from.min(lit_to));
+            if lower < upper {
+                set_fill(context, self.style.zone_fills[i].as_ref());
+                self.fill_span(position(lower), position(upper));
+            }
         }
 
-        if meter.value < meter.max {
-            set_fill(&context, self.style.background_fill.as_ref());
-            context.fill_rect(left, top, width, y_value - top);
+        set_fill(context, self.style.background_fill.as_ref());
+        if meter.min < lit_from {
+            self.fill_span(position(meter.min), position(lit_from));
         }
+        if lit_to < meter.max {
+            self.fill_span(position(lit_to), position(meter.max));
+        }
+
+        self.render_peak(meter, position);
+    }
+
+    fn render_segments<S: Scale<f64>>(
+        &self,
+        meter: &MeterModel<S>,
+        segmentation: &Segmentation,
+        gap: f64,
+        position: &impl Fn(f64) -> f64,
+    ) {
+        let context = &self.context;
+
+        let bar = &self.layout.bar;
+        set_fill(context, self.style.background_fill.as_ref());
+        context.fill_rect(bar.x, bar.y, bar.width, bar.height);
 
-        if self.draw_peak {
-            if (meter.peak - meter.max).abs() < f64::EPSILON {
+        let show_peak = self.peak_style != PeakStyle::Off;
+
+        for segment in segments(meter, segmentation, &self.zones) {
+            let zone = match segment.zone {
+                Some(zone) => zone,
+                None => continue,
+            };
+
+            let peak = show_peak && segment.peak;
+            if peak && is_over(meter) {
                 set_fill(context, self.style.clip_fill.as_ref());
-            } else if meter.peak > self.warning_threshold {
-                set_fill(context, self.style.warning_fill.as_ref());
-            } else if meter.peak > self.highlight_threshold {
-                set_fill(context, self.style.highlight_fill.as_ref());
             } else {
-                set_fill(context, self.style.base_fill.as_ref());
+                set_fill(context, self.style.zone_fills[zone].as_ref());
+            }
+
+            if segment.lit || peak {
+                context.set_global_alpha(1.0);
+            } else {
+                context.set_global_alpha(self.style.segment_off_alpha);
             }
 
-            context.fill_rect(left, y_peak, width, peak_height);
+            // the gap is left out at the upper end of each segment
+            let from = position(segment.from);
+            let to = position(segment.to);
+            if (to - from).abs() > gap {
+                self.fill_span(from, to - (to - from).signum() * gap);
+            }
         }
+
+        context.set_global_alpha(1.0);
     }
+
+    fn render_peak<S: Scale<f64>>(&self, meter: &MeterModel<S>, position: &impl Fn(f64) -> f64) {
+        let thickness = match self.peak_style {
+            PeakStyle::Off => return,
+            PeakStyle::Line => PEAK_LINE_THICKNESS,
+            PeakStyle::Segment => {
+                let bar = &self.layout.bar;
+                let (length, across) = if self.layout.is_vertical() {
+                    (bar.height, bar.width)
+                } else {
+                    (bar.width, bar.height)
+                };
+                (across / 2.0).min(length / 32.0).floor().max(1.0)
+            }
+        };
+
+        let context = &self.context;
+
+        if is_over(meter) {
+            set_fill(context, self.style.clip_fill.as_ref());
+        } else if let Some(zone) = zone_index(&self.zones, meter.peak) {
+            set_fill(context, self.style.zone_fills[zone].as_ref());
+        } else {
+            return;
+        }
+
+        let peak = position(meter.peak);
+        let toward_rest = (position(meter.rest_value()) - peak).signum();
+        self.fill_span(peak, peak + toward_rest * thickness);
+    }
+
+    fn fill_span(&self, from: f64, to: f64) {
+        let span = self.layout.span(from, to);
+        self.context
+            .fill_rect(span.x, span.y, span.width, span.height);
+    }
+
+    fn render_scale<S: Scale<f64>>(&self, meter: &MeterModel<S>) {
+        let bounds = match &self.layout.scale {
+            Some(scale) => scale,
+            None => return,
+        };
+
+        let context = &self.context;
+
+        context.clear_rect(bounds.x, bounds.y, bounds.width, bounds.height);
+
+        let scale = ScaleModel::new(
+            &meter.scale.scale,
+            self.layout.layout.clone(),
+            None,
+            meter.scale.major_scale_markers.clone(),
+            meter.scale.minor_scale_markers.clone(),
+        );
+        let pixel_scale = self.layout.pixel_scale();
+        let label_format = LabelFormat::GainShort(false);
+        let graph = plot_scale(&scale, &pixel_scale, TICK_LENGTH, Some(&label_format));
+
+        // the edge of the scale touching the bar and the direction pointing away from it
+        let (tick_edge, direction, text_align, text_baseline) = match &self.layout.layout {
+            Layout::Vertical(VerticalPosition::Left) => {
+                (bounds.x + bounds.width, -1.0, "right", "middle")
+            }
+            Layout::Vertical(VerticalPosition::Right) => (bounds.x, 1.0, "left", "middle"),
+            Layout::Horizontal(HorizontalPosition::Top) => {
+                (bounds.y + bounds.height, -1.0, "center", "bottom")
+            }
+            Layout::Horizontal(HorizontalPosition::Bottom) => (bounds.y, 1.0, "center", "top"),
+        };
+        let vertical = self.layout.is_vertical();
+
+        context.begin_path();
+        set_stroke(context, self.style.scale_stroke.as_ref());
+        let major = graph.major_lines.iter().map(|l| (l, TICK_LENGTH));
+        let minor = graph.minor_lines.iter().map(|l| (l, TICK_LENGTH / 2.0));
+        for (line, length) in major.chain(minor) {
+            let tick_end = tick_edge + direction * length;
+            if vertical {
+                let y = line.y_start + 0.5;
+                context.move_to(tick_edge, y);
+                context.line_to(tick_end, y);
+            } else {
+                let x = line.x_start + 0.5;
+                context.move_to(x, tick_edge);
+                context.line_to(x, tick_end);
+            }
+        }
+        context.stroke();
+
+        if let Some(font) = &self.style.scale_font {
+            context.set_font(font);
+        }
+        context.set_text_align(text_align);
+        context.set_text_baseline(text_baseline);
+        set_fill(context, self.style.scale_label_fill.as_ref());
+
+        let label_offset = tick_edge + direction * (TICK_LENGTH + LABEL_PADDING);
+        for label in &graph.labels {
+            let (x, y) = if vertical {
+                let min_y = bounds.y + LABEL_PADDING * 2.0;
+                let max_y = bounds.y + bounds.height - LABEL_PADDING * 2.0;
+                (label_offset, label.y.max(min_y).min(max_y))
+            } else {
+                (label.x, label_offset)
+            };
+            context.fill_text(&label.text, x, y).ignore();
+        }
+    }
+}
+
+fn is_over<S: Scale<f64>>(meter: &MeterModel<S>) -> bool {
+    meter.mode == MeterMode::Level && meter.peak >= meter.max
 }
@@ -0,0 +1,149 @@
+use crate::meter::analyzer::{LevelAnalyzer, LevelReading};
+use crate::meter::common::*;
+use crate::scale::{Layout, ScaleModel, VerticalPosition};
+use crate::utils::*;
+use crate::*;
+
+/// Converts plain RMS to the sine-calibrated RMS the K-System is specified in, so that a full
+/// scale sine reads 0 dBFS RMS.
+pub const SINE_CALIBRATION: Gain = 3.0103;
+/// Integration time of the K-System's RMS section.
+pub const K_SYSTEM_RMS_WINDOW: Seconds = 0.6;
+/// The dynamic range covered below full scale.
+const K_SYSTEM_RANGE: MeterValue = 60.0;
+
+const K_SYSTEM_MARKERS: [f64; 14] = [
+    -40.0, -30.0, -20.0, -15.0, -10.0, -5.0, 0.0, 4.0, 8.0, 12.0, 14.0, 16.0, 18.0, 20.0,
+];
+const MINOR_K_SYSTEM_MARKERS: [f64; 10] =
+    [-25.0, -8.0, -6.0, -4.0, -3.0, -2.0, -1.0, 1.0, 2.0, 3.0];
+
+/// Bob Katz's K-System. Meters read RMS level relative to a reference at `-k` dBFS, calibrated
+/// so that pink noise at the reference level reads 0. The number is the headroom above the
+/// reference.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum KSystem {
+    K12,
+    K14,
+    K20,
+}
+
+impl KSystem {
+    pub fn headroom(&self) -> MeterValue {
+        match self {
+            KSystem::K12 => 12.0,
+            KSystem::K14 => 14.0,
+            KSystem::K20 => 20.0,
+        }
+    }
+
+    /// The 0 dB reference in dBFS (sine-calibrated RMS).
+    pub fn reference_level(&self) -> MeterValue {
+        -self.headroom()
+    }
+
+    /// Converts a plain RMS level in dBFS to a K-System reading.
+    pub fn rms_to_k(&self, rms: MeterValue) -> MeterValue {
+        rms + SINE_CALIBRATION + self.headroom()
+    }
+
+    /// Converts a sample peak in dBFS to the K-System scale, where full scale sits at `+k`.
+    pub fn peak_to_k(&self, peak: PeakValue) -> PeakValue {
+        peak + self.headroom()
+    }
+
+    /// Converts an analyzer reading to a meter update on the K-System scale.
+    pub fn update(&self, reading: &LevelReading) -> Update {
+        (self.rms_to_k(reading.rms), self.peak_to_k(reading.peak))
+    }
+
+    /// Green up to the reference, yellow up to +4 dB and red above.
+    pub fn zones(&self) -> Vec<ColorZone> {
+        vec![
+            ColorZone::new(f64::NEG_INFINITY, Some("green")),
+            ColorZone::new(0.0, Some("yellow")),
+            ColorZone::new(4.0, Some("red")),
+        ]
+    }
+
+    pub fn scale(&self) -> ScaleModel<LinearScale<f64>> {
+        let max = self.headroom();
+        let min = max - K_SYSTEM_RANGE;
+        ScaleModel::new(
+            LinearScale::new(min, max),
+            Layout::Vertical(VerticalPosition::Right),
+            None,
+            filter_markers(&K_SYSTEM_MARKERS, min, max, true),
+            filter_markers(&MINOR_K_SYSTEM_MARKERS, min, max, true),
+        )
+    }
+}
+
+impl MeterModel<LinearScale<f64>> {
+    /// A meter reading on the scale of the provided K-System, with overs at `+k`, i.e. at 0 dBFS.
+    pub fn k_system(k: KSystem) -> LinearMeterModel {
+        MeterModel::new(k.scale())
+    }
+}
+
+impl LevelAnalyzer {
+    /// An analyzer integrating RMS over the K-System's window.
+    pub fn k_system(channels: usize, sample_rate: f64, refresh_rate: f64) -> LevelAnalyzer {
+        LevelAnalyzer::new(channels, sample_rate, K_SYSTEM_RMS_WINDOW, refresh_rate)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_reference_level_reads_zero() {
+        for k in &[KSystem::K12, KSystem::K14, KSystem::K20] {
+            // plain RMS of pink noise at the sine-calibrated reference level
+            let rms = k.reference_level() - SINE_CALIBRATION;
+            assert!(k.rms_to_k(rms).abs() < 1e-9);
+            assert!((k.peak_to_k(0.0) - k.headroom()).abs() < f64::EPSILON);
+        }
+    }
+
+    #[test]
+    fn test_k_system_scale_and_zones() {
+        let k = KSystem::K14;
+        let meter = MeterModel::k_system(k);
+
+        assert!((meter.max - 14.0).abs() < f64::EPSILON);
+        assert!((meter.min - -46.0).abs() < f64::EPSILON);
+        assert!(meter.scale.major_scale_markers.contains(&4.0));
+        assert!(!meter.scale.major_scale_markers.contains(&20.0));
+
+        let zones = k.zones();
+        assert_eq!(Some(0), zone_index(&zones, -0.5));
+        assert_eq!(Some(1), zone_index(&zones, 3.0));
+        assert_eq!(Some(2), zone_index(&zones, 4.5));
+
+        let reading = LevelReading {
+            peak: -0.5,
+            rms: -20.0,
+            crest_factor: 19.5,
+        };
+        let (value, peak) = k.update(&reading);
+        let meter = meter.update(value, peak);
+        assert!(!meter.clip.is_clipped());
+        assert!((meter.value - -2.9897).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_rms_window() {
+        let mut analyzer = LevelAnalyzer::k_system(1, 1_000.0, 10.0);
+        let sine: Vec<Sample> = (0..600)
+            .map(|i| (2.0 * std::f32::consts::PI * i as f32 / 20.0).sin())
+            .collect();
+
+        // after half the window, half the squares are still silent
+        let readings = analyzer.process_block(&[&sine[..300]]);
+        assert!((readings[2][0].rms - -6.0206).abs() < 0.01);
+        let readings = analyzer.process_block(&[&sine[300..]]);
+        assert!((readings[2][0].rms - -3.0103).abs() < 0.01);
+    }
+}
@@ -1,6 +1,16 @@
+mod analyzer;
+mod ballistics;
+mod bus;
 mod common;
+mod deflection;
+mod k_system;
 
+pub use crate::meter::analyzer::*;
+pub use crate::meter::ballistics::*;
+pub use crate::meter::bus::*;
 pub use crate::meter::common::*;
+pub use crate::meter::deflection::*;
+pub use crate::meter::k_system::*;
 
 #[cfg(feature = "js")]
 mod js;
@@ -1,41 +1,108 @@
 use crate::js_utils::*;
 use crate::meter::*;
+use crate::scale::{Layout, VerticalPosition};
+use crate::*;
+use derivative::*;
+use scales::prelude::*;
+use std::fmt::Debug;
 use wasm_bindgen::prelude::*;
 use web_sys::HtmlCanvasElement;
 use yew::prelude::*;
 
-pub struct Meter {
-    props: Props,
+/// Smoothed values closer than this to the reading in dB are considered settled.
+const SETTLED: MeterValue = 0.01;
+
+pub struct Meter<MeterScale: Scale<f64> + Debug + Clone + PartialEq + 'static> {
+    props: Props<MeterScale>,
+    /// The meter as it is drawn. Readings drained from the bus and resets only change this, so
+    /// they survive the host re-rendering with its own copy of the meter.
+    meter: MeterModel<MeterScale>,
+    /// Smooths the value of gain reduction meters between animation frames.
+    ballistics: Ballistics,
+    /// The latest unsmoothed value, the target of the ballistics.
+    reading: MeterValue,
+    /// The time of the last animation frame in milliseconds.
+    last_frame: Option<f64>,
+    link: ComponentLink<Self>,
     canvas: NodeRef,
     renderer: Option<CanvasMeterRenderer>,
     render_callback: Closure<dyn FnMut()>,
     needs_repaint: bool,
 }
 
-#[derive(Debug, Clone, PartialEq, Properties)]
-pub struct Props {
+#[derive(Derivative, Properties)]
+#[derivative(Debug, Clone, PartialEq)]
+pub struct Props<MeterScale: Scale<f64> + Clone + PartialEq> {
     pub id: String,
-    pub meter: MeterModel,
+    pub meter: MeterModel<MeterScale>,
     pub width: f64,
     pub height: f64,
+    /// The thickness of the meter bar, the remaining space is used for the scale.
     pub bar_width: f64,
+    /// Orientation of the meter and position of the scale.
+    #[prop_or(Layout::Vertical(VerticalPosition::Left))]
+    pub layout: Layout,
+    #[prop_or_default]
+    pub inverted: bool,
+    #[prop_or_else(default_zones)]
+    pub zones: Vec<ColorZone>,
+    #[prop_or(PeakStyle::Segment)]
+    pub peak_style: PeakStyle,
+    #[prop_or(RenderMode::Continuous)]
+    pub render_mode: RenderMode,
+    #[prop_or_default]
+    pub show_clip_indicator: bool,
+    #[prop_or_default]
+    pub show_scale: bool,
+    #[prop_or_default]
+    pub show_readout: bool,
+    /// If set, updates pushed to the bus are drained once per animation frame and applied to the
+    /// meter, so levels don't have to be routed through props.
+    #[prop_or_default]
+    pub bus: Option<MeterReceiver>,
+    /// Called when the clip indicator and max hold are reset by clicking the meter.
+    #[prop_or_default]
+    #[derivative(PartialEq = "ignore")]
+    pub on_reset: Option<Callback<()>>,
+}
+
+impl<MeterScale: Scale<f64> + Clone + PartialEq> Props<MeterScale> {
+    fn renderer_changed(&self, other: &Props<MeterScale>) -> bool {
+        self.width != other.width
+            || self.height != other.height
+            || self.bar_width != other.bar_width
+            || self.layout != other.layout
+            || self.inverted != other.inverted
+            || self.zones != other.zones
+            || self.peak_style != other.peak_style
+            || self.render_mode != other.render_mode
+            || self.show_clip_indicator != other.show_clip_indicator
+            || self.show_scale != other.show_scale
+    }
 }
 
 #[derive(Debug, Clone, PartialEq)]
 pub enum Msg {
     Render,
+    Reset,
 }
 
-impl Component for Meter {
+impl<MeterScale: Scale<f64> + Debug + Clone + PartialEq + 'static> Component for Meter<MeterScale> {
     type Message = Msg;
 
-    type Properties = Props;
+    type Properties = Props<MeterScale>;
 
     fn create(props: Self::Properties, link: ComponentLink<Self>) -> Self {
+        let cb_link = link.clone();
         let render_callback =
-            Closure::wrap(Box::new(move || link.send_message(Msg::Render)) as Box<dyn FnMut()>);
+            Closure::wrap(Box::new(move || cb_link.send_message(Msg::Render)) as Box<dyn FnMut()>);
         Meter {
+            meter: props.meter.clone(),
+            ballistics: Ballistics::gain_reduction(),
+            reading: props.meter.value,
+            last_frame: None,
             props,
+            link,
             canvas: NodeRef::default(),
             renderer: None,
             render_callback,
@@ -46,55 +113,219 @@ impl Component for Meter {
     fn update(&mut self, msg: Self::Message) -> ShouldRender {
         match msg {
             Msg::Render => self.render(),
+            Msg::Reset => self.reset(),
         }
-        false
     }
 
     fn change(&mut self, props: Self::Properties) -> ShouldRender {
+        if props == self.props {
+            return false;
+        }
+        let renderer_changed = props.renderer_changed(&self.props);
+        if renderer_changed {
+            self.renderer = None;
+        }
+        // as long as only levels change, repainting the canvas is enough
+        let view_changed = renderer_changed
+            || props.id != self.props.id
+            || props.show_readout
+            || self.props.show_readout;
+        self.meter = next_meter(&self.meter, &props);
+        if props.bus.is_none() {
+            self.reading = props.meter.value;
+        }
         self.props = props;
-        // TODO check if updates with same meter values are skipped
-        true
+        if !view_changed {
+            self.schedule_repaint();
+        }
+        view_changed
     }
 
     fn view(&self) -> Html {
+        let click_callback = self.link.callback(|_| Msg::Reset);
+
+        let readout = if self.props.show_readout {
+            self.format_readout()
+        } else {
+            html! {}
+        };
+
         html! {
-            <canvas id={self.props.id.clone()} class="meter" ref=self.canvas.clone() width={self.props.width}
-            height={self.props.height}/>
+            <div class="meter">
+                <canvas id={self.props.id.clone()} ref=self.canvas.clone() width={self.props.width}
+                height={self.props.height} onclick={click_callback}/>
+                {readout}
+            </div>
         }
     }
 
-    fn rendered(&mut self, first_render: bool) {
-        if first_render {
+    fn rendered(&mut self, _first_render: bool) {
+        if self.renderer.is_none() {
             self.renderer = self.canvas.cast::<HtmlCanvasElement>().and_then(|canvas| {
                 let rect = canvas.get_bounding_client_rect();
-                // TODO make thresholds properties
-                let highlight_threshold = -15.0;
-                let warning_threshold = -9.0;
+                let bounds = Bounds {
+                    x: 0.0,
+                    y: 0.0,
+                    width: rect.width(),
+                    height: rect.height(),
+                };
+                let layout = MeterLayout::new(
+                    &bounds,
+                    self.props.layout.clone(),
+                    self.props.inverted,
+                    self.props.bar_width,
+                    self.props.show_clip_indicator,
+                    self.props.show_scale,
+                );
                 CanvasMeterRenderer::new(
                     canvas,
-                    0.0,
-                    0.0,
-                    rect.width(),
-                    rect.height(),
-                    true,
-                    highlight_threshold,
-                    warning_threshold,
+                    layout,
+                    &self.props.zones,
+                    self.props.peak_style.clone(),
+                    self.props.render_mode.clone(),
                 )
             });
         }
 
+        self.schedule_repaint();
+    }
+}
+
+impl<MeterScale: Scale<f64> + Debug + Clone + PartialEq + 'static> Meter<MeterScale> {
+    fn render(&mut self) -> ShouldRender {
+        self.needs_repaint = false;
+
+        let now = js_sys::Date::now();
+        let elapsed = self.last_frame.map_or(0.0, |last| (now - last) / 1_000.0);
+        self.last_frame = Some(now);
+
+        let mut changed = false;
+        if let Some(bus) = &self.props.bus {
+            if let Some((value, peak)) = bus.drain(&self.meter.mode) {
+                self.meter = self.meter.update(value, peak);
+                self.reading = value;
+                changed = true;
+            }
+            // keep polling the bus for as long as it is attached
+            self.schedule_repaint();
+        }
+
+        self.meter = self
+            .meter
+            .with_ballistics(&mut self.ballistics, self.reading, elapsed);
+        // keep animating until the value has settled on the reading
+        if (self.meter.value - self.reading).abs() > SETTLED {
+            self.schedule_repaint();
+        }
+
+        if let Some(renderer) = self.renderer.as_ref() {
+            renderer.render_to_canvas(&self.meter);
+        }
+
+        changed && self.props.show_readout
+    }
+
+    fn schedule_repaint(&mut self) {
         if !self.needs_repaint {
             self.needs_repaint = true;
             request_animation_frame(&self.render_callback);
         }
     }
+
+    fn reset(&mut self) -> ShouldRender {
+        self.meter.reset_clip();
+        self.meter.reset_max_hold();
+        if let Some(Callback::Callback(fun)) = &self.props.on_reset {
+            fun(());
+        }
+        true
+    }
+
+    fn format_readout(&self) -> Html {
+        let meter = &self.meter;
+        // full scale gain reduction is still a finite amount of reduction
+        let floor = match meter.mode {
+            MeterMode::Level => meter.min,
+            MeterMode::GainReduction => f64::NEG_INFINITY,
+        };
+        let peak = format_readout(meter.peak, floor);
+        let rms = format_readout(meter.value, floor);
+        let max_hold = format_readout(meter.max_hold, floor);
+        html! {
+            <table class="meter-readout">
+                <tr>
+                    <td>{"Pk:"}</td> <td>{peak}</td>
+                </tr>
+                <tr>
+                    <td>{"RMS:"}</td> <td>{rms}</td>
+                </tr>
+                <tr>
+                    <td>{"Max:"}</td> <td class="max-hold">{max_hold}</td>
+                </tr>
+            </table>
+        }
+    }
 }
 
-impl Meter {
-    fn render(&mut self) {
-        self.needs_repaint = false;
-        if let Some(renderer) = self.renderer.as_ref() {
-            renderer.render_to_canvas(&self.props.meter);
+/// The meter to draw after the host passed new props. With a bus attached the host's meter only
+/// provides the configuration and the readings drained from the bus are kept.
+fn next_meter<MeterScale: Scale<f64> + Clone + PartialEq>(
+    meter: &MeterModel<MeterScale>,
+    props: &Props<MeterScale>,
+) -> MeterModel<MeterScale> {
+    if props.bus.is_some() {
+        meter.reconfigured(&props.meter)
+    } else {
+        props.meter.clone()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::scale::VerticalPosition;
+
+    fn props(bus: Option<MeterReceiver>) -> Props<LinearScale<f64>> {
+        Props {
+            id: "meter".to_owned(),
+            meter: LinearMeterModel::default(),
+            width: 20.0,
+            height: 200.0,
+            bar_width: 10.0,
+            layout: Layout::Vertical(VerticalPosition::Right),
+            inverted: false,
+            zones: default_zones(),
+            peak_style: PeakStyle::Line,
+            render_mode: RenderMode::Continuous,
+            show_clip_indicator: true,
+            show_scale: true,
+            show_readout: false,
+            bus,
+            on_reset: None,
         }
     }
+
+    #[test]
+    fn test_host_render_keeps_drained_state() {
+        let (mut sender, receiver) = meter_bus(8);
+        let props = props(Some(receiver.clone()));
+
+        sender.push(-6.0, 0.5);
+        let (value, peak) = receiver.drain(&MeterMode::Level).expect("nothing drained");
+        let drained = props.meter.update(value, peak);
+
+        let meter = next_meter(&drained, &props);
+
+        assert!(meter.clip.is_clipped());
+        assert!((meter.max_hold - 0.5).abs() < f64::EPSILON);
+        assert!((meter.value - -6.0).abs() < f64::EPSILON);
+    }
+
+    #[test]
+    fn test_host_driven_meter_follows_props() {
+        let props = props(None);
+        let previous = props.meter.update(-6.0, 0.5);
+
+        assert_eq!(props.meter, next_meter(&previous, &props));
+    }
 }
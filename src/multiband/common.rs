@@ -0,0 +1,332 @@
+use crate::compressor::*;
+use crate::eq::{
+    calc_high_pass_gain, calc_low_pass_gain, MAJOR_FREQUENCY_MARKERS, MAJOR_GAIN_MARKERS,
+    MINOR_FREQUENCY_MARKERS, MINOR_GAIN_MARKERS,
+};
+use crate::multiband::plotter;
+use crate::utils::*;
+use crate::*;
+
+pub const MIN_BANDS: usize = 2;
+pub const MAX_BANDS: usize = 5;
+/// Adjacent crossovers are kept at least this factor (a third of an octave) apart.
+pub const MIN_CROSSOVER_RATIO: f64 = 1.259_921_049_894_873_2;
+
+/// The slope of the Linkwitz-Riley crossover filters in dB/octave.
+#[derive(Debug, PartialEq, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum CrossoverSlope {
+    LR12,
+    LR24,
+    LR48,
+}
+
+impl CrossoverSlope {
+    pub fn slope(&self) -> Slope {
+        match self {
+            CrossoverSlope::LR12 => 12,
+            CrossoverSlope::LR24 => 24,
+            CrossoverSlope::LR48 => 48,
+        }
+    }
+
+    /// The gain in dB of the low pass half of a crossover at `frequency`. A Linkwitz-Riley
+    /// filter is two cascaded Butterworth filters of half its slope, so it is -6 dB at the
+    /// crossover frequency and low and high pass sum to unity.
+    pub fn low_pass_gain(&self, f: Frequency, frequency: Frequency) -> Gain {
+        2.0 * calc_low_pass_gain(f, frequency, self.slope() / 2)
+    }
+
+    /// The gain in dB of the high pass half of a crossover at `frequency`.
+    pub fn high_pass_gain(&self, f: Frequency, frequency: Frequency) -> Gain {
+        2.0 * calc_high_pass_gain(f, frequency, self.slope() / 2)
+    }
+}
+
+#[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct MultibandBand {
+    pub compressor: CompressorModel,
+    pub active: Active,
+}
+
+impl MultibandBand {
+    pub fn new(compressor: CompressorModel) -> MultibandBand {
+        MultibandBand {
+            compressor,
+            active: true,
+        }
+    }
+}
+
+/// Splits the signal into bands at ascending crossover frequencies and compresses each band on
+/// its own. There is always one band more than there are crossovers.
+#[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct MultibandModel {
+    pub crossovers: Vec<Frequency>,
+    pub slope: CrossoverSlope,
+    pub bands: Vec<MultibandBand>,
+    pub min_frequency: Frequency,
+    pub max_frequency: Frequency,
+    /// The lowest gain shown in the band plot and the gain reduction bars.
+    pub min_gain: Gain,
+}
+
+impl MultibandModel {
+    /// Creates a model with a default compressor for each band. The crossovers are sorted.
+    ///
+    /// # Panics
+    ///
+    /// If the crossovers don't make for `MIN_BANDS` to `MAX_BANDS` bands.
+    pub fn new(crossovers: Vec<Frequency>, slope: CrossoverSlope) -> MultibandModel {
+        assert!(
+            (MIN_BANDS - 1..MAX_BANDS).contains(&crossovers.len()),
+            "{} crossovers, expected {} to {}",
+            crossovers.len(),
+            MIN_BANDS - 1,
+            MAX_BANDS - 1
+        );
+        let mut crossovers = crossovers;
+        crossovers.sort_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
+        let bands = (0..=crossovers.len())
+            .map(|_| MultibandBand::new(CompressorModel::default()))
+            .collect();
+        MultibandModel {
+            crossovers,
+            slope,
+            bands,
+            min_frequency: 20.0,
+            max_frequency: 24_000.0,
+            min_gain: -24.0,
+        }
+    }
+
+    /// The lower and upper edge of a band, the plot's frequency range for the outer ones.
+    pub fn band_range(&self, index: usize) -> (Frequency, Frequency) {
+        let lower = if index == 0 {
+            self.min_frequency
+        } else {
+            self.crossovers[index - 1]
+        };
+        let upper = self
+            .crossovers
+            .get(index)
+            .copied()
+            .unwrap_or(self.max_frequency);
+        (lower, upper)
+    }
+
+    /// The gain in dB of a band's crossover filters at `f`.
+    pub fn band_gain(&self, index: usize, f: Frequency) -> Gain {
+        let high_pass = if index == 0 {
+            0.0
+        } else {
+            self.slope.high_pass_gain(f, self.crossovers[index - 1])
+        };
+        let low_pass = self
+            .crossovers
+            .get(index)
+            .map_or(0.0, |c| self.slope.low_pass_gain(f, *c));
+        high_pass + low_pass
+    }
+
+    /// The band containing `frequency`.
+    pub fn band_at(&self, frequency: Frequency) -> usize {
+        self.crossovers
+            .iter()
+            .take_while(|c| **c <= frequency)
+            .count()
+    }
+
+    pub fn update(&mut self, change: MultibandParameter) {
+        match change {
+            MultibandParameter::Crossover(index, frequency) => {
+                if index >= self.crossovers.len() {
+                    return;
+                }
+                let lower = if index == 0 {
+                    self.min_frequency
+                } else {
+                    self.crossovers[index - 1] * MIN_CROSSOVER_RATIO
+                };
+                let upper = self
+                    .crossovers
+                    .get(index + 1)
+                    .map_or(self.max_frequency, |c| c / MIN_CROSSOVER_RATIO);
+                if lower <= upper {
+                    self.crossovers[index] = frequency.clamp(lower, upper);
+                }
+            }
+            MultibandParameter::Slope(slope) => self.slope = slope,
+            MultibandParameter::Active(index, active) => {
+                if let Some(band) = self.bands.get_mut(index) {
+                    band.active = active;
+                }
+            }
+            MultibandParameter::Band(index, change) => {
+                if let Some(band) = self.bands.get_mut(index) {
+                    band.compressor.update(change);
+                }
+            }
+            MultibandParameter::Split(frequency) => self.split_band(frequency),
+            MultibandParameter::Merge(index) => self.remove_crossover(index),
+        }
+    }
+
+    /// Splits the band containing `frequency` in two at that frequency, copying its settings.
+    /// Frequencies closer than `MIN_CROSSOVER_RATIO` to the band's edges are ignored.
+    pub fn split_band(&mut self, frequency: Frequency) {
+        if self.bands.len() >= MAX_BANDS {
+            return;
+        }
+        let index = self.band_at(frequency);
+        let (lower, upper) = self.band_range(index);
+        if frequency < lower * MIN_CROSSOVER_RATIO || frequency > upper / MIN_CROSSOVER_RATIO {
+            return;
+        }
+        let band = self.bands[index].clone();
+        self.crossovers.insert(index, frequency);
+        self.bands.insert(index, band);
+    }
+
+    /// Removes a crossover, merging the bands on either side. The lower band's settings are kept.
+    pub fn remove_crossover(&mut self, index: usize) {
+        if self.bands.len() <= MIN_BANDS || index >= self.crossovers.len() {
+            return;
+        }
+        self.crossovers.remove(index);
+        self.bands.remove(index + 1);
+    }
+
+    pub fn x_to_frequency_converter(&self, width: f64) -> (PixelScale, FreqScale) {
+        let x_scale = PixelScale::new(0.0, width);
+        let freq_scale = FreqScale::new(self.min_frequency, self.max_frequency);
+        (x_scale, freq_scale)
+    }
+
+    pub fn y_to_gain_converter(&self, height: f64) -> (PixelScale, GainScale) {
+        let y_scale = PixelScale::inverted(0.0, height);
+        let gain_scale = GainScale::new(self.min_gain, 0.0);
+        (y_scale, gain_scale)
+    }
+
+    pub fn frequency_markers(&self) -> (Vec<Frequency>, Vec<Frequency>) {
+        let (min, max) = (self.min_frequency, self.max_frequency);
+        let major = filter_markers(&MAJOR_FREQUENCY_MARKERS, min, max, false);
+        let minor = filter_markers(&MINOR_FREQUENCY_MARKERS, min, max, false);
+        (major, minor)
+    }
+
+    pub fn gain_markers(&self) -> (Vec<Gain>, Vec<Gain>) {
+        let major = filter_markers(&MAJOR_GAIN_MARKERS, self.min_gain, 0.0, false);
+        let minor = filter_markers(&MINOR_GAIN_MARKERS, self.min_gain, 0.0, false);
+        (major, minor)
+    }
+
+    /// Plots the bands' filter curves, the crossovers and a gain reduction bar per band.
+    pub fn plot(&self, gain_reduction: &[Gain], width: f64, height: f64) -> MultibandGraph {
+        plotter::plot_multiband(self, gain_reduction, width, height)
+    }
+}
+
+impl Default for MultibandModel {
+    fn default() -> Self {
+        MultibandModel::new(vec![200.0, 2_000.0], CrossoverSlope::LR24)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum MultibandParameter {
+    Crossover(usize, Frequency),
+    Slope(CrossoverSlope),
+    Active(usize, Active),
+    Band(usize, CompressorParameter),
+    /// Adds a crossover at the given frequency.
+    Split(Frequency),
+    /// Removes the crossover with the given index.
+    Merge(usize),
+}
+
+pub struct MultibandGraph {
+    pub band_curves: Vec<(Vec<(X, Y)>, Active)>,
+    /// The horizontal extent of each band.
+    pub band_areas: Vec<(X, X)>,
+    pub crossovers: Vec<Line>,
+    /// A bar hanging from the top of each band, as deep as its gain reduction.
+    pub gain_reduction: Vec<Bounds>,
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_crossovers_sum_flat() {
+        let model = MultibandModel::new(vec![1_000.0, 100.0], CrossoverSlope::LR24);
+        assert_eq!(vec![100.0, 1_000.0], model.crossovers);
+        assert_eq!(3, model.bands.len());
+
+        for f in &[20.0, 100.0, 300.0, 1_000.0, 10_000.0] {
+            let sum: f64 = (0..3)
+                .map(|i| 10f64.powf(model.band_gain(i, *f) / 20.0))
+                .sum();
+            assert!((sum - 1.0).abs() < 0.01, "sum at {} Hz: {}", f, sum);
+        }
+        assert!((model.band_gain(1, 100.0) - -6.02).abs() < 0.1);
+    }
+
+    #[test]
+    fn test_crossover_limits() {
+        let mut model = MultibandModel::default();
+
+        model.update(MultibandParameter::Crossover(0, 5_000.0));
+        assert!((model.crossovers[0] - 2_000.0 / MIN_CROSSOVER_RATIO).abs() < 1e-9);
+        model.update(MultibandParameter::Crossover(1, 100_000.0));
+        assert!((model.crossovers[1] - model.max_frequency).abs() < f64::EPSILON);
+
+        model.update(MultibandParameter::Split(10_000.0));
+        assert_eq!(3, model.crossovers.len());
+        assert_eq!(2, model.band_at(10_000.0));
+        for _ in 0..3 {
+            model.update(MultibandParameter::Merge(0));
+        }
+        assert_eq!(MIN_BANDS, model.bands.len());
+    }
+
+    #[test]
+    fn test_split_keeps_crossovers_apart() {
+        let mut model = MultibandModel::default();
+
+        // at an existing crossover
+        model.update(MultibandParameter::Split(200.0));
+        // closer than a third of an octave to a crossover
+        model.update(MultibandParameter::Split(220.0));
+        model.update(MultibandParameter::Split(1_800.0));
+        // too close to the edges of the plot
+        model.update(MultibandParameter::Split(22.0));
+        model.update(MultibandParameter::Split(22_000.0));
+        model.update(MultibandParameter::Split(10.0));
+        assert_eq!(vec![200.0, 2_000.0], model.crossovers);
+        assert_eq!(3, model.bands.len());
+
+        model.update(MultibandParameter::Split(200.0 * MIN_CROSSOVER_RATIO));
+        assert_eq!(3, model.crossovers.len());
+        assert_eq!(4, model.bands.len());
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_too_few_crossovers() {
+        MultibandModel::new(vec![], CrossoverSlope::LR24);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_too_many_crossovers() {
+        MultibandModel::new(
+            vec![100.0, 200.0, 400.0, 800.0, 1_600.0],
+            CrossoverSlope::LR24,
+        );
+    }
+}
@@ -0,0 +1,127 @@
+use crate::js_utils::*;
+use crate::multiband::common::*;
+use crate::*;
+use web_sys::CanvasRenderingContext2d;
+use web_sys::HtmlCanvasElement;
+
+const CROSSOVER_HANDLE_WIDTH: f64 = 6.0;
+const CROSSOVER_HANDLE_HEIGHT: f64 = 16.0;
+
+pub struct CanvasMultibandRenderer {
+    pub context: CanvasRenderingContext2d,
+    pub style: Style,
+    pub bounds: Bounds,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Style {
+    band_fill: Option<String>,
+    band_alt_fill: Option<String>,
+    selected_band_fill: Option<String>,
+    curve_stroke: Option<String>,
+    inactive_curve_stroke: Option<String>,
+    crossover_stroke: Option<String>,
+    handle_fill: Option<String>,
+    gain_reduction_fill: Option<String>,
+}
+
+impl CanvasMultibandRenderer {
+    pub fn new(canvas: HtmlCanvasElement) -> Option<CanvasMultibandRenderer> {
+        let context = get_context_2d(&canvas)?;
+        let style = get_styles(&canvas);
+
+        let bounds = canvas.get_bounding_client_rect().into();
+
+        let band_fill = get_style("--band-fill", &style, None);
+        let band_alt_fill = get_style("--band-alt-fill", &style, Some("#fff1"));
+        let selected_band_fill = get_style("--selected-band-fill", &style, Some("#88f3"));
+        let curve_stroke = get_style("--curve-stroke", &style, Some("#88f"));
+        let inactive_curve_stroke = get_style("--inactive-curve-stroke", &style, Some("#555"));
+        let crossover_stroke = get_style("--crossover-stroke", &style, Some("#777"));
+        let handle_fill = get_style("--handle-fill", &style, Some("#88f6"));
+        let gain_reduction_fill = get_style("--gain-reduction-fill", &style, Some("orange"));
+
+        let style = Style {
+            band_fill,
+            band_alt_fill,
+            selected_band_fill,
+            curve_stroke,
+            inactive_curve_stroke,
+            crossover_stroke,
+            handle_fill,
+            gain_reduction_fill,
+        };
+
+        Some(CanvasMultibandRenderer {
+            context,
+            style,
+            bounds,
+        })
+    }
+
+    pub fn render_to_canvas(&self, graph: &MultibandGraph, selected: Option<usize>) {
+        let width = self.bounds.width;
+        let height = self.bounds.height;
+
+        let context = &self.context;
+
+        context.clear_rect(0.0, 0.0, width, height);
+
+        for (i, (from, to)) in graph.band_areas.iter().enumerate() {
+            let fill = if selected == Some(i) {
+                self.style.selected_band_fill.as_ref()
+            } else if i % 2 == 1 {
+                self.style.band_alt_fill.as_ref()
+            } else {
+                self.style.band_fill.as_ref()
+            };
+            if fill.is_some() {
+                set_fill(context, fill);
+                context.fill_rect(*from, 0.0, to - from, height);
+            }
+        }
+
+        set_fill(context, self.style.gain_reduction_fill.as_ref());
+        for bar in &graph.gain_reduction {
+            // leave some space between the bar and the crossover lines
+            let inset = (bar.width / 4.0).min(8.0);
+            context.fill_rect(bar.x + inset, bar.y, bar.width - 2.0 * inset, bar.height);
+        }
+
+        for (curve, active) in &graph.band_curves {
+            let stroke = if *active {
+                self.style.curve_stroke.as_ref()
+            } else {
+                self.style.inactive_curve_stroke.as_ref()
+            };
+            let mut points = curve.iter();
+            if let Some((x, y)) = points.next() {
+                context.begin_path();
+                set_stroke(context, stroke);
+                context.move_to(*x, *y);
+                for (x, y) in points {
+                    context.line_to(*x, *y);
+                }
+                context.stroke();
+            }
+        }
+
+        context.begin_path();
+        set_stroke(context, self.style.crossover_stroke.as_ref());
+        for line in &graph.crossovers {
+            context.move_to(line.x_start + 0.5, line.y_start);
+            context.line_to(line.x_end + 0.5, line.y_end);
+        }
+        context.stroke();
+
+        set_fill(context, self.style.handle_fill.as_ref());
+        for line in &graph.crossovers {
+            context.fill_rect(
+                line.x_start + 0.5 - CROSSOVER_HANDLE_WIDTH / 2.0,
+                (height - CROSSOVER_HANDLE_HEIGHT) / 2.0,
+                CROSSOVER_HANDLE_WIDTH,
+                CROSSOVER_HANDLE_HEIGHT,
+            );
+        }
+    }
+}
@@ -0,0 +1,15 @@
+mod common;
+mod plotter;
+
+pub use crate::multiband::common::*;
+pub use crate::multiband::plotter::*;
+
+#[cfg(feature = "js")]
+mod js;
+#[cfg(feature = "js")]
+pub use crate::multiband::js::*;
+
+#[cfg(feature = "yew-components")]
+mod yew_component;
+#[cfg(feature = "yew-components")]
+pub use crate::multiband::yew_component::*;
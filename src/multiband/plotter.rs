@@ -0,0 +1,95 @@
+use crate::multiband::common::*;
+use crate::*;
+use scales::prelude::*;
+
+pub fn plot_multiband(
+    model: &MultibandModel,
+    gain_reduction: &[Gain],
+    width: f64,
+    height: f64,
+) -> MultibandGraph {
+    let x_conv = model.x_to_frequency_converter(width);
+    let y_conv = model.y_to_gain_converter(height);
+
+    let band_curves = model
+        .bands
+        .iter()
+        .enumerate()
+        .map(|(i, band)| {
+            let curve = (0..=width as usize)
+                .map(|x| {
+                    let x = x as f64;
+                    let gain = model.band_gain(i, x_conv.convert(x));
+                    (x, y_conv.convert_back(gain.max(model.min_gain)))
+                })
+                .collect();
+            (curve, band.active)
+        })
+        .collect();
+
+    let band_areas: Vec<(X, X)> = (0..model.bands.len())
+        .map(|i| {
+            let (lower, upper) = model.band_range(i);
+            (x_conv.convert_back(lower), x_conv.convert_back(upper))
+        })
+        .collect();
+
+    let crossovers = model
+        .crossovers
+        .iter()
+        .map(|c| {
+            let x = x_conv.convert_back(*c).floor();
+            Line {
+                x_start: x,
+                y_start: 0.0,
+                x_end: x,
+                y_end: height,
+                value: *c,
+            }
+        })
+        .collect();
+
+    let gain_reduction = band_areas
+        .iter()
+        .zip(gain_reduction)
+        .map(|((from, to), gr)| {
+            let gr = gr.max(model.min_gain).min(0.0);
+            Bounds {
+                x: *from,
+                y: 0.0,
+                width: to - from,
+                height: y_conv.convert_back(gr),
+            }
+        })
+        .collect();
+
+    MultibandGraph {
+        band_curves,
+        band_areas,
+        crossovers,
+        gain_reduction,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_plot_multiband() {
+        let mut model = MultibandModel::new(vec![200.0, 2_000.0], CrossoverSlope::LR24);
+        model.min_frequency = 20.0;
+        model.max_frequency = 20_000.0;
+
+        let graph = model.plot(&[-6.0, 0.0, -30.0], 300.0, 48.0);
+
+        assert_eq!(3, graph.band_curves.len());
+        assert_eq!(301, graph.band_curves[1].0.len());
+        assert!((graph.crossovers[0].x_start - 100.0).abs() < f64::EPSILON);
+        assert!((graph.band_areas[2].0 - 200.0).abs() < 1e-9);
+        assert!((graph.band_areas[2].1 - 300.0).abs() < 1e-9);
+        assert!((graph.gain_reduction[0].height - 12.0).abs() < 1e-9);
+        assert!(graph.gain_reduction[1].height.abs() < f64::EPSILON);
+        assert!((graph.gain_reduction[2].height - 48.0).abs() < 1e-9);
+    }
+}
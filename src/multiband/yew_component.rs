@@ -0,0 +1,351 @@
+use crate::compressor::*;
+use crate::js_utils::*;
+use crate::multiband::*;
+use crate::scale;
+use crate::scale::ScaleModel;
+use crate::*;
+use derivative::*;
+use scales::prelude::*;
+use wasm_bindgen::prelude::*;
+use web_sys::HtmlCanvasElement;
+use yew::prelude::*;
+
+/// Crossovers further away from the pointer than this are not grabbed.
+const GRAB_DISTANCE: f64 = 8.0;
+
+pub struct Multiband {
+    props: Props,
+    ext_props: Option<Props>,
+    link: ComponentLink<Self>,
+    canvas: NodeRef,
+    active_crossover: Option<usize>,
+    last_touch: Option<X>,
+    renderer: Option<CanvasMultibandRenderer>,
+    render_callback: Closure<dyn FnMut()>,
+    needs_repaint: bool,
+}
+
+#[derive(Derivative, Properties)]
+#[derivative(Debug, Clone, PartialEq)]
+pub struct Props {
+    pub id: String,
+    pub multiband: MultibandModel,
+    /// The current gain reduction of each band.
+    pub gain_reduction: Vec<Gain>,
+    pub selected: Option<usize>,
+    pub width: f64,
+    pub height: f64,
+    #[derivative(PartialEq = "ignore")]
+    pub on_input: Option<Callback<MultibandParameter>>,
+    #[derivative(PartialEq = "ignore")]
+    pub on_select: Option<Callback<usize>>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Msg {
+    MouseDown(MouseEvent),
+    MouseUp(MouseEvent),
+    MouseMove(MouseEvent),
+    TouchStart(TouchEvent),
+    TouchEnd(TouchEvent),
+    TouchMove(TouchEvent),
+    RightClick(MouseEvent),
+    DoubleClick(MouseEvent),
+    Wheel(WheelEvent),
+    Render,
+}
+
+impl Component for Multiband {
+    type Message = Msg;
+
+    type Properties = Props;
+
+    fn create(props: Self::Properties, link: ComponentLink<Self>) -> Self {
+        let cb_link = link.clone();
+        let render_callback =
+            Closure::wrap(Box::new(move || cb_link.send_message(Msg::Render)) as Box<dyn FnMut()>);
+
+        let cb_link = link.clone();
+        let mouse_moved =
+            Closure::wrap(Box::new(move |e| cb_link.send_message(Msg::MouseMove(e)))
+                as Box<dyn Fn(MouseEvent)>);
+
+        let cb_link = link.clone();
+        let mouse_up = Closure::wrap(
+            Box::new(move |e| cb_link.send_message(Msg::MouseUp(e))) as Box<dyn Fn(MouseEvent)>
+        );
+
+        register_global_listener("mousemove", &mouse_moved);
+        register_global_listener("mouseup", &mouse_up);
+
+        mouse_moved.forget();
+        mouse_up.forget();
+
+        Multiband {
+            props,
+            ext_props: None,
+            link,
+            canvas: NodeRef::default(),
+            active_crossover: None,
+            last_touch: None,
+            renderer: None,
+            render_callback,
+            needs_repaint: false,
+        }
+    }
+
+    fn update(&mut self, msg: Self::Message) -> ShouldRender {
+        match msg {
+            Msg::MouseDown(e) => self.handle_mouse_down(e),
+            Msg::MouseUp(_) => self.handle_up(),
+            Msg::MouseMove(e) => self.handle_mouse_move(e),
+            Msg::TouchStart(e) => self.handle_touch_start(e),
+            Msg::TouchEnd(_) => self.handle_up(),
+            Msg::TouchMove(e) => self.handle_touch_move(e),
+            Msg::RightClick(e) => self.handle_right_click(e),
+            Msg::DoubleClick(e) => self.handle_double_click(e),
+            Msg::Wheel(e) => self.handle_wheel(e),
+            Msg::Render => self.render(),
+        }
+        false
+    }
+
+    fn change(&mut self, props: Self::Properties) -> ShouldRender {
+        let resized = props.width != self.props.width || props.height != self.props.height;
+        // don't accept external changes while a crossover is being dragged
+        if self.active_crossover.is_some() {
+            self.props.gain_reduction = props.gain_reduction.clone();
+            self.ext_props = Some(props);
+            self.schedule_repaint();
+        } else if props != self.props {
+            self.props = props;
+            self.schedule_repaint();
+        }
+        if resized {
+            self.renderer = None;
+        }
+        resized
+    }
+
+    fn view(&self) -> Html {
+        let mouse_down_callback = self.link.callback(Msg::MouseDown);
+        let touch_start_callback = self.link.callback(Msg::TouchStart);
+        let touch_end_callback = self.link.callback(Msg::TouchEnd);
+        let touch_move_callback = self.link.callback(Msg::TouchMove);
+        let right_click_callback = self.link.callback(Msg::RightClick);
+        let double_click_callback = self.link.callback(Msg::DoubleClick);
+        let wheel_callback = self.link.callback(Msg::Wheel);
+
+        let width = self.props.width;
+        let height = self.props.height;
+
+        let (freq_scale, gain_scale) = scales(&self.props.multiband);
+        let pixel_scale_x = PixelScale::new(0.0, width);
+        let pixel_scale_y = PixelScale::inverted(0.0, height);
+
+        html! {
+            <div class="multiband">
+                <svg class="scale" width={width} height={height}>
+                    <scale::Scale<FreqScale> scale={freq_scale} pixel_scale={pixel_scale_x} label_format={Some(LabelFormat::FrequencyShort(true))} width={height} />
+                    <scale::Scale<GainScale> scale={gain_scale} pixel_scale={pixel_scale_y} label_format={Some(LabelFormat::GainShort(true))} width={width} />
+                </svg>
+                <canvas
+                    id={self.props.id.clone()}
+                    ref=self.canvas.clone()
+                    width={width}
+                    height={height}
+                    onmousedown={mouse_down_callback}
+                    ontouchstart={touch_start_callback}
+                    ontouchend={touch_end_callback}
+                    ontouchmove={touch_move_callback}
+                    oncontextmenu={right_click_callback}
+                    ondblclick={double_click_callback}
+                    onwheel={wheel_callback}
+                />
+            </div>
+        }
+    }
+
+    fn rendered(&mut self, _first_render: bool) {
+        if self.renderer.is_none() {
+            self.renderer = self
+                .canvas
+                .cast::<HtmlCanvasElement>()
+                .and_then(CanvasMultibandRenderer::new);
+        }
+
+        self.schedule_repaint();
+    }
+}
+
+impl Multiband {
+    fn render(&mut self) {
+        self.needs_repaint = false;
+        if let Some(renderer) = self.renderer.as_ref() {
+            let props = &self.props;
+            let graph = props
+                .multiband
+                .plot(&props.gain_reduction, props.width, props.height);
+            renderer.render_to_canvas(&graph, props.selected);
+        }
+    }
+
+    fn schedule_repaint(&mut self) {
+        if !self.needs_repaint {
+            self.needs_repaint = true;
+            request_animation_frame(&self.render_callback);
+        }
+    }
+
+    fn handle_mouse_down(&mut self, e: MouseEvent) {
+        if e.button() != 0 {
+            return;
+        }
+        self.handle_down(e.offset_x() as f64);
+    }
+
+    fn handle_mouse_move(&mut self, e: MouseEvent) {
+        if let Some(index) = self.active_crossover {
+            self.handle_move(e.movement_x() as f64, index);
+        }
+    }
+
+    fn handle_touch_start(&mut self, e: TouchEvent) {
+        if e.target_touches().length() != 1 {
+            return;
+        }
+        if let (Some(touch), Some(canvas)) = (
+            e.changed_touches().get(0),
+            self.canvas.cast::<HtmlCanvasElement>(),
+        ) {
+            let rect = canvas.get_bounding_client_rect();
+            let x = touch.client_x() as f64;
+            self.last_touch = Some(x);
+            self.handle_down(x - rect.x());
+        }
+    }
+
+    fn handle_touch_move(&mut self, e: TouchEvent) {
+        if e.target_touches().length() != 1 {
+            return;
+        }
+        if let (Some(index), Some(touch)) = (self.active_crossover, e.changed_touches().get(0)) {
+            let x = touch.client_x() as f64;
+            if let Some(last_x) = self.last_touch {
+                self.handle_move(x - last_x, index);
+            }
+            self.last_touch = Some(x);
+        }
+        e.prevent_default();
+    }
+
+    fn handle_right_click(&mut self, e: MouseEvent) {
+        let band = self.band_at(e.offset_x() as f64);
+        let active = self.props.multiband.bands[band].active;
+        self.apply(MultibandParameter::Active(band, !active));
+        e.prevent_default();
+    }
+
+    /// Double clicking a crossover removes it, double clicking anywhere else adds one.
+    fn handle_double_click(&mut self, e: MouseEvent) {
+        let x = e.offset_x() as f64;
+        let change = match self.crossover_at(x) {
+            Some(index) => MultibandParameter::Merge(index),
+            None => {
+                let x_conv = self
+                    .props
+                    .multiband
+                    .x_to_frequency_converter(self.props.width);
+                MultibandParameter::Split(x_conv.convert(x))
+            }
+        };
+        self.apply(change);
+    }
+
+    /// Scrolling over a band adjusts its threshold.
+    fn handle_wheel(&mut self, e: WheelEvent) {
+        let band = self.band_at(e.offset_x() as f64);
+        let threshold = self.props.multiband.bands[band].compressor.threshold;
+        let change = CompressorParameter::Threshold(threshold - e.delta_y().signum());
+        self.apply(MultibandParameter::Band(band, change));
+        e.prevent_default();
+    }
+
+    fn handle_down(&mut self, x: X) {
+        self.active_crossover = self.crossover_at(x);
+        if self.active_crossover.is_none() {
+            let band = self.band_at(x);
+            if let Some(Callback::Callback(fun)) = &self.props.on_select {
+                fun(band);
+            }
+        }
+    }
+
+    fn handle_up(&mut self) {
+        self.active_crossover = None;
+        self.last_touch = None;
+        if let Some(props) = self.ext_props.take() {
+            self.props = props;
+            self.schedule_repaint();
+        }
+    }
+
+    fn handle_move(&mut self, d_x: X, index: usize) {
+        let multiband = &self.props.multiband;
+        let x_conv = multiband.x_to_frequency_converter(self.props.width);
+        let frequency = multiband.crossovers[index];
+        let new_frequency = x_conv.add_external_clamped(d_x, frequency);
+        if (new_frequency - frequency).abs() > f64::EPSILON {
+            self.apply(MultibandParameter::Crossover(index, new_frequency));
+        }
+    }
+
+    fn apply(&mut self, change: MultibandParameter) {
+        self.props.multiband.update(change.clone());
+        self.schedule_repaint();
+        if let Some(Callback::Callback(fun)) = &self.props.on_input {
+            fun(change);
+        }
+    }
+
+    fn crossover_at(&self, x: X) -> Option<usize> {
+        let multiband = &self.props.multiband;
+        let x_conv = multiband.x_to_frequency_converter(self.props.width);
+        multiband
+            .crossovers
+            .iter()
+            .map(|c| (x - x_conv.convert_back(*c)).abs())
+            .enumerate()
+            .filter(|(_, distance)| *distance <= GRAB_DISTANCE)
+            .min_by(|a, b| a.1.partial_cmp(&b.1).unwrap_or(std::cmp::Ordering::Equal))
+            .map(|(index, _)| index)
+    }
+
+    fn band_at(&self, x: X) -> usize {
+        let multiband = &self.props.multiband;
+        let x_conv = multiband.x_to_frequency_converter(self.props.width);
+        multiband.band_at(x_conv.convert(x))
+    }
+}
+
+fn scales(multiband: &MultibandModel) -> (ScaleModel<FreqScale>, ScaleModel<GainScale>) {
+    let (major_scale_markers, minor_scale_markers) = multiband.frequency_markers();
+    let freq_scale = ScaleModel::new(
+        FreqScale::new(multiband.min_frequency, multiband.max_frequency),
+        scale::Layout::Horizontal(scale::HorizontalPosition::Top),
+        None,
+        major_scale_markers,
+        minor_scale_markers,
+    );
+
+    let (major_scale_markers, minor_scale_markers) = multiband.gain_markers();
+    let gain_scale = ScaleModel::new(
+        GainScale::new(multiband.min_gain, 0.0),
+        scale::Layout::Vertical(scale::VerticalPosition::Left),
+        None,
+        major_scale_markers,
+        minor_scale_markers,
+    );
+
+    (freq_scale, gain_scale)
+}
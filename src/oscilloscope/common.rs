@@ -0,0 +1,326 @@
+use crate::oscilloscope::plotter;
+use crate::*;
+
+/// Timebase and vertical scale steps follow the usual 1-2-5 sequence.
+const STEPS: [f64; 3] = [1.0, 2.0, 5.0];
+/// The smallest timebase and vertical scale, also used in place of invalid values.
+const MIN_STEP: f64 = 1e-6;
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum Edge {
+    Rising,
+    Falling,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum TriggerMode {
+    /// Starts a new sweep as soon as the previous one is complete.
+    FreeRun,
+    /// Starts a new sweep when the trigger channel crosses the trigger level.
+    Edge(Edge),
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct Trigger {
+    pub mode: TriggerMode,
+    pub level: Sample,
+    /// Samples to wait after a sweep before the trigger is armed again.
+    pub holdoff: usize,
+    /// The index of the trace the trigger listens to.
+    pub channel: usize,
+}
+
+impl Default for Trigger {
+    fn default() -> Self {
+        Trigger {
+            mode: TriggerMode::Edge(Edge::Rising),
+            level: 0.0,
+            holdoff: 0,
+            channel: 0,
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Clone)]
+enum State {
+    Armed { holdoff: usize },
+    Capturing,
+}
+
+/// A scope with one trace per input channel. Incoming samples are captured into sweeps starting
+/// at the trigger point; the last complete sweep is what gets displayed.
+#[derive(Debug, PartialEq, Clone)]
+pub struct OscilloscopeModel {
+    pub sample_rate: f64,
+    /// Time per horizontal division.
+    pub timebase: Seconds,
+    pub horizontal_divisions: usize,
+    /// Amplitude per vertical division.
+    pub vertical_scale: f64,
+    pub vertical_divisions: usize,
+    pub trigger: Trigger,
+    /// The last complete sweep of each channel.
+    pub traces: Vec<Vec<Sample>>,
+    capture: Vec<Vec<Sample>>,
+    previous: Option<Sample>,
+    state: State,
+}
+
+impl OscilloscopeModel {
+    pub fn new(channels: usize, sample_rate: f64) -> OscilloscopeModel {
+        OscilloscopeModel {
+            sample_rate,
+            timebase: 0.001,
+            horizontal_divisions: 10,
+            vertical_scale: 0.25,
+            vertical_divisions: 8,
+            trigger: Trigger::default(),
+            traces: vec![vec![]; channels],
+            capture: vec![vec![]; channels],
+            previous: None,
+            state: State::Armed { holdoff: 0 },
+        }
+    }
+
+    pub fn channels(&self) -> usize {
+        self.traces.len()
+    }
+
+    /// The number of samples per sweep.
+    pub fn sweep_length(&self) -> usize {
+        let duration = self.timebase * self.horizontal_divisions as f64;
+        ((duration * self.sample_rate).round() as usize).max(2)
+    }
+
+    /// Processes one block per channel. Returns `true` if at least one sweep was completed.
+    /// Channels missing from the block are captured as silence.
+    pub fn push_block(&mut self, block: &[&[Sample]]) -> bool {
+        let len = block.iter().map(|channel| channel.len()).min().unwrap_or(0);
+        let mut completed = false;
+        let mut frame = vec![0.0; block.len()];
+        for i in 0..len {
+            for (sample, channel) in frame.iter_mut().zip(block.iter()) {
+                *sample = channel[i];
+            }
+            completed |= self.push_frame(&frame);
+        }
+        completed
+    }
+
+    /// Like `push_block` for frames of interleaved samples.
+    pub fn push_interleaved(&mut self, block: &[Sample]) -> bool {
+        let channels = self.channels();
+        if channels == 0 {
+            return false;
+        }
+        let mut completed = false;
+        for frame in block.chunks_exact(channels) {
+            completed |= self.push_frame(frame);
+        }
+        completed
+    }
+
+    /// Discards the current capture and re-arms the trigger.
+    pub fn rearm(&mut self) {
+        self.capture.iter_mut().for_each(Vec::clear);
+        self.previous = None;
+        self.state = State::Armed { holdoff: 0 };
+    }
+
+    /// Moves the timebase by `steps` along the 1-2-5 sequence.
+    pub fn step_timebase(&mut self, steps: i32) {
+        self.timebase = step(self.timebase, steps);
+        self.rearm();
+    }
+
+    /// Moves the vertical scale by `steps` along the 1-2-5 sequence.
+    pub fn step_vertical_scale(&mut self, steps: i32) {
+        self.vertical_scale = step(self.vertical_scale, steps);
+    }
+
+    pub fn plot(&self, width: f64, height: f64) -> OscilloscopeGraph {
+        plotter::plot_oscilloscope(self, width, height)
+    }
+
+    fn push_frame(&mut self, frame: &[Sample]) -> bool {
+        let current = frame.get(self.trigger.channel).copied().unwrap_or(0.0);
+        let previous = self.previous.replace(current);
+
+        if let State::Armed { holdoff } = self.state {
+            if holdoff > 0 {
+                self.state = State::Armed {
+                    holdoff: holdoff - 1,
+                };
+                return false;
+            }
+            let triggered = match &self.trigger.mode {
+                TriggerMode::FreeRun => true,
+                TriggerMode::Edge(edge) => match previous {
+                    Some(previous) => crosses(edge, self.trigger.level, previous, current),
+                    None => false,
+                },
+            };
+            if !triggered {
+                return false;
+            }
+            self.state = State::Capturing;
+        }
+
+        for (channel, capture) in self.capture.iter_mut().enumerate() {
+            capture.push(frame.get(channel).copied().unwrap_or(0.0));
+        }
+
+        let sweep_length = self.sweep_length();
+        if self.capture.iter().all(|c| c.len() >= sweep_length) {
+            for (trace, capture) in self.traces.iter_mut().zip(self.capture.iter_mut()) {
+                std::mem::swap(trace, capture);
+                capture.clear();
+            }
+            self.state = State::Armed {
+                holdoff: self.trigger.holdoff,
+            };
+            true
+        } else {
+            false
+        }
+    }
+}
+
+impl Default for OscilloscopeModel {
+    fn default() -> Self {
+        OscilloscopeModel::new(1, 48_000.0)
+    }
+}
+
+pub struct OscilloscopeGraph {
+    pub grid: Vec<Line>,
+    /// The trigger level, unless free running.
+    pub trigger_level: Option<Line>,
+    pub traces: Vec<Vec<(X, Y)>>,
+}
+
+/// Reduces samples to the minimum and maximum of each of `columns` equally wide columns.
+pub fn decimate(samples: &[Sample], columns: usize) -> Vec<(Sample, Sample)> {
+    if samples.is_empty() || columns == 0 {
+        return vec![];
+    }
+    (0..columns)
+        .filter_map(|column| {
+            let start = column * samples.len() / columns;
+            let end = ((column + 1) * samples.len() / columns).max(start + 1);
+            let slice = samples.get(start..end.min(samples.len()))?;
+            let min = slice.iter().fold(Sample::INFINITY, |a, b| a.min(*b));
+            let max = slice.iter().fold(Sample::NEG_INFINITY, |a, b| a.max(*b));
+            Some((min, max))
+        })
+        .collect()
+}
+
+fn crosses(edge: &Edge, level: Sample, previous: Sample, current: Sample) -> bool {
+    match edge {
+        Edge::Rising => previous < level && current >= level,
+        Edge::Falling => previous > level && current <= level,
+    }
+}
+
+fn step(value: f64, steps: i32) -> f64 {
+    if !value.is_finite() || value <= 0.0 {
+        return MIN_STEP;
+    }
+    let exponent = value.log10().floor();
+    let mantissa = value / 10f64.powf(exponent);
+    let index = STEPS
+        .iter()
+        .rposition(|s| *s <= mantissa * 1.0001)
+        .unwrap_or(0) as i32;
+    let position = exponent as i32 * STEPS.len() as i32 + index + steps;
+    let exponent = position.div_euclid(STEPS.len() as i32);
+    let index = position.rem_euclid(STEPS.len() as i32) as usize;
+    (STEPS[index] * 10f64.powi(exponent)).max(MIN_STEP)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn ramp(len: usize, period: usize) -> Vec<Sample> {
+        (0..len)
+            .map(|i| (i % period) as Sample / period as Sample - 0.5)
+            .collect()
+    }
+
+    #[test]
+    fn test_rising_edge_trigger() {
+        let mut scope = OscilloscopeModel::new(1, 1_000.0);
+        scope.timebase = 0.001;
+        scope.trigger.level = 0.25;
+        let signal = ramp(100, 20);
+
+        assert!(scope.push_block(&[&signal]));
+
+        let trace = &scope.traces[0];
+        assert_eq!(10, trace.len());
+        assert!(trace[0] >= 0.25);
+        assert!(trace[0] - 0.25 < 0.05);
+    }
+
+    #[test]
+    fn test_holdoff_and_free_run() {
+        let signal = ramp(200, 20);
+
+        let mut triggered = OscilloscopeModel::new(1, 1_000.0);
+        triggered.timebase = 0.0005;
+        triggered.trigger.mode = TriggerMode::Edge(Edge::Falling);
+        triggered.trigger.level = -0.25;
+        let mut sweeps = 0;
+        for chunk in signal.chunks(20) {
+            if triggered.push_block(&[chunk]) {
+                sweeps += 1;
+            }
+        }
+        // the ramp only falls once per period
+        assert_eq!(9, sweeps);
+
+        triggered.trigger.holdoff = 25;
+        triggered.rearm();
+        assert!(triggered.push_block(&[&signal[..30]]));
+        assert!(!triggered.push_block(&[&signal[30..60]]));
+
+        let mut free = OscilloscopeModel::new(2, 1_000.0);
+        free.timebase = 0.001;
+        free.trigger.mode = TriggerMode::FreeRun;
+        let interleaved: Vec<Sample> = signal[..20].iter().flat_map(|s| vec![*s, -*s]).collect();
+        assert!(free.push_interleaved(&interleaved));
+        assert_eq!(signal[10..20].to_vec(), free.traces[0]);
+        assert!((free.traces[1][0] + signal[10]).abs() < f32::EPSILON);
+    }
+
+    #[test]
+    fn test_missing_channels_are_silent() {
+        let signal = ramp(30, 20);
+        let mut scope = OscilloscopeModel::new(2, 1_000.0);
+        scope.trigger.mode = TriggerMode::FreeRun;
+
+        assert!(scope.push_block(&[&signal]));
+        assert_eq!(signal[..10].to_vec(), scope.traces[0]);
+        assert_eq!(vec![0.0; 10], scope.traces[1]);
+        assert!(scope.capture.iter().all(|c| c.len() < scope.sweep_length()));
+    }
+
+    #[test]
+    fn test_decimate_and_step() {
+        let samples = [0.0, 1.0, -1.0, 0.5, 0.25, 0.0];
+        assert_eq!(
+            vec![(0.0, 1.0), (-1.0, 0.5), (0.0, 0.25)],
+            decimate(&samples, 3)
+        );
+
+        assert!((step(0.001, 1) - 0.002).abs() < 1e-12);
+        assert!((step(0.005, 1) - 0.01).abs() < 1e-12);
+        assert!((step(0.5, -2) - 0.1).abs() < 1e-12);
+        assert!((step(2e-6, -3) - MIN_STEP).abs() < f64::EPSILON);
+        for invalid in &[0.0, -1.0, f64::NAN, f64::INFINITY] {
+            assert!((step(*invalid, 1) - MIN_STEP).abs() < f64::EPSILON);
+        }
+    }
+}
@@ -0,0 +1,103 @@
+use crate::js_utils::*;
+use crate::oscilloscope::common::*;
+use crate::*;
+use web_sys::CanvasRenderingContext2d;
+use web_sys::HtmlCanvasElement;
+
+pub struct CanvasOscilloscopeRenderer {
+    pub context: CanvasRenderingContext2d,
+    pub style: Style,
+    pub bounds: Bounds,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Style {
+    background_fill: Option<String>,
+    grid_stroke: Option<String>,
+    trigger_stroke: Option<String>,
+    trace_stroke: Option<String>,
+    trace_strokes: Vec<Option<String>>,
+}
+
+impl CanvasOscilloscopeRenderer {
+    pub fn new(canvas: HtmlCanvasElement) -> Option<CanvasOscilloscopeRenderer> {
+        let context = get_context_2d(&canvas)?;
+        let style = get_styles(&canvas);
+
+        let bounds = canvas.get_bounding_client_rect().into();
+
+        let background_fill = get_style("--background-fill", &style, Some("black"));
+        let grid_stroke = get_style("--grid-stroke", &style, Some("#333"));
+        let trigger_stroke = get_style("--trigger-stroke", &style, Some("#a60"));
+        let trace_stroke = get_style("--trace-stroke", &style, Some("#8fd7e0"));
+        let trace_strokes = (0..8)
+            .map(|i| {
+                let style_name = format!("--trace-{}-stroke", (i + 1));
+                get_style(style_name, &style, None)
+            })
+            .collect();
+
+        let style = Style {
+            background_fill,
+            grid_stroke,
+            trigger_stroke,
+            trace_stroke,
+            trace_strokes,
+        };
+
+        Some(CanvasOscilloscopeRenderer {
+            context,
+            style,
+            bounds,
+        })
+    }
+
+    pub fn render_to_canvas(&self, oscilloscope: &OscilloscopeModel) {
+        let width = self.bounds.width;
+        let height = self.bounds.height;
+
+        let context = &self.context;
+
+        let graph = oscilloscope.plot(width, height);
+
+        context.clear_rect(0.0, 0.0, width, height);
+
+        set_fill(context, self.style.background_fill.as_ref());
+        context.fill_rect(0.0, 0.0, width, height);
+
+        context.begin_path();
+        set_stroke(context, self.style.grid_stroke.as_ref());
+        for line in &graph.grid {
+            context.move_to(line.x_start + 0.5, line.y_start + 0.5);
+            context.line_to(line.x_end + 0.5, line.y_end + 0.5);
+        }
+        context.stroke();
+
+        if let Some(line) = &graph.trigger_level {
+            context.begin_path();
+            set_stroke(context, self.style.trigger_stroke.as_ref());
+            context.move_to(line.x_start, line.y_start + 0.5);
+            context.line_to(line.x_end, line.y_end + 0.5);
+            context.stroke();
+        }
+
+        for (i, trace) in graph.traces.iter().enumerate() {
+            let stroke = self
+                .style
+                .trace_strokes
+                .get(i)
+                .and_then(|s| s.as_ref())
+                .or(self.style.trace_stroke.as_ref());
+            let mut points = trace.iter();
+            if let Some((x, y)) = points.next() {
+                context.begin_path();
+                set_stroke(context, stroke);
+                context.move_to(*x, *y);
+                for (x, y) in points {
+                    context.line_to(*x, *y);
+                }
+                context.stroke();
+            }
+        }
+    }
+}
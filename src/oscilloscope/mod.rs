@@ -0,0 +1,15 @@
+mod common;
+mod plotter;
+
+pub use crate::oscilloscope::common::*;
+pub use crate::oscilloscope::plotter::*;
+
+#[cfg(feature = "js")]
+mod js;
+#[cfg(feature = "js")]
+pub use crate::oscilloscope::js::*;
+
+#[cfg(feature = "yew-components")]
+mod yew_component;
+#[cfg(feature = "yew-components")]
+pub use crate::oscilloscope::yew_component::*;
@@ -0,0 +1,118 @@
+use crate::oscilloscope::common::*;
+use crate::*;
+
+pub fn plot_oscilloscope(
+    oscilloscope: &OscilloscopeModel,
+    width: f64,
+    height: f64,
+) -> OscilloscopeGraph {
+    let grid = grid(oscilloscope, width, height);
+
+    let center = height / 2.0;
+    let pixels_per_unit =
+        height / oscilloscope.vertical_divisions as f64 / oscilloscope.vertical_scale;
+    let y = |sample: Sample| center - sample as f64 * pixels_per_unit;
+
+    let trigger_level = match oscilloscope.trigger.mode {
+        TriggerMode::FreeRun => None,
+        TriggerMode::Edge(_) => {
+            let level = oscilloscope.trigger.level;
+            let y = y(level).floor();
+            Some(Line {
+                x_start: 0.0,
+                y_start: y,
+                x_end: width,
+                y_end: y,
+                value: level as f64,
+            })
+        }
+    };
+
+    let columns = width.floor().max(1.0) as usize;
+    let sweep_length = oscilloscope.sweep_length();
+    let traces = oscilloscope
+        .traces
+        .iter()
+        .map(|trace| {
+            if trace.len() > columns * 2 {
+                // draw the envelope of each pixel column
+                decimate(trace, columns)
+                    .into_iter()
+                    .enumerate()
+                    .flat_map(|(column, (min, max))| {
+                        let x = column as f64 * width / columns as f64;
+                        vec![(x, y(min)), (x, y(max))]
+                    })
+                    .collect()
+            } else {
+                let step = width / (sweep_length - 1) as f64;
+                trace
+                    .iter()
+                    .enumerate()
+                    .map(|(i, sample)| (i as f64 * step, y(*sample)))
+                    .collect()
+            }
+        })
+        .collect();
+
+    OscilloscopeGraph {
+        grid,
+        trigger_level,
+        traces,
+    }
+}
+
+fn grid(oscilloscope: &OscilloscopeModel, width: f64, height: f64) -> Vec<Line> {
+    let columns = oscilloscope.horizontal_divisions;
+    let rows = oscilloscope.vertical_divisions;
+
+    let vertical = (0..=columns).map(|i| {
+        let x = (i as f64 * width / columns as f64).floor();
+        Line {
+            x_start: x,
+            y_start: 0.0,
+            x_end: x,
+            y_end: height,
+            value: i as f64 * oscilloscope.timebase,
+        }
+    });
+    let horizontal = (0..=rows).map(|i| {
+        let y = (i as f64 * height / rows as f64).floor();
+        Line {
+            x_start: 0.0,
+            y_start: y,
+            x_end: width,
+            y_end: y,
+            value: (rows as f64 / 2.0 - i as f64) * oscilloscope.vertical_scale,
+        }
+    });
+
+    vertical.chain(horizontal).collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_plot_oscilloscope() {
+        let mut scope = OscilloscopeModel::new(2, 1_000.0);
+        scope.timebase = 0.001;
+        scope.vertical_scale = 0.25;
+        scope.trigger.mode = TriggerMode::FreeRun;
+        let short: Vec<Sample> = (0..10).map(|i| i as Sample / 10.0).collect();
+        scope.traces = vec![short, vec![0.5; 1_000]];
+
+        let graph = scope.plot(100.0, 80.0);
+
+        assert_eq!(11 + 9, graph.grid.len());
+        assert!(graph.trigger_level.is_none());
+        // 10 samples span the full width
+        assert_eq!(10, graph.traces[0].len());
+        assert!((graph.traces[0][9].0 - 100.0).abs() < 1e-9);
+        assert!((graph.traces[0][0].1 - 40.0).abs() < 1e-9);
+        // 1000 samples are decimated to a min and max point per pixel column
+        assert_eq!(200, graph.traces[1].len());
+        assert!((graph.traces[1][0].1 - 20.0).abs() < 1e-9);
+    }
+}
@@ -0,0 +1,128 @@
+use crate::js_utils::*;
+use crate::oscilloscope::*;
+use crate::*;
+use derivative::*;
+use wasm_bindgen::prelude::*;
+use web_sys::HtmlCanvasElement;
+use yew::prelude::*;
+
+pub struct Oscilloscope {
+    props: Props,
+    link: ComponentLink<Self>,
+    canvas: NodeRef,
+    renderer: Option<CanvasOscilloscopeRenderer>,
+    render_callback: Closure<dyn FnMut()>,
+    needs_repaint: bool,
+}
+
+/// Turning the wheel changes the timebase, with shift held down it changes the vertical scale.
+#[derive(Derivative, Properties)]
+#[derivative(Debug, Clone, PartialEq)]
+pub struct Props {
+    pub id: String,
+    pub oscilloscope: OscilloscopeModel,
+    pub width: f64,
+    pub height: f64,
+    #[derivative(PartialEq = "ignore")]
+    pub on_timebase: Option<Callback<Seconds>>,
+    #[derivative(PartialEq = "ignore")]
+    pub on_vertical_scale: Option<Callback<f64>>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Msg {
+    Render,
+    Wheel(WheelEvent),
+}
+
+impl Component for Oscilloscope {
+    type Message = Msg;
+
+    type Properties = Props;
+
+    fn create(props: Self::Properties, link: ComponentLink<Self>) -> Self {
+        let cb_link = link.clone();
+        let render_callback =
+            Closure::wrap(Box::new(move || cb_link.send_message(Msg::Render)) as Box<dyn FnMut()>);
+        Oscilloscope {
+            props,
+            link,
+            canvas: NodeRef::default(),
+            renderer: None,
+            render_callback,
+            needs_repaint: false,
+        }
+    }
+
+    fn update(&mut self, msg: Self::Message) -> ShouldRender {
+        match msg {
+            Msg::Render => self.render(),
+            Msg::Wheel(e) => self.handle_wheel(e),
+        }
+        false
+    }
+
+    fn change(&mut self, props: Self::Properties) -> ShouldRender {
+        if props != self.props {
+            self.props = props;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn view(&self) -> Html {
+        let wheel_callback = self.link.callback(Msg::Wheel);
+        html! {
+            <canvas id={self.props.id.clone()} class="oscilloscope" ref=self.canvas.clone()
+            width={self.props.width} height={self.props.height} onwheel={wheel_callback}/>
+        }
+    }
+
+    fn rendered(&mut self, first_render: bool) {
+        if first_render {
+            self.renderer = self
+                .canvas
+                .cast::<HtmlCanvasElement>()
+                .and_then(CanvasOscilloscopeRenderer::new);
+        }
+
+        self.schedule_repaint();
+    }
+}
+
+impl Oscilloscope {
+    fn render(&mut self) {
+        self.needs_repaint = false;
+        if let Some(renderer) = self.renderer.as_ref() {
+            renderer.render_to_canvas(&self.props.oscilloscope);
+        }
+    }
+
+    fn schedule_repaint(&mut self) {
+        if !self.needs_repaint {
+            self.needs_repaint = true;
+            request_animation_frame(&self.render_callback);
+        }
+    }
+
+    fn handle_wheel(&mut self, e: WheelEvent) {
+        let steps = if e.delta_y() > 0.0 { 1 } else { -1 };
+        let oscilloscope = &mut self.props.oscilloscope;
+
+        if e.shift_key() {
+            oscilloscope.step_vertical_scale(steps);
+            if let Some(Callback::Callback(fun)) = &self.props.on_vertical_scale {
+                fun(oscilloscope.vertical_scale);
+            }
+        } else {
+            oscilloscope.step_timebase(steps);
+            if let Some(Callback::Callback(fun)) = &self.props.on_timebase {
+                fun(oscilloscope.timebase);
+            }
+        }
+
+        self.schedule_repaint();
+        e.prevent_default();
+    }
+}
@@ -2,6 +2,7 @@ mod common;
 mod plotter;
 
 pub use crate::scale::common::*;
+pub use crate::scale::plotter::*;
 
 #[cfg(feature = "yew-components")]
 mod yew_component;
@@ -0,0 +1,129 @@
+use crate::scale::ScaleModel;
+use crate::*;
+use scales::prelude::*;
+use std::fmt::Debug;
+
+/// Dragged values closer to the detent than this fraction of the scale snap to it.
+pub const DETENT_RANGE: f64 = 0.03;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SliderModel<S: Scale<f64>> {
+    pub min: SliderValue,
+    pub max: SliderValue,
+    pub value: SliderValue,
+    pub scale: ScaleModel<S>,
+    /// A value the slider sticks to when moved across it, like the centre of a pan control.
+    pub detent: Option<SliderValue>,
+}
+
+impl<S: Scale<f64>> SliderModel<S> {
+    pub fn new(scale: ScaleModel<S>) -> SliderModel<S> {
+        SliderModel {
+            min: scale.min(),
+            max: scale.max(),
+            value: scale.default_value.unwrap_or_else(|| scale.min()),
+            scale,
+            detent: None,
+        }
+    }
+
+    /// Creates a slider for values around a centre, e.g. pan or balance. The detent and the
+    /// origin of the filled track are at the scale's default value, or its middle if it has none.
+    pub fn bipolar(scale: ScaleModel<S>) -> SliderModel<S> {
+        let centre = scale
+            .default_value
+            .unwrap_or_else(|| scale.to_absolute(0.5));
+        SliderModel {
+            min: scale.min(),
+            max: scale.max(),
+            value: centre,
+            scale,
+            detent: Some(centre),
+        }
+    }
+
+    pub fn update(&mut self, value: SliderValue) {
+        self.value = value;
+    }
+
+    pub fn pixel_scale(&self, x_offset: f64, width: f64, knob_width: f64) -> PixelScale {
+        PixelScale::new(x_offset, x_offset + width - knob_width)
+    }
+
+    /// Snaps a dragged value to the detent if it is close enough.
+    pub fn snap(&self, value: SliderValue) -> SliderValue {
+        match self.detent {
+            Some(detent)
+                if (self.scale.to_relative(value) - self.scale.to_relative(detent)).abs()
+                    < DETENT_RANGE =>
+            {
+                detent
+            }
+            _ => value,
+        }
+    }
+
+    /// Stops a step from `old` to `new` at the detent if it would move across it.
+    pub fn stop_at_detent(&self, old: SliderValue, new: SliderValue) -> SliderValue {
+        match self.detent {
+            Some(detent) if (old < detent && new > detent) || (old > detent && new < detent) => {
+                detent
+            }
+            _ => new,
+        }
+    }
+
+    /// The relative start and end of the filled part of the track, which runs from the detent
+    /// to the value for bipolar sliders and from the minimum otherwise.
+    pub fn fill_range(&self) -> (f64, f64) {
+        let origin = self.detent.map_or(0.0, |d| self.scale.to_relative(d));
+        let value = self.scale.to_relative(self.value);
+        if value < origin {
+            (value, origin)
+        } else {
+            (origin, value)
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::scale::{HorizontalPosition, Layout};
+
+    fn pan_scale() -> ScaleModel<LinearScale<f64>> {
+        ScaleModel::new(
+            LinearScale::new(-100.0, 100.0),
+            Layout::Horizontal(HorizontalPosition::Bottom),
+            None,
+            vec![-100.0, 0.0, 100.0],
+            vec![],
+        )
+    }
+
+    #[test]
+    fn test_detent() {
+        let mut slider = SliderModel::bipolar(pan_scale());
+        assert_eq!(Some(0.0), slider.detent);
+        assert!(slider.value.abs() < f64::EPSILON);
+
+        assert!(slider.snap(5.0).abs() < f64::EPSILON);
+        assert!((slider.snap(-7.0) - -7.0).abs() < f64::EPSILON);
+        assert!(slider.stop_at_detent(-2.0, 2.0).abs() < f64::EPSILON);
+        assert!((slider.stop_at_detent(0.0, 2.0) - 2.0).abs() < f64::EPSILON);
+
+        slider.update(-50.0);
+        assert_eq!((0.25, 0.5), slider.fill_range());
+    }
+
+    #[test]
+    fn test_unipolar() {
+        let mut slider = SliderModel::new(pan_scale());
+        assert_eq!(None, slider.detent);
+        assert!((slider.value - -100.0).abs() < f64::EPSILON);
+        assert!((slider.snap(1.0) - 1.0).abs() < f64::EPSILON);
+
+        slider.update(50.0);
+        assert_eq!((0.0, 0.75), slider.fill_range());
+    }
+}
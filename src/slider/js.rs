@@ -0,0 +1,13 @@
+use crate::*;
+use web_sys::*;
+
+#[derive(Debug, Clone)]
+pub struct Elements {
+    pub knob: HtmlElement,
+    pub fill: HtmlElement,
+    pub tooltip: Option<HtmlElement>,
+    pub bounds: Bounds,
+    pub knob_bounds: Bounds,
+    pub pixel_scale: PixelScale,
+    pub scale_pixel_scale: PixelScale,
+}
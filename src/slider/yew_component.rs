@@ -0,0 +1,517 @@
+use crate::js_utils::*;
+use crate::slider::common::*;
+use crate::slider::js::*;
+use crate::*;
+use derivative::*;
+use scales::prelude::*;
+use std::fmt::Debug;
+use wasm_bindgen::prelude::*;
+use web_sys::*;
+use yew::prelude::*;
+
+#[derive(Debug)]
+pub struct Slider<SliderScale: Scale<f64> + Debug + Clone + PartialEq + 'static> {
+    props: Props<SliderScale>,
+    ext_props: Option<Props<SliderScale>>,
+    link: ComponentLink<Self>,
+    root: NodeRef,
+    knob: NodeRef,
+    fill: NodeRef,
+    tooltip: NodeRef,
+    elements: Option<Elements>,
+    touched: bool,
+    layout_callback: Closure<dyn FnMut()>,
+    needs_layout: bool,
+    background: NodeRef,
+    /// The value following the pointer while dragging, before it is snapped to the detent.
+    drag_value: Option<SliderValue>,
+    last_touch: Option<X>,
+    touch_interrupted: bool,
+}
+
+#[derive(Derivative, Properties)]
+#[derivative(Debug, Clone, PartialEq)]
+pub struct Props<SliderScale: Scale<f64> + Clone + PartialEq> {
+    pub id: Option<String>,
+    pub slider: SliderModel<SliderScale>,
+    #[derivative(PartialEq = "ignore")]
+    pub on_input: Callback<SliderValue>,
+    pub show_tooltip: bool,
+    pub label: String,
+    /// The format of the tooltip and the scale labels.
+    pub value_format: LabelFormat,
+}
+
+impl<SliderScale: Scale<f64> + Clone + PartialEq> Props<SliderScale> {
+    pub fn regular(
+        slider: SliderModel<SliderScale>,
+        on_input: Callback<SliderValue>,
+        label: String,
+        value_format: LabelFormat,
+    ) -> Self {
+        Props {
+            id: None,
+            slider,
+            on_input,
+            show_tooltip: true,
+            label,
+            value_format,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Msg {
+    MouseDown(MouseEvent),
+    MouseUp(MouseEvent),
+    MouseMove(MouseEvent),
+    Wheel(WheelEvent),
+    Scroll(Event),
+    DoubleClick(MouseEvent),
+    TouchStart(TouchEvent),
+    TouchEnd(TouchEvent),
+    TouchMove(TouchEvent),
+    TouchCancel(TouchEvent),
+    Layout,
+    InternalUpdate(SliderValue),
+    Refresh,
+}
+
+impl<SliderScale: Scale<f64> + Debug + Clone + PartialEq> Slider<SliderScale> {
+    fn format_tooltip_text(&self) -> Html {
+        let value = self.props.slider.value;
+        let label = &self.props.label;
+        html! {
+            <table>
+                <tr>
+                    <td>{format!("{}: ", label)}</td> <td>{self.props.value_format.format(value)}</td>
+                </tr>
+            </table>
+        }
+    }
+
+    fn update_knob_position(&mut self) {
+        self.needs_layout = false;
+        if let Some(elements) = &self.elements {
+            let slider = &self.props.slider;
+            let conv = (&slider.scale, &elements.pixel_scale);
+            let x = conv.convert(slider.value);
+            set_style(&elements.knob, "left", &format!("{}px", x));
+
+            let (from, to) = slider.fill_range();
+            let offset = elements.knob_bounds.width / 2.0;
+            let from = elements.pixel_scale.to_absolute(from) + offset;
+            let to = elements.pixel_scale.to_absolute(to) + offset;
+            set_style(&elements.fill, "left", &format!("{}px", from));
+            set_style(&elements.fill, "width", &format!("{}px", to - from));
+
+            self.update_tooltip();
+        }
+    }
+
+    fn handle_mouse_down(&mut self, e: MouseEvent) {
+        if e.button() == 0 {
+            self.handle_down();
+        }
+    }
+
+    fn handle_mouse_up(&mut self, e: MouseEvent) {
+        if e.button() == 0 {
+            self.handle_up();
+        }
+    }
+
+    fn handle_mouse_move(&mut self, e: MouseEvent) {
+        let d_x = e.movement_x() as f64;
+        self.handle_move(d_x);
+    }
+
+    fn handle_touch_start(&mut self, e: TouchEvent) {
+        if e.target_touches().length() != 1 {
+            self.touch_interrupted = true;
+            return;
+        }
+
+        let touches = e.changed_touches();
+
+        if let Some(touch) = touches.get(0) {
+            self.touch_interrupted = false;
+            let x = touch.client_x() as f64;
+            self.last_touch = Some(x);
+            self.handle_down();
+        }
+    }
+
+    fn handle_touch_end(&mut self, e: TouchEvent) {
+        if e.target_touches().length() != 0 {
+            return;
+        }
+
+        self.handle_up();
+    }
+
+    fn handle_touch_move(&mut self, e: TouchEvent) {
+        if e.target_touches().length() != 1 || self.touch_interrupted {
+            return;
+        }
+
+        let touches = e.changed_touches();
+
+        if let Some(touch) = touches.get(0) {
+            let x = touch.client_x() as f64;
+            if let Some(last_x) = self.last_touch {
+                let d_x = x - last_x;
+                self.handle_move(d_x);
+            }
+            self.last_touch = Some(x);
+        }
+    }
+
+    fn handle_touch_cancel(&mut self, e: TouchEvent) {
+        if e.target_touches().length() != 0 {
+            return;
+        }
+
+        self.handle_up();
+    }
+
+    fn handle_wheel(&mut self, e: WheelEvent) {
+        if let Some(elements) = &self.elements {
+            let slider = &self.props.slider;
+            let value = slider.value;
+            let conv = (&elements.pixel_scale, &slider.scale);
+            let dampening = 2.0;
+            // scrolling up moves the slider to the right
+            let delta = -e.delta_y().signum() * dampening;
+            let new_value = conv.add_external_clamped(delta, value);
+            let new_value = slider.stop_at_detent(value, new_value);
+            if (new_value - value).abs() > f64::EPSILON {
+                self.update_internally(new_value);
+                self.update_backend(new_value);
+            }
+        }
+
+        e.prevent_default();
+    }
+
+    fn handle_scroll(&self, e: Event) {
+        // prevent pull-to-refresh on mobile devices
+        e.prevent_default();
+    }
+
+    /// Double clicking resets the slider to its default value, or the detent if it has none.
+    fn handle_double_click(&self, _e: MouseEvent) {
+        let slider = &self.props.slider;
+        let value = slider
+            .scale
+            .default_value
+            .or(slider.detent)
+            .unwrap_or(slider.min);
+        self.update_internally(value);
+        self.update_backend(value);
+    }
+
+    fn handle_down(&mut self) {
+        if !self.touched {
+            self.touched = true;
+            self.drag_value = Some(self.props.slider.value);
+            self.show_tooltip();
+            self.link.send_message(Msg::Refresh);
+        }
+    }
+
+    fn handle_up(&mut self) {
+        if self.touched {
+            self.touched = false;
+            self.drag_value = None;
+            self.hide_tooltip();
+            self.apply_ext_props();
+        }
+    }
+
+    fn handle_move(&mut self, d_x: X) {
+        if !self.touched {
+            return;
+        }
+        if let (Some(elements), Some(drag_value)) = (&self.elements, self.drag_value) {
+            let slider = &self.props.slider;
+            let x_conv = (&elements.pixel_scale, &slider.scale);
+            let new_drag_value = x_conv.add_external_clamped(d_x, drag_value);
+            let new_value = slider.snap(new_drag_value);
+            let value = slider.value;
+            self.drag_value = Some(new_drag_value);
+            if (new_value - value).abs() > f64::EPSILON {
+                self.update_internally(new_value);
+                self.update_backend(new_value);
+            }
+        }
+    }
+
+    fn update_internally(&self, value: SliderValue) {
+        self.link.send_message(Msg::InternalUpdate(value));
+    }
+
+    fn update_backend(&self, value: SliderValue) {
+        if let Callback::Callback(fun) = &self.props.on_input {
+            fun(value);
+        }
+    }
+
+    fn show_tooltip(&self) {
+        if !self.props.show_tooltip {
+            return;
+        }
+
+        if let Some(tooltip) = self.elements.as_ref().and_then(|s| s.tooltip.as_ref()) {
+            set_style(tooltip, "opacity", "1");
+        }
+    }
+
+    fn hide_tooltip(&self) {
+        if !self.props.show_tooltip {
+            return;
+        }
+
+        if let Some(tooltip) = self.elements.as_ref().and_then(|s| s.tooltip.as_ref()) {
+            set_style(tooltip, "opacity", "0");
+        }
+    }
+
+    fn update_tooltip(&self) {
+        if !self.props.show_tooltip {
+            return;
+        }
+
+        if let (Some(tooltip), Some(elements)) =
+            (&self.tooltip.cast::<HtmlElement>(), &self.elements)
+        {
+            let slider = &self.props.slider;
+            let conv = (&elements.pixel_scale, &slider.scale);
+            let x = conv.convert_back(slider.value) + elements.knob_bounds.width / 2.0;
+
+            position_tooltip(tooltip, x);
+        }
+    }
+
+    fn apply_ext_props(&mut self) {
+        if let Some(props) = &self.ext_props {
+            self.props = props.to_owned();
+            self.ext_props = None;
+            self.link.send_message(Msg::Refresh);
+        }
+    }
+
+    fn background_bounds(&self) -> Option<Bounds> {
+        self.background
+            .cast::<Element>()
+            .map(|e| get_absolute_bounding_rect(&e))
+    }
+}
+
+impl<SliderScale: Scale<f64> + Debug + Clone + PartialEq + 'static> Component
+    for Slider<SliderScale>
+{
+    type Message = Msg;
+
+    type Properties = Props<SliderScale>;
+
+    fn create(props: Self::Properties, link: ComponentLink<Self>) -> Self {
+        let cb_link = link.clone();
+        let layout_callback =
+            Closure::wrap(Box::new(move || cb_link.send_message(Msg::Layout)) as Box<dyn FnMut()>);
+
+        let cb_link = link.clone();
+        let mouse_moved =
+            Closure::wrap(Box::new(move |e| cb_link.send_message(Msg::MouseMove(e)))
+                as Box<dyn Fn(MouseEvent)>);
+
+        let cb_link = link.clone();
+        let mouse_up = Closure::wrap(
+            Box::new(move |e| cb_link.send_message(Msg::MouseUp(e))) as Box<dyn Fn(MouseEvent)>
+        );
+
+        register_global_listener("mousemove", &mouse_moved);
+        register_global_listener("mouseup", &mouse_up);
+
+        mouse_moved.forget();
+        mouse_up.forget();
+
+        Slider {
+            props,
+            ext_props: None,
+            link,
+            root: NodeRef::default(),
+            knob: NodeRef::default(),
+            fill: NodeRef::default(),
+            tooltip: NodeRef::default(),
+            elements: None,
+            touched: false,
+            layout_callback,
+            needs_layout: false,
+            background: NodeRef::default(),
+            drag_value: None,
+            last_touch: None,
+            touch_interrupted: false,
+        }
+    }
+
+    fn update(&mut self, msg: Self::Message) -> ShouldRender {
+        match msg {
+            Msg::MouseDown(e) => self.handle_mouse_down(e),
+            Msg::MouseUp(e) => self.handle_mouse_up(e),
+            Msg::MouseMove(e) => self.handle_mouse_move(e),
+            Msg::Wheel(e) => self.handle_wheel(e),
+            Msg::Scroll(e) => self.handle_scroll(e),
+            Msg::DoubleClick(e) => self.handle_double_click(e),
+            Msg::TouchStart(e) => self.handle_touch_start(e),
+            Msg::TouchEnd(e) => self.handle_touch_end(e),
+            Msg::TouchMove(e) => self.handle_touch_move(e),
+            Msg::TouchCancel(e) => self.handle_touch_cancel(e),
+            Msg::Layout => self.update_knob_position(),
+            Msg::Refresh => {
+                return true;
+            }
+            Msg::InternalUpdate(new_value) => {
+                self.props.slider.update(new_value);
+                return true;
+            }
+        }
+        false
+    }
+
+    fn change(&mut self, props: Self::Properties) -> ShouldRender {
+        // don't accept external changes while the slider is being dragged
+        if self.touched {
+            self.ext_props = Some(props);
+            false
+        } else if props != self.props {
+            self.props = props;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn view(&self) -> Html {
+        let id = self.props.id.as_deref().unwrap_or("");
+
+        let mouse_down_callback = self.link.callback(Msg::MouseDown);
+        let wheel_callback = self.link.callback(Msg::Wheel);
+        let scroll_callback = self.link.callback(Msg::Scroll);
+        let double_click_callback = self.link.callback(Msg::DoubleClick);
+
+        let touch_start_callback = self.link.callback(Msg::TouchStart);
+        let touch_end_callback = self.link.callback(Msg::TouchEnd);
+        let touch_move_callback = self.link.callback(Msg::TouchMove);
+        let touch_cancel_callback = self.link.callback(Msg::TouchCancel);
+
+        let scale = self.props.slider.scale.clone();
+        let label_format = Some(self.props.value_format.clone());
+
+        let background = self.background.clone();
+        let background_bounds = self.background_bounds();
+
+        let scale = if let Some(elements) = self.elements.as_ref() {
+            let height = elements.bounds.height;
+            let pixel_scale = elements.scale_pixel_scale.clone();
+            html! {
+                <svg class="scale" width={background_bounds.as_ref().map(|b|b.width).unwrap_or(0.0)} height={background_bounds.as_ref().map(|b|b.height).unwrap_or(0.0)}>
+                    <scale::Scale<SliderScale> scale={scale} label_format={label_format} width={height} pixel_scale={pixel_scale} />
+                </svg>
+            }
+        } else {
+            html! {}
+        };
+
+        let class = if self.props.slider.detent.is_some() {
+            "slider bipolar"
+        } else {
+            "slider"
+        };
+
+        html! {
+            <div
+                class={class}
+                id={id} ref=self.root.clone()
+                onmousedown={mouse_down_callback}
+                onwheel={wheel_callback}
+                onscroll={scroll_callback}
+                ondblclick={double_click_callback}
+                ontouchstart={touch_start_callback}
+                ontouchend={touch_end_callback}
+                ontouchmove={touch_move_callback}
+                ontouchcancel={touch_cancel_callback}
+            >
+                <div class="slider-background" ref={background}>
+                    <span class="track"></span>
+                    {scale}
+                </div>
+                <span class="fill" ref=self.fill.clone()></span>
+                <span class="knob" ref=self.knob.clone()></span>
+                {
+                    if self.props.show_tooltip {
+                        let tooltip_text = self.format_tooltip_text();
+                        html!{<span ref=self.tooltip.clone() class="tooltip">{tooltip_text}</span>}
+                    } else {
+                        html!{}
+                    }
+                }
+            </div>
+        }
+    }
+
+    fn rendered(&mut self, first_render: bool) {
+        if first_render {
+            if let (Some(root), Some(knob), Some(fill)) = (
+                self.root.cast::<HtmlElement>(),
+                self.knob.cast::<HtmlElement>(),
+                self.fill.cast::<HtmlElement>(),
+            ) {
+                let tooltip = self.tooltip.cast::<HtmlElement>();
+                let bounds = get_absolute_bounding_rect(&root);
+                let knob_bounds = get_absolute_bounding_rect(&knob);
+
+                let pixel_scale =
+                    self.props
+                        .slider
+                        .pixel_scale(bounds.x, bounds.width, knob_bounds.width);
+
+                let offset = knob_bounds.width / 2.0;
+                let range = bounds.width - knob_bounds.width;
+                let scale_pixel_scale = LinearScale::new(offset, offset + range);
+
+                self.elements = Some(Elements {
+                    knob,
+                    fill,
+                    tooltip,
+                    bounds,
+                    knob_bounds,
+                    pixel_scale,
+                    scale_pixel_scale,
+                });
+            } else {
+                self.elements = None;
+            }
+            self.link.send_message(Msg::Refresh)
+        }
+
+        if !self.needs_layout {
+            self.needs_layout = true;
+            request_animation_frame(&self.layout_callback);
+        }
+    }
+}
+
+fn position_tooltip(tooltip: &HtmlElement, x: X) {
+    let padding = 8.0;
+
+    let tooltip_rect = tooltip.get_bounding_client_rect();
+    let tooltip_width = tooltip_rect.width();
+
+    let left = (x - tooltip_width / 2.0).max(padding);
+
+    tooltip
+        .style()
+        .set_property("left", &format!("{}px", left))
+        .unwrap();
+}
@@ -0,0 +1,539 @@
+use crate::scale::{HorizontalPosition, Layout, ScaleModel, VerticalPosition};
+use crate::spectrum::fft::Fft;
+use crate::spectrum::plotter;
+use crate::utils::*;
+use crate::*;
+use std::f64::consts::PI;
+
+pub const MAJOR_ANALYZER_FREQUENCY_MARKERS: [f64; 10] = [
+    20.0, 50.0, 100.0, 200.0, 500.0, 1_000.0, 2_000.0, 5_000.0, 10_000.0, 20_000.0,
+];
+pub const MINOR_ANALYZER_FREQUENCY_MARKERS: [f64; 18] = [
+    30.0, 40.0, 60.0, 70.0, 80.0, 90.0, 300.0, 400.0, 600.0, 700.0, 800.0, 900.0, 3_000.0, 4_000.0,
+    6_000.0, 7_000.0, 8_000.0, 9_000.0,
+];
+
+/// The frequency around which slope tilt is applied, i.e. the one left unchanged.
+pub const TILT_PIVOT: Frequency = 1_000.0;
+/// Power levels are floored to this value to keep silence finite.
+pub const MIN_MAGNITUDE: Gain = -200.0;
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Window {
+    Rectangular,
+    Hann,
+    BlackmanHarris,
+}
+
+impl Window {
+    pub fn coefficients(&self, size: usize) -> Vec<f64> {
+        let n = size as f64;
+        (0..size)
+            .map(|i| {
+                let x = 2.0 * PI * i as f64 / n;
+                match self {
+                    Window::Rectangular => 1.0,
+                    Window::Hann => 0.5 - 0.5 * x.cos(),
+                    Window::BlackmanHarris => {
+                        0.35875 - 0.48829 * x.cos() + 0.14128 * (2.0 * x).cos()
+                            - 0.01168 * (3.0 * x).cos()
+                    }
+                }
+            })
+            .collect()
+    }
+}
+
+/// Magnitudes in dB of equally spaced frequency bins, starting at DC.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Spectrum {
+    /// The frequency distance between two bins.
+    pub bin_width: Frequency,
+    pub magnitudes: Vec<Gain>,
+}
+
+impl Spectrum {
+    pub fn new(bin_width: Frequency, magnitudes: Vec<Gain>) -> Spectrum {
+        Spectrum {
+            bin_width,
+            magnitudes,
+        }
+    }
+
+    /// Creates a spectrum from linear amplitudes, where 1.0 corresponds to 0 dB.
+    pub fn from_amplitudes(bin_width: Frequency, amplitudes: &[Sample]) -> Spectrum {
+        let magnitudes = amplitudes
+            .iter()
+            .map(|a| power_to_db((*a as f64).powi(2)))
+            .collect();
+        Spectrum::new(bin_width, magnitudes)
+    }
+
+    pub fn frequency(&self, bin: usize) -> Frequency {
+        bin as f64 * self.bin_width
+    }
+
+    /// Sums the power of all bins within each fractional-octave band overlapping `min` to `max`.
+    /// Band centers are spaced from 1 kHz. Bands too narrow to contain a bin take the level of the
+    /// bin closest to their center. A range that is not positive and finite has no bands.
+    pub fn bands(&self, bands: OctaveBands, min: Frequency, max: Frequency) -> Vec<Band> {
+        if !(min > 0.0 && max.is_finite()) {
+            return vec![];
+        }
+        let fraction = bands.fraction() as f64;
+        let half_band = 2f64.powf(0.5 / fraction);
+        // every band overlapping the range is included
+        let first = (fraction * (min / half_band / 1_000.0).log2()).ceil() as i32;
+        let last = (fraction * (max * half_band / 1_000.0).log2()).floor() as i32;
+        let last_bin = self.magnitudes.len().saturating_sub(1);
+
+        (first..=last)
+            .map(|i| {
+                let center = 1_000.0 * 2f64.powf(i as f64 / fraction);
+                let lower = center / half_band;
+                let upper = center * half_band;
+                let first_bin = (lower / self.bin_width).ceil() as usize;
+                let last_bin = ((upper / self.bin_width).ceil() as usize).min(last_bin + 1);
+                let level = if first_bin < last_bin {
+                    let power = self.magnitudes[first_bin..last_bin]
+                        .iter()
+                        .map(|m| to_power(*m))
+                        .sum();
+                    power_to_db(power)
+                } else {
+                    let nearest = ((center / self.bin_width).round() as usize).min(last_bin);
+                    self.magnitudes
+                        .get(nearest)
+                        .copied()
+                        .unwrap_or(MIN_MAGNITUDE)
+                };
+                Band {
+                    center,
+                    lower,
+                    upper,
+                    level,
+                }
+            })
+            .collect()
+    }
+
+    /// Averages the power of each bin over a band of 1/`fraction` octave centered on it.
+    pub fn smoothed(&self, fraction: usize) -> Spectrum {
+        if fraction == 0 || self.magnitudes.is_empty() {
+            return self.clone();
+        }
+
+        // prefix sums of linear power, so every band is averaged in constant time
+        let mut sums = Vec::with_capacity(self.magnitudes.len() + 1);
+        sums.push(0.0);
+        for magnitude in &self.magnitudes {
+            let last = sums[sums.len() - 1];
+            sums.push(last + to_power(*magnitude));
+        }
+
+        let half_band = 2f64.powf(0.5 / fraction as f64);
+        let last_bin = self.magnitudes.len() - 1;
+        let magnitudes = (0..self.magnitudes.len())
+            .map(|bin| {
+                let lower = ((bin as f64 / half_band).floor() as usize).min(bin);
+                let upper = ((bin as f64 * half_band).ceil() as usize).clamp(bin, last_bin);
+                let power = (sums[upper + 1] - sums[lower]) / (upper + 1 - lower) as f64;
+                power_to_db(power)
+            })
+            .collect();
+
+        Spectrum::new(self.bin_width, magnitudes)
+    }
+
+    /// Tilts the spectrum by `slope` dB per octave around [`TILT_PIVOT`], e.g. +3 dB/oct to make
+    /// pink noise look flat.
+    pub fn tilted(&self, slope: Gain) -> Spectrum {
+        if slope == 0.0 {
+            return self.clone();
+        }
+        let magnitudes = self
+            .magnitudes
+            .iter()
+            .enumerate()
+            .map(|(bin, magnitude)| match self.frequency(bin) {
+                f if f > 0.0 => magnitude + slope * (f / TILT_PIVOT).log2(),
+                _ => *magnitude,
+            })
+            .collect();
+        Spectrum::new(self.bin_width, magnitudes)
+    }
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum OctaveBands {
+    Third,
+    Sixth,
+    Twelfth,
+}
+
+impl OctaveBands {
+    pub fn fraction(&self) -> usize {
+        match self {
+            OctaveBands::Third => 3,
+            OctaveBands::Sixth => 6,
+            OctaveBands::Twelfth => 12,
+        }
+    }
+}
+
+/// A fractional-octave band and its summed level in dB.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Band {
+    pub center: Frequency,
+    pub lower: Frequency,
+    pub upper: Frequency,
+    pub level: Gain,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum DisplayMode {
+    Line,
+    Bars,
+}
+
+/// The state of a standalone analyzer display: the current spectrum, its peak hold trace and an
+/// optional frozen trace to compare against, shown on frequency and level scales.
+#[derive(Debug, PartialEq, Clone)]
+pub struct AnalyzerModel {
+    pub spectrum: Spectrum,
+    pub peaks: Option<Spectrum>,
+    pub frozen: Option<Spectrum>,
+    pub mode: DisplayMode,
+    /// Shows fractional-octave bands instead of FFT bins if set.
+    pub bands: Option<OctaveBands>,
+    pub frequency_scale: ScaleModel<FreqScale>,
+    pub level_scale: ScaleModel<GainScale>,
+}
+
+impl AnalyzerModel {
+    pub fn new(
+        min_frequency: Frequency,
+        max_frequency: Frequency,
+        min_level: Gain,
+        max_level: Gain,
+    ) -> AnalyzerModel {
+        let frequency_scale = ScaleModel::new(
+            FreqScale::new(min_frequency, max_frequency),
+            Layout::Horizontal(HorizontalPosition::Bottom),
+            None,
+            filter_markers(
+                &MAJOR_ANALYZER_FREQUENCY_MARKERS,
+                min_frequency,
+                max_frequency,
+                true,
+            ),
+            filter_markers(
+                &MINOR_ANALYZER_FREQUENCY_MARKERS,
+                min_frequency,
+                max_frequency,
+                true,
+            ),
+        );
+        let steps = ((max_level - min_level) / 10.0).floor() as usize;
+        let major_level_markers = (0..=steps).map(|i| max_level - i as f64 * 10.0).collect();
+        let minor_level_markers = (0..steps)
+            .map(|i| max_level - 5.0 - i as f64 * 10.0)
+            .collect();
+        let level_scale = ScaleModel::new(
+            GainScale::new(min_level, max_level),
+            Layout::Vertical(VerticalPosition::Left),
+            None,
+            major_level_markers,
+            minor_level_markers,
+        );
+        AnalyzerModel {
+            spectrum: Spectrum::new(1.0, vec![]),
+            peaks: None,
+            frozen: None,
+            mode: DisplayMode::Line,
+            bands: None,
+            frequency_scale,
+            level_scale,
+        }
+    }
+
+    /// Shows a precomputed spectrum, e.g. one measured by the host.
+    pub fn update(&mut self, spectrum: Spectrum, peaks: Option<Spectrum>) {
+        self.spectrum = spectrum;
+        self.peaks = peaks;
+    }
+
+    /// Shows the current output of an analyzer fed with sample blocks.
+    pub fn update_from_analyzer(&mut self, analyzer: &SpectrumAnalyzer) {
+        self.update(analyzer.spectrum(), Some(analyzer.peak_spectrum()));
+    }
+
+    /// Keeps a copy of the current spectrum as comparison trace.
+    pub fn freeze(&mut self) {
+        self.frozen = Some(self.spectrum.clone());
+    }
+
+    pub fn unfreeze(&mut self) {
+        self.frozen = None;
+    }
+
+    pub fn is_frozen(&self) -> bool {
+        self.frozen.is_some()
+    }
+
+    pub fn plot(&self, width: f64, height: f64) -> AnalyzerGraph {
+        plotter::plot_analyzer(self, width, height)
+    }
+}
+
+impl Default for AnalyzerModel {
+    fn default() -> Self {
+        AnalyzerModel::new(20.0, 20_000.0, -90.0, 0.0)
+    }
+}
+
+pub struct AnalyzerGraph {
+    /// The current spectrum as a curve in line mode.
+    pub trace: Vec<(X, Y)>,
+    /// The current spectrum as bars in bar mode.
+    pub bars: Vec<Bounds>,
+    pub peaks: Vec<(X, Y)>,
+    pub frozen: Vec<(X, Y)>,
+    pub frequency_scale: crate::scale::ScaleGraph,
+    pub level_scale: crate::scale::ScaleGraph,
+}
+
+/// Turns blocks of samples into spectra using overlapping windowed FFTs. Power is averaged
+/// exponentially across frames and a decaying peak hold trace is kept alongside.
+#[derive(Debug, PartialEq, Clone)]
+pub struct SpectrumAnalyzer {
+    pub sample_rate: f64,
+    /// Weight of the previous frames when averaging, from 0 (no averaging) towards 1.
+    pub averaging: f64,
+    /// How fast held peaks fall in dB per second, 0 holds them until reset.
+    pub peak_decay: Gain,
+    /// Applies 1/n-octave smoothing to the output if set.
+    pub smoothing: Option<usize>,
+    /// Slope tilt of the output in dB per octave.
+    pub slope: Gain,
+    window: Window,
+    fft: Fft,
+    coefficients: Vec<f64>,
+    normalization: f64,
+    input: Vec<f64>,
+    position: usize,
+    hop: usize,
+    since_frame: usize,
+    re: Vec<f64>,
+    im: Vec<f64>,
+    power: Vec<f64>,
+    peaks: Vec<Gain>,
+}
+
+impl SpectrumAnalyzer {
+    /// Creates an analyzer with a Hann window and 50 % overlap. `fft_size` must be a power of two.
+    pub fn new(fft_size: usize, sample_rate: f64) -> SpectrumAnalyzer {
+        let window = Window::Hann;
+        let coefficients = window.coefficients(fft_size);
+        let bins = fft_size / 2 + 1;
+        SpectrumAnalyzer {
+            sample_rate,
+            averaging: 0.7,
+            peak_decay: 12.0,
+            smoothing: None,
+            slope: 0.0,
+            window,
+            fft: Fft::new(fft_size),
+            normalization: normalization(&coefficients),
+            coefficients,
+            input: vec![0.0; fft_size],
+            position: 0,
+            hop: (fft_size / 2).max(1),
+            since_frame: 0,
+            re: vec![0.0; fft_size],
+            im: vec![0.0; fft_size],
+            power: vec![0.0; bins],
+            peaks: vec![MIN_MAGNITUDE; bins],
+        }
+    }
+
+    pub fn fft_size(&self) -> usize {
+        self.fft.size()
+    }
+
+    pub fn bin_width(&self) -> Frequency {
+        self.sample_rate / self.fft_size() as f64
+    }
+
+    pub fn window(&self) -> Window {
+        self.window
+    }
+
+    pub fn set_window(&mut self, window: Window) {
+        self.window = window;
+        self.coefficients = window.coefficients(self.fft_size());
+        self.normalization = normalization(&self.coefficients);
+    }
+
+    /// Feeds mono samples into the analyzer. Returns `true` if at least one new frame has been
+    /// analyzed.
+    pub fn push(&mut self, samples: &[Sample]) -> bool {
+        let mut analyzed = false;
+        for sample in samples {
+            self.input[self.position] = *sample as f64;
+            self.position = (self.position + 1) % self.input.len();
+            self.since_frame += 1;
+            if self.since_frame >= self.hop {
+                self.since_frame = 0;
+                self.analyze();
+                analyzed = true;
+            }
+        }
+        analyzed
+    }
+
+    /// The averaged spectrum with smoothing and tilt applied.
+    pub fn spectrum(&self) -> Spectrum {
+        let magnitudes = self.power.iter().map(|p| power_to_db(*p)).collect();
+        self.post_process(Spectrum::new(self.bin_width(), magnitudes))
+    }
+
+    /// The peak hold trace with smoothing and tilt applied.
+    pub fn peak_spectrum(&self) -> Spectrum {
+        self.post_process(Spectrum::new(self.bin_width(), self.peaks.clone()))
+    }
+
+    pub fn reset_peaks(&mut self) {
+        self.peaks.iter_mut().for_each(|p| *p = MIN_MAGNITUDE);
+    }
+
+    pub fn reset(&mut self) {
+        self.input.iter_mut().for_each(|s| *s = 0.0);
+        self.power.iter_mut().for_each(|p| *p = 0.0);
+        self.position = 0;
+        self.since_frame = 0;
+        self.reset_peaks();
+    }
+
+    fn analyze(&mut self) {
+        let size = self.fft_size();
+        for i in 0..size {
+            // the oldest sample sits at the current write position
+            let sample = self.input[(self.position + i) % size];
+            self.re[i] = sample * self.coefficients[i];
+            self.im[i] = 0.0;
+        }
+
+        self.fft.process(&mut self.re, &mut self.im);
+
+        let decay = self.peak_decay * self.hop as f64 / self.sample_rate;
+        let averaging = self.averaging.clamp(0.0, 0.999);
+        for (bin, power) in self.power.iter_mut().enumerate() {
+            let current = (self.re[bin].powi(2) + self.im[bin].powi(2)) * self.normalization;
+            *power = averaging * *power + (1.0 - averaging) * current;
+
+            let peak = &mut self.peaks[bin];
+            *peak = (*peak - decay).max(power_to_db(current));
+        }
+    }
+
+    fn post_process(&self, spectrum: Spectrum) -> Spectrum {
+        let spectrum = match self.smoothing {
+            Some(fraction) => spectrum.smoothed(fraction),
+            None => spectrum,
+        };
+        spectrum.tilted(self.slope)
+    }
+}
+
+/// Scales squared FFT magnitudes so that a full scale sine reads 0 dB regardless of the window.
+fn normalization(coefficients: &[f64]) -> f64 {
+    let sum: f64 = coefficients.iter().sum();
+    (2.0 / sum).powi(2)
+}
+
+fn to_power(magnitude: Gain) -> f64 {
+    to_amplitude(magnitude).powi(2)
+}
+
+fn power_to_db(power: f64) -> Gain {
+    (0.5 * to_db(power)).max(MIN_MAGNITUDE)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_full_scale_sine_reads_zero() {
+        let sample_rate = 48_000.0;
+        let mut analyzer = SpectrumAnalyzer::new(1_024, sample_rate);
+        analyzer.averaging = 0.0;
+        let bin = 64;
+        let frequency = bin as f64 * analyzer.bin_width();
+        let signal: Vec<Sample> = (0..4_096)
+            .map(|i| (2.0 * PI * frequency * i as f64 / sample_rate).sin() as Sample)
+            .collect();
+
+        assert!(analyzer.push(&signal));
+
+        let spectrum = analyzer.spectrum();
+        assert!(spectrum.magnitudes[bin].abs() < 0.01);
+        assert!(spectrum.magnitudes[bin + 8] < -60.0);
+        assert!(analyzer.peak_spectrum().magnitudes[bin].abs() < 0.01);
+    }
+
+    #[test]
+    fn test_smoothing_and_tilt() {
+        let flat = Spectrum::new(10.0, vec![-20.0; 1_000]);
+
+        let smoothed = flat.smoothed(3);
+        assert!(smoothed.magnitudes.iter().all(|m| (m - -20.0).abs() < 1e-9));
+
+        let tilted = flat.tilted(3.0);
+        assert!((tilted.magnitudes[100] - -20.0).abs() < 1e-9);
+        assert!((tilted.magnitudes[200] - -17.0).abs() < 1e-9);
+        assert!((tilted.magnitudes[0] - -20.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_octave_bands() {
+        let spectrum = Spectrum::new(1.0, vec![-30.0; 24_001]);
+
+        let thirds = spectrum.bands(OctaveBands::Third, 20.0, 20_000.0);
+
+        assert_eq!(31, thirds.len());
+        let khz = &thirds[17];
+        assert!((khz.center - 1_000.0).abs() < 1e-9);
+        // roughly 231 bins of -30 dB add up to about -6.4 dB
+        assert!((khz.level - (-30.0 + 10.0 * 231f64.log10())).abs() < 0.1);
+        assert!(thirds
+            .windows(2)
+            .all(|w| (w[0].upper - w[1].lower).abs() < 1e-9));
+    }
+
+    #[test]
+    fn test_bands_of_invalid_range() {
+        let spectrum = Spectrum::new(1.0, vec![-30.0; 1_025]);
+
+        assert!(spectrum.bands(OctaveBands::Third, 0.0, 1_000.0).is_empty());
+        assert!(spectrum
+            .bands(OctaveBands::Sixth, -20.0, 1_000.0)
+            .is_empty());
+        assert!(spectrum
+            .bands(OctaveBands::Sixth, 20.0, f64::INFINITY)
+            .is_empty());
+    }
+
+    #[test]
+    fn test_smoothing_spreads_peak() {
+        let mut magnitudes = vec![MIN_MAGNITUDE; 512];
+        magnitudes[256] = 0.0;
+
+        let smoothed = Spectrum::new(10.0, magnitudes).smoothed(6);
+
+        assert!(smoothed.magnitudes[256] < 0.0);
+        assert!(smoothed.magnitudes[250] > MIN_MAGNITUDE);
+        assert!(smoothed.magnitudes[128] <= MIN_MAGNITUDE + 1e-9);
+    }
+}
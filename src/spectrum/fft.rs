@@ -0,0 +1,94 @@
+use std::f64::consts::PI;
+
+/// An in-place iterative radix-2 FFT with precomputed twiddle factors.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Fft {
+    size: usize,
+    twiddles: Vec<(f64, f64)>,
+}
+
+impl Fft {
+    pub fn new(size: usize) -> Fft {
+        assert!(size.is_power_of_two(), "FFT size must be a power of two");
+        let twiddles = (0..size / 2)
+            .map(|k| {
+                let (sin, cos) = (-2.0 * PI * k as f64 / size as f64).sin_cos();
+                (cos, sin)
+            })
+            .collect();
+        Fft { size, twiddles }
+    }
+
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    /// Transforms the signal given by its real and imaginary parts in place.
+    pub fn process(&self, re: &mut [f64], im: &mut [f64]) {
+        let n = self.size;
+        assert!(
+            re.len() == n && im.len() == n,
+            "buffers must match FFT size"
+        );
+
+        let mut j = 0;
+        for i in 1..n {
+            let mut bit = n >> 1;
+            while j & bit != 0 {
+                j ^= bit;
+                bit >>= 1;
+            }
+            j |= bit;
+            if i < j {
+                re.swap(i, j);
+                im.swap(i, j);
+            }
+        }
+
+        let mut len = 2;
+        while len <= n {
+            let half = len / 2;
+            let step = n / len;
+            for start in (0..n).step_by(len) {
+                for k in 0..half {
+                    let (w_re, w_im) = self.twiddles[k * step];
+                    let a = start + k;
+                    let b = a + half;
+                    let t_re = re[b] * w_re - im[b] * w_im;
+                    let t_im = re[b] * w_im + im[b] * w_re;
+                    re[b] = re[a] - t_re;
+                    im[b] = im[a] - t_im;
+                    re[a] += t_re;
+                    im[a] += t_im;
+                }
+            }
+            len <<= 1;
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_matches_dft() {
+        let n = 16;
+        let signal: Vec<f64> = (0..n).map(|i| ((i * 7) % 5) as f64 - 2.0).collect();
+        let mut re = signal.clone();
+        let mut im = vec![0.0; n];
+
+        Fft::new(n).process(&mut re, &mut im);
+
+        for k in 0..n {
+            let (mut dft_re, mut dft_im) = (0.0, 0.0);
+            for (i, x) in signal.iter().enumerate() {
+                let angle = -2.0 * PI * (k * i) as f64 / n as f64;
+                dft_re += x * angle.cos();
+                dft_im += x * angle.sin();
+            }
+            assert!((re[k] - dft_re).abs() < 1e-9);
+            assert!((im[k] - dft_im).abs() < 1e-9);
+        }
+    }
+}
@@ -0,0 +1,194 @@
+use crate::js_utils::*;
+use crate::spectrum::common::*;
+use crate::spectrum::spectrogram::*;
+use crate::utils::*;
+use crate::*;
+use wasm_bindgen::Clamped;
+use web_sys::CanvasRenderingContext2d;
+use web_sys::HtmlCanvasElement;
+use web_sys::ImageData;
+
+const LABEL_PADDING: f64 = 3.0;
+
+pub struct CanvasAnalyzerRenderer {
+    pub context: CanvasRenderingContext2d,
+    pub style: Style,
+    pub bounds: Bounds,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Style {
+    background_fill: Option<String>,
+    grid_stroke: Option<String>,
+    scale_label_fill: Option<String>,
+    scale_font: Option<String>,
+    trace_stroke: Option<String>,
+    trace_fill: Option<String>,
+    peak_stroke: Option<String>,
+    frozen_stroke: Option<String>,
+}
+
+impl CanvasAnalyzerRenderer {
+    pub fn new(canvas: HtmlCanvasElement) -> Option<CanvasAnalyzerRenderer> {
+        let context = get_context_2d(&canvas)?;
+        let style = get_styles(&canvas);
+
+        let bounds = canvas.get_bounding_client_rect().into();
+
+        let background_fill = get_style("--background-fill", &style, Some("black"));
+        let grid_stroke = get_style("--grid-stroke", &style, Some("#333"));
+        let scale_label_fill = get_style("--scale-label-fill", &style, Some("#777"));
+        let scale_font = get_style("--scale-font", &style, Some("9px sans-serif"));
+        let trace_stroke = get_style("--trace-stroke", &style, Some("#8fd7e0"));
+        let trace_fill = get_style("--trace-fill", &style, Some("#8fd7e066"));
+        let peak_stroke = get_style("--peak-stroke", &style, Some("#8fd7e088"));
+        let frozen_stroke = get_style("--frozen-stroke", &style, Some("orange"));
+
+        let style = Style {
+            background_fill,
+            grid_stroke,
+            scale_label_fill,
+            scale_font,
+            trace_stroke,
+            trace_fill,
+            peak_stroke,
+            frozen_stroke,
+        };
+
+        Some(CanvasAnalyzerRenderer {
+            context,
+            style,
+            bounds,
+        })
+    }
+
+    pub fn render_to_canvas(&self, analyzer: &AnalyzerModel) {
+        let width = self.bounds.width;
+        let height = self.bounds.height;
+
+        let context = &self.context;
+
+        let graph = analyzer.plot(width, height);
+
+        context.clear_rect(0.0, 0.0, width, height);
+
+        set_fill(context, self.style.background_fill.as_ref());
+        context.fill_rect(0.0, 0.0, width, height);
+
+        context.begin_path();
+        set_stroke(context, self.style.grid_stroke.as_ref());
+        let frequency_lines = graph.frequency_scale.major_lines.iter();
+        let level_lines = graph.level_scale.major_lines.iter();
+        for line in frequency_lines.chain(level_lines) {
+            context.move_to(line.x_start.floor() + 0.5, line.y_start.floor() + 0.5);
+            context.line_to(line.x_end.floor() + 0.5, line.y_end.floor() + 0.5);
+        }
+        context.stroke();
+
+        set_fill(context, self.style.trace_fill.as_ref());
+        for bar in &graph.bars {
+            // leave a gap between bands wide enough to show one
+            let gap = if bar.width > 3.0 { 1.0 } else { 0.0 };
+            context.fill_rect(bar.x, bar.y, bar.width - gap, bar.height);
+        }
+
+        self.render_trace(&graph.trace, self.style.trace_stroke.as_ref());
+        self.render_trace(&graph.peaks, self.style.peak_stroke.as_ref());
+        self.render_trace(&graph.frozen, self.style.frozen_stroke.as_ref());
+
+        if let Some(font) = &self.style.scale_font {
+            context.set_font(font);
+        }
+        set_fill(context, self.style.scale_label_fill.as_ref());
+
+        context.set_text_align("left");
+        context.set_text_baseline("bottom");
+        for label in &graph.level_scale.labels {
+            let y = label.y.max(LABEL_PADDING * 4.0);
+            context
+                .fill_text(&label.text, label.x + LABEL_PADDING, y - LABEL_PADDING)
+                .ignore();
+        }
+
+        context.set_text_align("center");
+        for label in &graph.frequency_scale.labels {
+            let x = label
+                .x
+                .max(LABEL_PADDING * 6.0)
+                .min(width - LABEL_PADDING * 6.0);
+            context
+                .fill_text(&label.text, x, label.y - LABEL_PADDING)
+                .ignore();
+        }
+    }
+
+    fn render_trace(&self, points: &[(X, Y)], stroke: Option<&String>) {
+        let context = &self.context;
+        let mut points = points.iter();
+        if let Some((x, y)) = points.next() {
+            context.begin_path();
+            set_stroke(context, stroke);
+            context.move_to(*x + 0.5, *y + 0.5);
+            for (x, y) in points {
+                context.line_to(*x + 0.5, *y + 0.5);
+            }
+            context.stroke();
+        }
+    }
+}
+
+pub struct CanvasSpectrogramRenderer {
+    pub canvas: HtmlCanvasElement,
+    pub context: CanvasRenderingContext2d,
+    pub bounds: Bounds,
+    background_fill: Option<String>,
+}
+
+impl CanvasSpectrogramRenderer {
+    pub fn new(canvas: HtmlCanvasElement) -> Option<CanvasSpectrogramRenderer> {
+        let context = get_context_2d(&canvas)?;
+        let style = get_styles(&canvas);
+
+        let bounds = canvas.get_bounding_client_rect().into();
+
+        let background_fill = get_style("--background-fill", &style, Some("black"));
+
+        Some(CanvasSpectrogramRenderer {
+            canvas,
+            context,
+            bounds,
+            background_fill,
+        })
+    }
+
+    pub fn clear(&self) {
+        set_fill(&self.context, self.background_fill.as_ref());
+        self.context
+            .fill_rect(0.0, 0.0, self.bounds.width, self.bounds.height);
+    }
+
+    /// Scrolls the existing image by one pixel and draws the spectrum as new line.
+    pub fn push_spectrum(&self, spectrogram: &SpectrogramModel, spectrum: &Spectrum) {
+        let context = &self.context;
+        let (line, (dx, dy)) = spectrogram.line_bounds(self.bounds.width, self.bounds.height);
+
+        context
+            .draw_image_with_html_canvas_element(&self.canvas, dx, dy)
+            .ignore();
+
+        let length = if spectrogram.is_horizontal() {
+            line.height
+        } else {
+            line.width
+        } as usize;
+        let data = spectrogram.line(spectrum, length);
+        let image = ImageData::new_with_u8_clamped_array_and_sh(
+            Clamped(&data),
+            line.width as u32,
+            line.height as u32,
+        );
+        if let Ok(image) = image {
+            context.put_image_data(&image, line.x, line.y).ignore();
+        }
+    }
+}
@@ -0,0 +1,18 @@
+mod common;
+mod fft;
+mod plotter;
+mod spectrogram;
+
+pub use crate::spectrum::common::*;
+pub use crate::spectrum::plotter::*;
+pub use crate::spectrum::spectrogram::*;
+
+#[cfg(feature = "js")]
+mod js;
+#[cfg(feature = "js")]
+pub use crate::spectrum::js::*;
+
+#[cfg(feature = "yew-components")]
+mod yew_component;
+#[cfg(feature = "yew-components")]
+pub use crate::spectrum::yew_component::*;
@@ -0,0 +1,168 @@
+use crate::scale::plot_scale;
+use crate::spectrum::common::*;
+use crate::*;
+use scales::prelude::*;
+
+pub fn plot_analyzer(analyzer: &AnalyzerModel, width: f64, height: f64) -> AnalyzerGraph {
+    let x_scale = PixelScale::new(0.0, width);
+    let y_scale = PixelScale::inverted(0.0, height);
+    let x_conv = (x_scale.clone(), analyzer.frequency_scale.scale.clone());
+    let y_conv = (y_scale.clone(), analyzer.level_scale.scale.clone());
+
+    let curve = |spectrum: &Spectrum| match analyzer.bands {
+        Some(bands) => plot_bands(spectrum, bands, &x_conv, &y_conv)
+            .into_iter()
+            .map(|bar| (bar.x + bar.width / 2.0, bar.y))
+            .collect(),
+        None => plot_spectrum(spectrum, &x_conv, &y_conv),
+    };
+
+    let (trace, bars) = match (&analyzer.mode, analyzer.bands) {
+        (DisplayMode::Line, _) => (curve(&analyzer.spectrum), vec![]),
+        (DisplayMode::Bars, Some(bands)) => {
+            let bars = plot_bands(&analyzer.spectrum, bands, &x_conv, &y_conv);
+            (vec![], bars)
+        }
+        (DisplayMode::Bars, None) => {
+            let bars = plot_spectrum(&analyzer.spectrum, &x_conv, &y_conv)
+                .into_iter()
+                .map(|(x, y)| Bounds {
+                    x,
+                    y,
+                    width: 1.0,
+                    height: height - y,
+                })
+                .collect();
+            (vec![], bars)
+        }
+    };
+
+    let peaks = analyzer.peaks.as_ref().map(curve).unwrap_or_default();
+    let frozen = analyzer.frozen.as_ref().map(curve).unwrap_or_default();
+
+    let frequency_format = LabelFormat::FrequencyShort(true);
+    let level_format = LabelFormat::GainShort(false);
+
+    AnalyzerGraph {
+        trace,
+        bars,
+        peaks,
+        frozen,
+        frequency_scale: plot_scale(
+            &analyzer.frequency_scale,
+            &x_scale,
+            height,
+            Some(&frequency_format),
+        ),
+        level_scale: plot_scale(&analyzer.level_scale, &y_scale, width, Some(&level_format)),
+    }
+}
+
+/// Plots fractional-octave bands as bars reaching from the bottom of the plot to their level.
+pub fn plot_bands(
+    spectrum: &Spectrum,
+    bands: OctaveBands,
+    x_conv: &(PixelScale, FreqScale),
+    y_conv: &(PixelScale, GainScale),
+) -> Vec<Bounds> {
+    let (min_frequency, max_frequency) = (x_conv.1.min(), x_conv.1.max());
+    let (min_gain, max_gain) = (y_conv.1.min(), y_conv.1.max());
+    let bottom = y_conv.convert_back(min_gain);
+
+    spectrum
+        .bands(bands, min_frequency, max_frequency)
+        .into_iter()
+        .map(|band| {
+            let left = x_conv.convert_back(band.lower.max(min_frequency)).floor();
+            let right = x_conv.convert_back(band.upper.min(max_frequency)).floor();
+            let y = y_conv.convert_back(band.level.max(min_gain).min(max_gain));
+            Bounds {
+                x: left,
+                y,
+                width: right - left,
+                height: bottom - y,
+            }
+        })
+        .collect()
+}
+
+/// Maps a spectrum onto a logarithmic frequency axis, e.g. the one of an
+/// [`EqModel`](crate::eq::EqModel). Bins that end up in the same pixel column are reduced to
+/// their maximum, bins outside the frequency range are dropped.
+pub fn plot_spectrum(
+    spectrum: &Spectrum,
+    x_conv: &(PixelScale, FreqScale),
+    y_conv: &(PixelScale, GainScale),
+) -> Vec<(X, Y)> {
+    let (min_frequency, max_frequency) = (x_conv.1.min(), x_conv.1.max());
+    let (min_gain, max_gain) = (y_conv.1.min(), y_conv.1.max());
+
+    let mut columns: Vec<(X, Gain)> = vec![];
+    for (bin, magnitude) in spectrum.magnitudes.iter().enumerate() {
+        let frequency = spectrum.frequency(bin);
+        if frequency < min_frequency || frequency > max_frequency {
+            continue;
+        }
+        let x = x_conv.convert_back(frequency).floor();
+        match columns.last_mut() {
+            Some((last_x, gain)) if (*last_x - x).abs() < f64::EPSILON => {
+                *gain = gain.max(*magnitude)
+            }
+            _ => columns.push((x, *magnitude)),
+        }
+    }
+
+    columns
+        .into_iter()
+        .map(|(x, gain)| (x, y_conv.convert_back(gain.max(min_gain).min(max_gain))))
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_bins_are_reduced_per_column() {
+        let magnitudes = (0..2_401).map(|bin| -(bin as f64) / 100.0).collect();
+        let spectrum = Spectrum::new(10.0, magnitudes);
+        let x_conv = (PixelScale::new(0.0, 100.0), FreqScale::new(20.0, 20_000.0));
+        let y_conv = (PixelScale::inverted(0.0, 100.0), GainScale::new(-20.0, 0.0));
+
+        let points = plot_spectrum(&spectrum, &x_conv, &y_conv);
+
+        assert!(points.len() <= 101);
+        assert!(points.windows(2).all(|w| w[0].0 < w[1].0));
+        // 20 Hz is the loudest bin and sits at the left edge
+        assert!((points[0].0 - 0.0).abs() < f64::EPSILON);
+        assert!((points[0].1 - 0.1).abs() < 1e-9);
+        // everything above 2 kHz is below the range and clamped to the bottom
+        assert!((points[points.len() - 1].1 - 100.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_plot_analyzer_modes() {
+        let mut analyzer = AnalyzerModel::default();
+        analyzer.update(Spectrum::new(1.0, vec![-45.0; 24_001]), None);
+        analyzer.mode = DisplayMode::Bars;
+        analyzer.bands = Some(OctaveBands::Sixth);
+
+        let graph = analyzer.plot(600.0, 90.0);
+
+        assert!(graph.trace.is_empty());
+        assert_eq!(61, graph.bars.len());
+        assert!(graph.bars.iter().all(|b| b.y + b.height <= 90.0 + 1e-9));
+        assert!(graph.frozen.is_empty());
+
+        analyzer.freeze();
+        analyzer.mode = DisplayMode::Line;
+        analyzer.bands = None;
+
+        let graph = analyzer.plot(600.0, 90.0);
+
+        assert!(graph.bars.is_empty());
+        assert_eq!(graph.trace, graph.frozen);
+        assert!((graph.trace[0].1 - 45.0).abs() < 1e-9);
+        assert_eq!("1 kHz", graph.frequency_scale.labels[5].text);
+    }
+}
@@ -0,0 +1,232 @@
+use crate::spectrum::common::Spectrum;
+use crate::*;
+use scales::prelude::*;
+
+pub type Rgb = (u8, u8, u8);
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum ColorMap {
+    Grayscale,
+    /// Black through red and yellow to white.
+    Heat,
+    /// An approximation of matplotlib's perceptually uniform viridis map.
+    Viridis,
+    /// Evenly spaced color stops from lowest to highest level.
+    Custom(Vec<Rgb>),
+}
+
+const GRAYSCALE: [Rgb; 2] = [(0, 0, 0), (255, 255, 255)];
+const HEAT: [Rgb; 5] = [
+    (0, 0, 0),
+    (128, 0, 0),
+    (255, 64, 0),
+    (255, 200, 0),
+    (255, 255, 255),
+];
+const VIRIDIS: [Rgb; 5] = [
+    (68, 1, 84),
+    (59, 82, 139),
+    (33, 145, 140),
+    (94, 201, 98),
+    (253, 231, 37),
+];
+
+impl ColorMap {
+    fn stops(&self) -> &[Rgb] {
+        match self {
+            ColorMap::Grayscale => &GRAYSCALE,
+            ColorMap::Heat => &HEAT,
+            ColorMap::Viridis => &VIRIDIS,
+            ColorMap::Custom(stops) => stops,
+        }
+    }
+
+    /// Maps an intensity between 0 and 1 to a color by interpolating linearly between stops.
+    pub fn color(&self, intensity: Intensity) -> Rgb {
+        let stops = self.stops();
+        match stops.len() {
+            0 => return (0, 0, 0),
+            1 => return stops[0],
+            _ => (),
+        }
+        let position = intensity.clamp(0.0, 1.0) * (stops.len() - 1) as f64;
+        let index = (position.floor() as usize).min(stops.len() - 2);
+        let t = position - index as f64;
+        let (from, to) = (stops[index], stops[index + 1]);
+        let mix = |a: u8, b: u8| (a as f64 + (b as f64 - a as f64) * t).round() as u8;
+        (mix(from.0, to.0), mix(from.1, to.1), mix(from.2, to.2))
+    }
+}
+
+/// The direction the image moves in. New lines enter at the opposite edge.
+#[derive(Debug, PartialEq, Clone)]
+pub enum ScrollDirection {
+    Left,
+    Right,
+    Up,
+    Down,
+}
+
+/// A scrolling spectrogram. Each spectrum becomes one line of pixels across the frequency axis,
+/// which runs vertically when scrolling left or right and horizontally otherwise.
+#[derive(Debug, PartialEq, Clone)]
+pub struct SpectrogramModel {
+    pub frequency_scale: FreqScale,
+    pub min_level: Gain,
+    pub max_level: Gain,
+    pub color_map: ColorMap,
+    pub direction: ScrollDirection,
+}
+
+impl SpectrogramModel {
+    pub fn new(frequency_scale: FreqScale, min_level: Gain, max_level: Gain) -> SpectrogramModel {
+        SpectrogramModel {
+            frequency_scale,
+            min_level,
+            max_level,
+            color_map: ColorMap::Heat,
+            direction: ScrollDirection::Left,
+        }
+    }
+
+    /// Whether the image scrolls horizontally, i.e. frequency runs along the vertical axis.
+    pub fn is_horizontal(&self) -> bool {
+        matches!(
+            self.direction,
+            ScrollDirection::Left | ScrollDirection::Right
+        )
+    }
+
+    /// The intensity of each of `length` pixels along the frequency axis, from lowest to highest
+    /// frequency. Pixels spanning several bins show the loudest one, pixels between bins are
+    /// interpolated.
+    pub fn intensities(&self, spectrum: &Spectrum, length: usize) -> Vec<Intensity> {
+        let conv = (PixelScale::new(0.0, length as f64), &self.frequency_scale);
+        let magnitudes = &spectrum.magnitudes;
+        let last_bin = match magnitudes.len() {
+            0 => return vec![0.0; length],
+            len => len - 1,
+        };
+        let range = self.max_level - self.min_level;
+
+        (0..length)
+            .map(|pixel| {
+                let lower = conv.convert(pixel as f64) / spectrum.bin_width;
+                let upper = conv.convert(pixel as f64 + 1.0) / spectrum.bin_width;
+                let first = lower.ceil() as usize;
+                let end = (upper.ceil() as usize).min(last_bin + 1);
+                let level = if first < end {
+                    magnitudes[first..end]
+                        .iter()
+                        .fold(f64::NEG_INFINITY, |a, b| a.max(*b))
+                } else {
+                    let center = conv.convert(pixel as f64 + 0.5) / spectrum.bin_width;
+                    let below = (center.floor() as usize).min(last_bin);
+                    let above = (below + 1).min(last_bin);
+                    let t = (center - below as f64).clamp(0.0, 1.0);
+                    magnitudes[below] + (magnitudes[above] - magnitudes[below]) * t
+                };
+                ((level - self.min_level) / range).clamp(0.0, 1.0)
+            })
+            .collect()
+    }
+
+    /// RGBA pixel data of the line a spectrum is drawn as, in canvas order, i.e. from top to
+    /// bottom or left to right.
+    pub fn line(&self, spectrum: &Spectrum, length: usize) -> Vec<u8> {
+        let mut intensities = self.intensities(spectrum, length);
+        if self.is_horizontal() {
+            // low frequencies at the bottom
+            intensities.reverse();
+        }
+        intensities
+            .into_iter()
+            .flat_map(|intensity| {
+                let (r, g, b) = self.color_map.color(intensity);
+                vec![r, g, b, 255]
+            })
+            .collect()
+    }
+
+    /// Where a new line is drawn on a canvas of the given size and by how many pixels the
+    /// existing image is shifted before.
+    pub fn line_bounds(&self, width: f64, height: f64) -> (Bounds, (X, Y)) {
+        let (x, y, line_width, line_height) = match self.direction {
+            ScrollDirection::Left => (width - 1.0, 0.0, 1.0, height),
+            ScrollDirection::Right => (0.0, 0.0, 1.0, height),
+            ScrollDirection::Up => (0.0, height - 1.0, width, 1.0),
+            ScrollDirection::Down => (0.0, 0.0, width, 1.0),
+        };
+        let shift = match self.direction {
+            ScrollDirection::Left => (-1.0, 0.0),
+            ScrollDirection::Right => (1.0, 0.0),
+            ScrollDirection::Up => (0.0, -1.0),
+            ScrollDirection::Down => (0.0, 1.0),
+        };
+        let bounds = Bounds {
+            x,
+            y,
+            width: line_width,
+            height: line_height,
+        };
+        (bounds, shift)
+    }
+}
+
+impl Default for SpectrogramModel {
+    fn default() -> Self {
+        SpectrogramModel::new(FreqScale::new(20.0, 20_000.0), -90.0, 0.0)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_color_map() {
+        assert_eq!((0, 0, 0), ColorMap::Grayscale.color(-1.0));
+        assert_eq!((128, 128, 128), ColorMap::Grayscale.color(0.5));
+        assert_eq!((255, 64, 0), ColorMap::Heat.color(0.5));
+        assert_eq!((253, 231, 37), ColorMap::Viridis.color(1.0));
+        let custom = ColorMap::Custom(vec![(0, 0, 255)]);
+        assert_eq!((0, 0, 255), custom.color(0.3));
+    }
+
+    #[test]
+    fn test_bins_to_pixels() {
+        let mut model = SpectrogramModel::new(FreqScale::new(10.0, 10_000.0), -60.0, 0.0);
+        // level rises with frequency by 1 dB per bin up to 0 dB at 60 Hz
+        let magnitudes = (0..=10_000)
+            .map(|bin| (bin as f64 - 60.0).min(0.0))
+            .collect();
+        let spectrum = Spectrum::new(1.0, magnitudes);
+
+        let intensities = model.intensities(&spectrum, 3);
+        assert_eq!(3, intensities.len());
+        // the lowest decade contains the bins from 10 to 100 Hz, the loudest is at 0 dB
+        assert!((intensities[0] - 1.0).abs() < 1e-9);
+
+        model.max_level = 20.0;
+        let line = model.line(&spectrum, 3);
+        assert_eq!(12, line.len());
+        // low frequencies end up at the bottom when scrolling horizontally
+        assert_eq!(&line[8..12], &line[0..4]);
+
+        model.direction = ScrollDirection::Up;
+        let (bounds, shift) = model.line_bounds(300.0, 100.0);
+        assert_eq!((0.0, -1.0), shift);
+        assert!((bounds.y - 99.0).abs() < f64::EPSILON);
+    }
+
+    #[test]
+    fn test_interpolates_between_bins() {
+        let model = SpectrogramModel::new(FreqScale::new(110.0, 190.0), -100.0, 0.0);
+        let spectrum = Spectrum::new(100.0, vec![-100.0, -100.0, 0.0]);
+
+        let intensities = model.intensities(&spectrum, 4);
+
+        assert!(intensities.windows(2).all(|w| w[0] <= w[1]));
+        assert!(intensities[0] > 0.0 && intensities[3] < 1.0);
+    }
+}
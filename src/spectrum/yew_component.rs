@@ -0,0 +1,243 @@
+use crate::js_utils::*;
+use crate::spectrum::*;
+use derivative::*;
+use wasm_bindgen::prelude::*;
+use web_sys::HtmlCanvasElement;
+use yew::prelude::*;
+
+pub struct Analyzer {
+    props: Props,
+    link: ComponentLink<Self>,
+    canvas: NodeRef,
+    renderer: Option<CanvasAnalyzerRenderer>,
+    render_callback: Closure<dyn FnMut()>,
+    needs_repaint: bool,
+    /// The trace frozen by the user, kept when the host pushes new spectra.
+    frozen: Option<Spectrum>,
+}
+
+#[derive(Derivative, Properties)]
+#[derivative(Debug, Clone, PartialEq)]
+pub struct Props {
+    pub id: String,
+    pub analyzer: AnalyzerModel,
+    pub width: f64,
+    pub height: f64,
+    /// Called with the new freeze state when the user double clicks the analyzer.
+    #[derivative(PartialEq = "ignore")]
+    pub on_freeze: Option<Callback<bool>>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Msg {
+    Render,
+    ToggleFreeze,
+}
+
+impl Component for Analyzer {
+    type Message = Msg;
+
+    type Properties = Props;
+
+    fn create(props: Self::Properties, link: ComponentLink<Self>) -> Self {
+        let cb_link = link.clone();
+        let render_callback =
+            Closure::wrap(Box::new(move || cb_link.send_message(Msg::Render)) as Box<dyn FnMut()>);
+        Analyzer {
+            frozen: props.analyzer.frozen.clone(),
+            props,
+            link,
+            canvas: NodeRef::default(),
+            renderer: None,
+            render_callback,
+            needs_repaint: false,
+        }
+    }
+
+    fn update(&mut self, msg: Self::Message) -> ShouldRender {
+        match msg {
+            Msg::Render => self.render(),
+            Msg::ToggleFreeze => self.toggle_freeze(),
+        }
+        false
+    }
+
+    fn change(&mut self, props: Self::Properties) -> ShouldRender {
+        if props != self.props {
+            self.frozen = next_frozen(&self.frozen, &self.props, &props);
+            self.props = props;
+            self.props.analyzer.frozen = self.frozen.clone();
+            true
+        } else {
+            false
+        }
+    }
+
+    fn view(&self) -> Html {
+        let double_click_callback = self.link.callback(|_| Msg::ToggleFreeze);
+        html! {
+            <canvas id={self.props.id.clone()} class="analyzer" ref=self.canvas.clone()
+            width={self.props.width} height={self.props.height} ondblclick={double_click_callback}/>
+        }
+    }
+
+    fn rendered(&mut self, first_render: bool) {
+        if first_render {
+            self.renderer = self
+                .canvas
+                .cast::<HtmlCanvasElement>()
+                .and_then(CanvasAnalyzerRenderer::new);
+        }
+
+        self.schedule_repaint();
+    }
+}
+
+impl Analyzer {
+    fn render(&mut self) {
+        self.needs_repaint = false;
+        if let Some(renderer) = self.renderer.as_ref() {
+            renderer.render_to_canvas(&self.props.analyzer);
+        }
+    }
+
+    fn schedule_repaint(&mut self) {
+        if !self.needs_repaint {
+            self.needs_repaint = true;
+            request_animation_frame(&self.render_callback);
+        }
+    }
+
+    fn toggle_freeze(&mut self) {
+        let analyzer = &mut self.props.analyzer;
+        if analyzer.is_frozen() {
+            analyzer.unfreeze();
+        } else {
+            analyzer.freeze();
+        }
+        let frozen = analyzer.is_frozen();
+        self.frozen = analyzer.frozen.clone();
+        self.schedule_repaint();
+        if let Some(Callback::Callback(fun)) = &self.props.on_freeze {
+            fun(frozen);
+        }
+    }
+}
+
+/// The frozen trace for new props. A change of the host's frozen trace is taken over, otherwise
+/// the one frozen by the user is kept.
+fn next_frozen(current: &Option<Spectrum>, previous: &Props, props: &Props) -> Option<Spectrum> {
+    if props.analyzer.frozen != previous.analyzer.frozen {
+        props.analyzer.frozen.clone()
+    } else {
+        current.clone()
+    }
+}
+
+pub struct Spectrogram {
+    props: SpectrogramProps,
+    canvas: NodeRef,
+    renderer: Option<CanvasSpectrogramRenderer>,
+}
+
+/// The spectrogram draws a new line whenever it receives a spectrum that differs from the last
+/// one, so `spectrum` should be updated once per analyzed frame.
+#[derive(Debug, Clone, PartialEq, Properties)]
+pub struct SpectrogramProps {
+    pub id: String,
+    pub spectrogram: SpectrogramModel,
+    pub spectrum: Spectrum,
+    pub width: f64,
+    pub height: f64,
+}
+
+impl Component for Spectrogram {
+    type Message = ();
+
+    type Properties = SpectrogramProps;
+
+    fn create(props: Self::Properties, _link: ComponentLink<Self>) -> Self {
+        Spectrogram {
+            props,
+            canvas: NodeRef::default(),
+            renderer: None,
+        }
+    }
+
+    fn update(&mut self, _msg: Self::Message) -> ShouldRender {
+        false
+    }
+
+    fn change(&mut self, props: Self::Properties) -> ShouldRender {
+        if props == self.props {
+            return false;
+        }
+        let resized = props.width != self.props.width || props.height != self.props.height;
+        let redirected = props.spectrogram.direction != self.props.spectrogram.direction;
+        let spectrum_changed = props.spectrum != self.props.spectrum;
+        self.props = props;
+
+        if resized {
+            // the canvas is recreated with the new size, the history is lost anyway
+            self.renderer = None;
+            return true;
+        }
+        if let Some(renderer) = &self.renderer {
+            if redirected {
+                renderer.clear();
+            }
+            if spectrum_changed {
+                renderer.push_spectrum(&self.props.spectrogram, &self.props.spectrum);
+            }
+        }
+        false
+    }
+
+    fn view(&self) -> Html {
+        html! {
+            <canvas id={self.props.id.clone()} class="spectrogram" ref=self.canvas.clone()
+            width={self.props.width} height={self.props.height}/>
+        }
+    }
+
+    fn rendered(&mut self, _first_render: bool) {
+        if self.renderer.is_none() {
+            self.renderer = self
+                .canvas
+                .cast::<HtmlCanvasElement>()
+                .and_then(CanvasSpectrogramRenderer::new);
+            if let Some(renderer) = &self.renderer {
+                renderer.clear();
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_freeze_survives_new_spectra() {
+        let previous = Props {
+            id: "analyzer".to_owned(),
+            analyzer: AnalyzerModel::default(),
+            width: 300.0,
+            height: 200.0,
+            on_freeze: None,
+        };
+        let trace = Some(Spectrum::new(10.0, vec![0.5; 8]));
+
+        let mut props = previous.clone();
+        props
+            .analyzer
+            .update(Spectrum::new(10.0, vec![0.25; 8]), None);
+        assert_eq!(next_frozen(&trace, &previous, &props), trace);
+
+        props.analyzer.frozen = Some(Spectrum::new(10.0, vec![1.0; 8]));
+        assert_eq!(
+            next_frozen(&trace, &previous, &props),
+            props.analyzer.frozen
+        );
+    }
+}
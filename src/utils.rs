@@ -26,6 +26,19 @@ fn format_frequency_with_digits(frequency: f64, with_unit: bool, digits: usize)
     }
 }
 
+/// Converts an amplitude to dB, where 1.0 corresponds to 0 dB.
+pub fn to_db(amplitude: f64) -> f64 {
+    if amplitude > 0.0 {
+        20.0 * amplitude.log10()
+    } else {
+        f64::NEG_INFINITY
+    }
+}
+
+pub fn to_amplitude(gain: f64) -> f64 {
+    10f64.powf(gain / 20.0)
+}
+
 pub fn format_gain(gain: f64, with_unit: bool) -> String {
     let digits = if (gain.abs() * 10.0).round() >= 100.0 {
         0
@@ -61,6 +74,39 @@ pub fn format_q(q: f64) -> String {
     format!("{:.*}", 2 - (q.log10().ceil() as usize), q)
 }
 
+pub fn format_time(seconds: f64, with_unit: bool) -> String {
+    let total = seconds.abs().round() as u64;
+    let sign = if seconds < 0.0 && total > 0 { "-" } else { "" };
+    let (hours, minutes, seconds) = (total / 3600, (total / 60) % 60, total % 60);
+
+    if hours > 0 {
+        format!("{}{}:{:02}:{:02}", sign, hours, minutes, seconds)
+    } else if minutes > 0 {
+        format!("{}{}:{:02}", sign, minutes, seconds)
+    } else if with_unit {
+        format!("{}{} s", sign, seconds)
+    } else {
+        format!("{}{}", sign, seconds)
+    }
+}
+
+pub fn filter_markers(markers: &[f64], min: f64, max: f64, incl: bool) -> Vec<f64> {
+    let iter = markers.iter();
+    if incl {
+        iter.filter_map(|m| {
+            if &min <= m && m <= &max {
+                Some(*m)
+            } else {
+                None
+            }
+        })
+        .collect()
+    } else {
+        iter.filter_map(|m| if &min < m && m < &max { Some(*m) } else { None })
+            .collect()
+    }
+}
+
 pub trait Ignore {
     fn ignore(self);
 }
@@ -100,4 +146,19 @@ mod test {
         assert_eq!("+9.9 dB", format_gain(9.94999, true));
         assert_eq!("+10 dB", format_gain(9.95, true));
     }
+
+    #[test]
+    fn test_db_conversion() {
+        assert_eq!(f64::NEG_INFINITY, to_db(0.0));
+        assert!((to_db(0.5) + 6.0206).abs() < 1e-4);
+        assert!((to_amplitude(to_db(0.25)) - 0.25).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_format_time() {
+        assert_eq!("0 s", format_time(-0.4, true));
+        assert_eq!("-45", format_time(-45.0, false));
+        assert_eq!("-1:30", format_time(-90.0, true));
+        assert_eq!("1:00:05", format_time(3605.0, false));
+    }
 }